@@ -2,9 +2,12 @@
 
 // use crate::prelude::*; // see lib.rs
 use crate::{
-    app::{render, state::App},
+    app::{
+        render,
+        state::{App, Engine},
+    },
     config,
-    gol::{life, utils},
+    gol::{life::TILE_SIZE, utils},
 };
 
 use rfd::FileDialog;
@@ -75,9 +78,26 @@ impl ApplicationHandler for App {
             }
 
             WindowEvent::MouseInput { state, button, .. } => {
-                // Left mouse button for panning
+                // Left mouse button for panning, or scrubbing when pressed on the timeline bar (paused only),
+                // or selecting the cells of the next soup with Shift held
                 if button == MouseButton::Left {
-                    self.is_panning = state == ElementState::Pressed;
+                    let pressed = state == ElementState::Pressed;
+                    let timeline = self.cursor_pos.and_then(|pos| self.timeline_fraction(pos));
+                    if let (true, Some(fraction)) = (pressed, timeline) {
+                        self.scrubbing = true;
+                        self.jump_to_timeline(fraction);
+                        return;
+                    }
+                    self.scrubbing = false;
+                    if pressed && self.modifiers.shift_key() {
+                        self.start_selection();
+                        return;
+                    }
+                    if !pressed && self.selecting.is_some() {
+                        self.end_selection();
+                        return;
+                    }
+                    self.is_panning = pressed;
                     if !self.is_panning {
                         // Release: clear last mouse position
                         self.last_mouse_pos = None;
@@ -86,6 +106,15 @@ impl ApplicationHandler for App {
             }
 
             WindowEvent::CursorMoved { position, .. } => {
+                self.cursor_pos = Some((position.x as f32, position.y as f32));
+                if self.scrubbing {
+                    self.jump_to_timeline(position.x as f32 / self.surface_w.max(1) as f32);
+                    return;
+                }
+                if self.selecting.is_some() {
+                    self.update_selection();
+                    return;
+                }
                 // Handle panning when left mouse button is held
                 if self.is_panning {
                     if let Some((last_x, last_y)) = self.last_mouse_pos {
@@ -163,9 +192,11 @@ impl ApplicationHandler for App {
                     return;
                 }
 
-                // `ESC` : exit fullscreen or quit application
+                // `ESC` : clear the selection, exit fullscreen or quit application
                 if matches!(logical_key, Key::Named(NamedKey::Escape)) {
-                    if self.full_screen {
+                    if self.clear_selection() {
+                        log::info!("Selection cleared");
+                    } else if self.full_screen {
                         // In fullscreen: exit to windowed mode
                         self.full_screen = false;
                         if let Some(window) = &self.window {
@@ -178,6 +209,110 @@ impl ApplicationHandler for App {
                     return;
                 }
 
+                // `Space` : pause / resume, `N` : one generation then pause
+                if matches!(logical_key, Key::Named(NamedKey::Space)) {
+                    self.toggle_pause();
+                    return;
+                }
+                if matches!(logical_key.as_ref(), Key::Character(s) if s.eq_ignore_ascii_case("n")) && !self.modifiers.control_key() {
+                    self.single_step();
+                    return;
+                }
+
+                // `,` / `.` : half / double speed (generations per second), `W` : warp (as fast as possible)
+                if matches!(logical_key.as_ref(), Key::Character(",") | Key::Character("<")) {
+                    self.change_speed(0.5);
+                    return;
+                }
+                if matches!(logical_key.as_ref(), Key::Character(".") | Key::Character(">")) {
+                    self.change_speed(2.0);
+                    return;
+                }
+                if matches!(logical_key.as_ref(), Key::Character(s) if s.eq_ignore_ascii_case("w")) && !self.modifiers.control_key() {
+                    self.toggle_warp();
+                    return;
+                }
+
+                // `L` : how long until the board is periodic, and what is left
+                if matches!(logical_key.as_ref(), Key::Character(s) if s.eq_ignore_ascii_case("l")) && !self.modifiers.control_key() {
+                    self.analyse_lifespan(config::LIFESPAN_MAX_GENERATIONS);
+                    return;
+                }
+
+                // `S` : new random soup (new seed, same size or selection, density and symmetry)
+                if matches!(logical_key.as_ref(), Key::Character(s) if s.eq_ignore_ascii_case("s")) && !self.modifiers.control_key() {
+                    if let Err(e) = self.load_soup(None) {
+                        let error_msg = format!("Failed to create soup: {}", e);
+                        log::error!("{}", error_msg);
+                        self.set_error(error_msg, 5);
+                    }
+                    return;
+                }
+
+                // `P` : where the next pattern goes (auto, centred, absolute, cursor)
+                if matches!(logical_key.as_ref(), Key::Character(s) if s.eq_ignore_ascii_case("p")) && !self.modifiers.control_key() {
+                    self.cycle_placement();
+                    return;
+                }
+
+                // `C` : census of the objects on the board
+                if matches!(logical_key.as_ref(), Key::Character(s) if s.eq_ignore_ascii_case("c")) && !self.modifiers.control_key() {
+                    self.take_census();
+                    return;
+                }
+
+                // `Ctrl+E` : write the statistics to CSV, `G` : population graph
+                if matches!(logical_key.as_ref(), Key::Character(s) if s.eq_ignore_ascii_case("e")) && self.modifiers.control_key() {
+                    self.export_stats();
+                    return;
+                }
+                if matches!(logical_key.as_ref(), Key::Character(s) if s.eq_ignore_ascii_case("g")) && !self.modifiers.control_key() {
+                    self.toggle_stats_graph();
+                    return;
+                }
+
+                // `Backspace` : back one recorded generation (pauses), `Home` : oldest recorded generation, `End` : newest one and run again
+                if matches!(logical_key, Key::Named(NamedKey::Backspace)) {
+                    self.step_back();
+                    return;
+                }
+                if matches!(logical_key, Key::Named(NamedKey::Home)) {
+                    self.jump_to_oldest();
+                    return;
+                }
+                if matches!(logical_key, Key::Named(NamedKey::End)) {
+                    self.resume();
+                    return;
+                }
+
+                // `[` / `]` : HashLife step exponent (2^k generations per step)
+                if matches!(logical_key.as_ref(), Key::Character("[")) {
+                    self.change_step_exponent(-1);
+                    return;
+                }
+                if matches!(logical_key.as_ref(), Key::Character("]")) {
+                    self.change_step_exponent(1);
+                    return;
+                }
+
+                // `E` : switch to the next engine able to run the rule, keeping the cells
+                if matches!(logical_key.as_ref(), Key::Character(s) if s.eq_ignore_ascii_case("e")) && !self.modifiers.control_key() {
+                    self.cycle_engine();
+                    return;
+                }
+
+                // `A` : show the tiles the dense engine computed during the last step
+                if matches!(logical_key.as_ref(), Key::Character(s) if s.eq_ignore_ascii_case("a")) && !self.modifiers.control_key() {
+                    self.toggle_active_tiles();
+                    return;
+                }
+
+                // `T` : cycle the topology (plane, torus, Klein bottle, cross-surface, sphere)
+                if matches!(logical_key.as_ref(), Key::Character(s) if s.eq_ignore_ascii_case("t")) && !self.modifiers.control_key() {
+                    self.cycle_topology();
+                    return;
+                }
+
                 // `Ctrl+Q` : quit application
                 if matches!(logical_key.as_ref(), Key::Character(s) if s.eq_ignore_ascii_case("q")) && self.modifiers.control_key() {
                     event_loop.exit();
@@ -204,10 +339,27 @@ impl ApplicationHandler for App {
                     return;
                 }
 
+                // `Ctrl+S` : save the board (cropped to its live cells) to a .rle file
+                if matches!(logical_key.as_ref(), Key::Character(s) if s.eq_ignore_ascii_case("s"))
+                    && self.modifiers.control_key()
+                    && let Some(path) = FileDialog::new()
+                        .add_filter("RLE files", &["rle"])
+                        .add_filter("Plaintext files", &["cells"])
+                        .add_filter("Life 1.05 files", &["lif"])
+                        .add_filter("Life 1.06 files", &["life"])
+                        .set_directory("rle/")
+                        .save_file()
+                    && let Err(e) = self.save_pattern(&path)
+                {
+                    let error_msg = format!("Failed to save pattern: {}", e);
+                    log::error!("{}", error_msg);
+                    self.set_error(error_msg, 5);
+                }
+
                 // `Ctrl+O` : to open .rle file
                 if matches!(logical_key.as_ref(), Key::Character(s) if s.eq_ignore_ascii_case("o"))
                     && self.modifiers.control_key()
-                    && let Some(path) = FileDialog::new().add_filter("RLE files", &["rle"]).set_directory("rle/").pick_file()
+                    && let Some(path) = FileDialog::new().add_filter("Pattern files", &["rle", "cells", "lif", "life"]).set_directory("rle/").pick_file()
                     && let Err(e) = self.load_pattern(&path)
                 {
                     let error_msg = format!("Failed to load pattern: {}", e);
@@ -244,27 +396,67 @@ impl ApplicationHandler for App {
             }
 
             WindowEvent::RedrawRequested => {
-                // Update the board & measure
-                let step_start = Instant::now();
-                life::step_life(&self.board_current, &mut self.board_next, self.board_width, self.board_height);
-                std::mem::swap(&mut self.board_current, &mut self.board_next);
-                let step_duration = step_start.elapsed();
-                self.perf_metrics.record_step(step_duration);
-
-                // Draw the current board with camera and zoom & measure
+                // Update the board & measure (as many generations as the speed asks for, none while paused)
+                self.advance();
+                self.update_title();
+
+                // Draw the current board (or the visible part of the universe) with camera and zoom & measure
+                let render_start = Instant::now();
+                let view = self.engine.is_unbounded().then(|| self.update_view());
+                self.sync_dense();
                 if let Some(pixels) = &mut self.pixels {
-                    let render_start = Instant::now();
-                    render::draw_board_with_camera(
-                        pixels,
-                        &self.board_current,
-                        self.board_width,
-                        self.board_height,
-                        self.camera_x,
-                        self.camera_y,
-                        self.zoom_level,
-                        self.surface_w,
-                        self.surface_h,
-                    );
+                    match view {
+                        Some(region) => render::draw_universe_with_camera(
+                            pixels,
+                            &self.view_cells,
+                            region,
+                            self.board_width,
+                            self.board_height,
+                            self.camera_x,
+                            self.camera_y,
+                            self.zoom_level,
+                            self.surface_w,
+                            self.surface_h,
+                        ),
+                        None => render::draw_board_with_camera(
+                            pixels,
+                            self.dense.cells(),
+                            (!self.rule.is_life_like()).then_some(self.board_states.as_slice()),
+                            (self.show_active_tiles && self.engine == Engine::Dense && self.rule.is_life_like()).then(|| self.dense.active_tiles()),
+                            self.board_width,
+                            self.board_height,
+                            &self.rule,
+                            self.camera_x,
+                            self.camera_y,
+                            self.zoom_level,
+                            self.surface_w,
+                            self.surface_h,
+                        ),
+                    }
+                    if let Some(selection) = self.selection {
+                        render::draw_selection(
+                            pixels,
+                            selection,
+                            self.board_width,
+                            self.board_height,
+                            self.camera_x,
+                            self.camera_y,
+                            self.zoom_level,
+                            self.surface_w,
+                            self.surface_h,
+                        );
+                    }
+                    if self.show_stats_graph {
+                        render::draw_population_graph(pixels, self.board_width, self.board_height, &self.stats);
+                    }
+
+                    // Timeline of the recorded generations while they are reviewed
+                    if self.paused
+                        && let (Some(oldest), Some(newest)) = (self.history.oldest(), self.history.newest())
+                    {
+                        render::draw_timeline(pixels, self.board_width, self.board_height, oldest, newest, self.generation);
+                    }
+                    pixels.render().unwrap();
                     let render_duration = render_start.elapsed();
                     self.perf_metrics.record_render(render_duration);
 
@@ -282,16 +474,56 @@ impl ApplicationHandler for App {
                     let fps_theoretical = if total.as_micros() > 0 { 1_000_000 / total.as_micros() } else { 0 };
 
                     log::info!(
-                        "Perf: step={:>6.2}ms (p95={:>6.2}ms) | render={:>6.2}ms | total={:>6.2}ms | theo_fps={:>4} | board={}x{} | zoom={:.2}",
+                        "Perf: step={:>6.2}ms (p95={:>6.2}ms) | render={:>6.2}ms | total={:>6.2}ms | theo_fps={:>4} | generation={} | population={} | engine={} | threads={} | board={}x{} | zoom={:.2}",
                         avg_step.as_secs_f64() * 1000.0,
                         p95_step.as_secs_f64() * 1000.0,
                         avg_render.as_secs_f64() * 1000.0,
                         total.as_secs_f64() * 1000.0,
                         fps_theoretical,
+                        self.generation,
+                        self.stats.latest().map(|row| row.population).unwrap_or_default(),
+                        self.engine.name(),
+                        self.workers.threads(),
                         self.board_width,
                         self.board_height,
                         self.zoom_level
                     );
+
+                    log::info!(
+                        "History: generation={}{} | recorded={}..{} ({} frames) | memory={:.1}/{} MiB",
+                        self.generation,
+                        if self.paused { " (paused)" } else { "" },
+                        self.history.oldest().unwrap_or_default(),
+                        self.history.newest().unwrap_or_default(),
+                        self.history.len(),
+                        self.history.bytes() as f64 / (1 << 20) as f64,
+                        self.history.budget() >> 20
+                    );
+                    if self.engine == Engine::Dense && self.rule.is_life_like() {
+                        let tiles = self.dense.active_tiles();
+                        log::info!("Dense: active_tiles={}/{} | tile={}x{}", tiles.active_count(), tiles.tile_count(), TILE_SIZE, TILE_SIZE);
+                    }
+                    if self.engine == Engine::HashLife {
+                        log::info!(
+                            "HashLife: generation={} | step=2^{} | population={} | bbox={:?} | nodes={}",
+                            self.hashlife.generation(),
+                            self.hashlife.step_exponent(),
+                            self.hashlife.population(),
+                            self.hashlife.bounding_box(),
+                            self.hashlife.node_count()
+                        );
+                    }
+                    if self.engine == Engine::Sparse {
+                        log::info!(
+                            "Sparse: generation={} | population={} | bbox={:?} | tiles={} | camera=({:.0}, {:.0})",
+                            self.sparse.generation(),
+                            self.sparse.population(),
+                            self.sparse.bounding_box(),
+                            self.sparse.tile_count(),
+                            self.camera_x,
+                            self.camera_y
+                        );
+                    }
                 }
             }
 
@@ -1,10 +1,15 @@
 // src/app/render.rs
 
 use crate::config;
+use crate::gol::{
+    life::ActiveTiles,
+    rule::{Neighbourhood, Rule},
+    stats::Statistics,
+};
 // use crate::prelude::*; // see lib.rs
 use pixels::Pixels;
 
-// Draw the visible portion of the board according to camera position and zoom
+// Draw the visible portion of the board according to camera position and zoom (call pixels.render() once overlays are drawn)
 ///
 // - `board_*`: Full simulation grid (FIXED size, e.g. 2000x1500)
 // - `camera_x/y`: Center of view in board coordinates
@@ -12,36 +17,53 @@ use pixels::Pixels;
 // - Pixels buffer size = board size (rendering buffer stays fixed)
 // - Window can be any size (surface texture scales automatically)
 // - Colors distinguish between: alive cells, dead cells inside board, and out-of-bounds area
-pub fn draw_board_with_camera(pixels: &mut Pixels, board_current: &[bool], board_width: u32, board_height: u32, camera_x: f32, camera_y: f32, zoom_level: f32, window_width: u32, window_height: u32) {
+// - `rule.topology()`: when edges are joined the seam is drawn around the board and the area beyond it
+//   shows "ghost" copies of the cells the topology glues there
+// - `cell_states`: cell states of a Generations rule or a rule table, dying states are drawn with a gradient (see state_palette())
+// - Hexagonal rules are drawn sheared like in Golly, with hexagon cells when zoomed in (see hex_cell())
+// - `active_tiles`: dead cells of the tiles computed by the last step are tinted (dense engine overlay)
+#[allow(clippy::too_many_arguments)]
+pub fn draw_board_with_camera(
+    pixels: &mut Pixels,
+    board_current: &[bool],
+    cell_states: Option<&[u8]>,
+    active_tiles: Option<&ActiveTiles>,
+    board_width: u32,
+    board_height: u32,
+    rule: &Rule,
+    camera_x: f64,
+    camera_y: f64,
+    zoom_level: f32,
+    window_width: u32,
+    window_height: u32,
+) {
     let frame = pixels.frame_mut();
+    let topology = rule.topology();
 
-    // Extract RGB components from config colors (RGBA format: 0xRRGGBBAA)
-    let color_alive = [
-        ((config::COLOR_CELL_ALIVE >> 24) & 0xFF) as u8,
-        ((config::COLOR_CELL_ALIVE >> 16) & 0xFF) as u8,
-        ((config::COLOR_CELL_ALIVE >> 8) & 0xFF) as u8,
-        (config::COLOR_CELL_ALIVE & 0xFF) as u8,
-    ];
-    let color_dead = [
-        ((config::COLOR_CELL_DEAD >> 24) & 0xFF) as u8,
-        ((config::COLOR_CELL_DEAD >> 16) & 0xFF) as u8,
-        ((config::COLOR_CELL_DEAD >> 8) & 0xFF) as u8,
-        (config::COLOR_CELL_DEAD & 0xFF) as u8,
-    ];
-    let color_out_of_bounds = [
-        ((config::COLOR_OUT_OF_BOUNDS >> 24) & 0xFF) as u8,
-        ((config::COLOR_OUT_OF_BOUNDS >> 16) & 0xFF) as u8,
-        ((config::COLOR_OUT_OF_BOUNDS >> 8) & 0xFF) as u8,
-        (config::COLOR_OUT_OF_BOUNDS & 0xFF) as u8,
-    ];
+    // One color per cell state: dead, alive, then the dying states of a Generations rule (or a rule table's colors)
+    let palette = state_palette(rule);
+    let color_alive = palette[1];
+    let color_out_of_bounds = config::COLOR_OUT_OF_BOUNDS.to_be_bytes();
+    let color_seam = config::COLOR_SEAM.to_be_bytes();
+    let color_ghost_alive = config::COLOR_GHOST_ALIVE.to_be_bytes();
+    let color_active_tile = config::COLOR_ACTIVE_TILE.to_be_bytes();
 
     // Calculate how many board cells fit in the window at current zoom
     let cells_visible_width = window_width as f32 / (config::CELL_SIZE as f32 * zoom_level);
     let cells_visible_height = window_height as f32 / (config::CELL_SIZE as f32 * zoom_level);
 
     // Calculate the top-left corner of the visible area in board coordinates
-    let view_left = camera_x - cells_visible_width / 2.0;
-    let view_top = camera_y - cells_visible_height / 2.0;
+    let view_left = camera_x as f32 - cells_visible_width / 2.0;
+    let view_top = camera_y as f32 - cells_visible_height / 2.0;
+
+    // The seam is one cell wide, but never thinner than one buffer pixel so it stays visible when zoomed out
+    let seam_w = (cells_visible_width / board_width as f32).max(1.0);
+    let seam_h = (cells_visible_height / board_height as f32).max(1.0);
+
+    // Hexagonal grid: rows are sheared around the middle one so the board stays where it was
+    let hexagonal = rule.neighbourhood() == Neighbourhood::Hexagonal;
+    let hexagons = board_width as f32 / cells_visible_width >= config::HEX_MIN_CELL_SIZE;
+    let middle_row = board_height as f32 / 2.0;
 
     // For each pixel in the rendering buffer, determine which board cell to show
     for buffer_y in 0..board_height {
@@ -49,22 +71,40 @@ pub fn draw_board_with_camera(pixels: &mut Pixels, board_current: &[bool], board
             // Map buffer pixel to board cell coordinate
             let board_cell_x = view_left + (buffer_x as f32 / board_width as f32) * cells_visible_width;
             let board_cell_y = view_top + (buffer_y as f32 / board_height as f32) * cells_visible_height;
+            let (board_cell_x, board_cell_y) = if hexagonal {
+                hex_cell(board_cell_x, board_cell_y, middle_row, hexagons)
+            } else {
+                (board_cell_x, board_cell_y)
+            };
 
             // Check if this cell is within the board bounds
-            let (is_alive, is_out_of_bounds) = if board_cell_x >= 0.0 && board_cell_y >= 0.0 && (board_cell_x as u32) < board_width && (board_cell_y as u32) < board_height {
+            let (state, is_out_of_bounds) = if board_cell_x >= 0.0 && board_cell_y >= 0.0 && (board_cell_x as u32) < board_width && (board_cell_y as u32) < board_height {
                 let cell_idx = ((board_cell_y as u32) * board_width + (board_cell_x as u32)) as usize;
-                (board_current.get(cell_idx).copied().unwrap_or(false), false)
+                let state = match cell_states {
+                    Some(states) => states.get(cell_idx).copied().unwrap_or(0),
+                    None => board_current.get(cell_idx).copied().unwrap_or(false) as u8,
+                };
+                (state, false)
             } else {
-                (false, true) // Outside board bounds
+                (0, true) // Outside board bounds
             };
 
             // Choose color based on cell state
-            let color = if is_alive {
-                &color_alive
-            } else if is_out_of_bounds {
+            let color = if !is_out_of_bounds {
+                match active_tiles {
+                    Some(tiles) if state == 0 && tiles.is_active(board_cell_x as u32, board_cell_y as u32) => &color_active_tile,
+                    _ => palette.get(state as usize).unwrap_or(&color_alive),
+                }
+            } else if !topology.wraps() {
                 &color_out_of_bounds
+            } else if board_cell_x >= -seam_w && board_cell_y >= -seam_h && board_cell_x < board_width as f32 + seam_w && board_cell_y < board_height as f32 + seam_h {
+                &color_seam
             } else {
-                &color_dead
+                // Beyond the seam: show the cell the topology glues there
+                match topology.map(board_cell_x.floor() as i64, board_cell_y.floor() as i64, board_width, board_height) {
+                    Some((x, y)) if board_current.get((y * board_width + x) as usize).copied().unwrap_or(false) => &color_ghost_alive,
+                    _ => &color_out_of_bounds,
+                }
             };
 
             // Draw the pixel
@@ -75,8 +115,29 @@ pub fn draw_board_with_camera(pixels: &mut Pixels, board_current: &[bool], board
             frame[pixel_idx + 3] = color[3]; // A
         }
     }
+}
 
-    pixels.render().unwrap();
+// Board coordinates of the cell drawn at (x, y) on the sheared hexagonal grid
+// Golly's hexagonal neighbourhood leaves out NE and SW: shifting each row half a cell to the left of the one
+// above it puts the 6 neighbours (NW, N above, W, E, S, SE below) around the cell.
+// With `hexagons` the point goes to the nearest cell centre (hexagonal cells, they reach into the rows above and
+// below), otherwise cells stay sheared squares.
+fn hex_cell(x: f32, y: f32, middle_row: f32, hexagons: bool) -> (f32, f32) {
+    let shift = |row: f32| (row - middle_row) / 2.0;
+    let row = y.floor();
+    if !hexagons {
+        return (x + shift(row), y);
+    }
+
+    let column = (x + shift(row)).floor();
+    let other_row = if y - row < 0.5 { row - 1.0 } else { row + 1.0 };
+    let other_column = (x + shift(other_row)).floor();
+    let distance = |column: f32, row: f32| (x - (column + 0.5 - shift(row))).powi(2) + (y - (row + 0.5)).powi(2);
+    if distance(other_column, other_row) < distance(column, row) {
+        (other_column + 0.5, other_row + 0.5)
+    } else {
+        (column + 0.5, row + 0.5)
+    }
 }
 
 // Legacy function for compatibility
@@ -85,14 +146,171 @@ pub fn draw_board(pixels: &mut Pixels, board_current: &[bool], buffer_width: u32
     draw_board_with_camera(
         pixels,
         board_current,
+        None,
+        None,
         buffer_width,
         buffer_height,
-        buffer_width as f32 / 2.0,
-        buffer_height as f32 / 2.0,
+        &Rule::life(),
+        buffer_width as f64 / 2.0,
+        buffer_height as f64 / 2.0,
         1.0,
         buffer_width * config::CELL_SIZE,
         buffer_height * config::CELL_SIZE,
     );
+    pixels.render().unwrap();
+}
+
+// Colors of the cell states: [dead, alive, first dying state .. last dying state]
+// Dying states of a Generations rule fade from COLOR_DYING_FIRST to COLOR_DYING_LAST
+// A rule table's @COLORS replace the colors of the states they list
+pub fn state_palette(rule: &Rule) -> Vec<[u8; 4]> {
+    let rgba = |color: u32| color.to_be_bytes();
+    let mut palette = vec![rgba(config::COLOR_CELL_DEAD), rgba(config::COLOR_CELL_ALIVE)];
+    let dying = rule.states().saturating_sub(2);
+    let (first, last) = (rgba(config::COLOR_DYING_FIRST), rgba(config::COLOR_DYING_LAST));
+    for i in 0..dying {
+        let t = if dying > 1 { i as f32 / (dying - 1) as f32 } else { 0.0 };
+        palette.push(std::array::from_fn(|c| (first[c] as f32 + (last[c] as f32 - first[c] as f32) * t).round() as u8));
+    }
+    if let Some(table) = rule.rule_table() {
+        for (color, rgb) in palette.iter_mut().zip(table.colors()) {
+            if let Some([r, g, b]) = *rgb {
+                *color = [r, g, b, 0xFF];
+            }
+        }
+    }
+    palette
+}
+
+// Draw the visible portion of an unbounded universe (sparse or HashLife engine)
+//
+// - `cells`, `region`: window (x, y, width, height) of the universe copied by App::update_view(), it covers the viewport
+// - `buffer_*`: size of the rendering buffer (the board size, even though the universe has no edge)
+// - Coordinates are computed in f64 so the view stays exact far away from the origin
+#[allow(clippy::too_many_arguments)]
+pub fn draw_universe_with_camera(
+    pixels: &mut Pixels,
+    cells: &[bool],
+    region: (i64, i64, u32, u32),
+    buffer_width: u32,
+    buffer_height: u32,
+    camera_x: f64,
+    camera_y: f64,
+    zoom_level: f32,
+    window_width: u32,
+    window_height: u32,
+) {
+    let frame = pixels.frame_mut();
+
+    // Extract RGB components from config colors (RGBA format: 0xRRGGBBAA)
+    let color_alive = config::COLOR_CELL_ALIVE.to_be_bytes();
+    let color_dead = config::COLOR_CELL_DEAD.to_be_bytes();
+
+    let (region_x, region_y, region_w, region_h) = region;
+    let cells_visible_width = window_width as f64 / (config::CELL_SIZE as f64 * zoom_level as f64);
+    let cells_visible_height = window_height as f64 / (config::CELL_SIZE as f64 * zoom_level as f64);
+    let view_left = camera_x - cells_visible_width / 2.0;
+    let view_top = camera_y - cells_visible_height / 2.0;
+
+    for buffer_y in 0..buffer_height {
+        // Row of the region shown on this line of the buffer (None if the region does not cover it)
+        let cell_y = (view_top + (buffer_y as f64 / buffer_height as f64) * cells_visible_height).floor() as i64 - region_y;
+        let row = (0..region_h as i64).contains(&cell_y).then(|| &cells[(cell_y as usize * region_w as usize)..][..region_w as usize]);
+
+        for buffer_x in 0..buffer_width {
+            let cell_x = (view_left + (buffer_x as f64 / buffer_width as f64) * cells_visible_width).floor() as i64 - region_x;
+            let is_alive = match row {
+                Some(row) if (0..region_w as i64).contains(&cell_x) => row[cell_x as usize],
+                _ => false,
+            };
+
+            let color = if is_alive { &color_alive } else { &color_dead };
+            let pixel_idx = ((buffer_y * buffer_width + buffer_x) * 4) as usize;
+            frame[pixel_idx..pixel_idx + 4].copy_from_slice(color);
+        }
+    }
+}
+
+// Outline of the selected cells (min_x, min_y, max_x, max_y, inclusive) over the board, with the same camera and zoom
+#[allow(clippy::too_many_arguments)]
+pub fn draw_selection(
+    pixels: &mut Pixels,
+    selection: (i64, i64, i64, i64),
+    buffer_width: u32,
+    buffer_height: u32,
+    camera_x: f64,
+    camera_y: f64,
+    zoom_level: f32,
+    window_width: u32,
+    window_height: u32,
+) {
+    let frame = pixels.frame_mut();
+    let color = config::COLOR_SELECTION.to_be_bytes();
+
+    let cells_visible_width = window_width as f64 / (config::CELL_SIZE as f64 * zoom_level as f64);
+    let cells_visible_height = window_height as f64 / (config::CELL_SIZE as f64 * zoom_level as f64);
+    let view_left = camera_x - cells_visible_width / 2.0;
+    let view_top = camera_y - cells_visible_height / 2.0;
+
+    // Buffer pixel where a cell starts, the selection ends where the cell after its last one starts
+    let to_x = |x: i64| ((x as f64 - view_left) / cells_visible_width * buffer_width as f64).floor() as i64;
+    let to_y = |y: i64| ((y as f64 - view_top) / cells_visible_height * buffer_height as f64).floor() as i64;
+    let (min_x, min_y, max_x, max_y) = selection;
+    let (left, top) = (to_x(min_x), to_y(min_y));
+    let (right, bottom) = ((to_x(max_x + 1) - 1).max(left), (to_y(max_y + 1) - 1).max(top));
+
+    let mut plot = |x: i64, y: i64| {
+        if (0..buffer_width as i64).contains(&x) && (0..buffer_height as i64).contains(&y) {
+            let pixel_idx = ((y as u32 * buffer_width + x as u32) * 4) as usize;
+            frame[pixel_idx..pixel_idx + 4].copy_from_slice(&color);
+        }
+    };
+    for x in left.max(0)..=right.min(buffer_width as i64 - 1) {
+        plot(x, top);
+        plot(x, bottom);
+    }
+    for y in top.max(0)..=bottom.min(buffer_height as i64 - 1) {
+        plot(left, y);
+        plot(right, y);
+    }
+}
+
+// Bar at the bottom of the buffer: the recorded generations from `oldest` (left) to `newest` (right), `current` is marked
+pub fn draw_timeline(pixels: &mut Pixels, buffer_width: u32, buffer_height: u32, oldest: u64, newest: u64, current: u64) {
+    let frame = pixels.frame_mut();
+    let bar_height = config::TIMELINE_HEIGHT.min(buffer_height);
+    let span = (newest - oldest).max(1) as f64;
+    let cursor_x = ((current.clamp(oldest, newest) - oldest) as f64 / span * (buffer_width - 1) as f64).round() as u32;
+
+    for y in buffer_height - bar_height..buffer_height {
+        for x in 0..buffer_width {
+            let color = if x.abs_diff(cursor_x) <= 1 { config::COLOR_TIMELINE_CURSOR } else { config::COLOR_TIMELINE };
+            let pixel_idx = ((y * buffer_width + x) * 4) as usize;
+            frame[pixel_idx..pixel_idx + 4].copy_from_slice(&color.to_be_bytes());
+        }
+    }
+}
+
+// Population of the last STATS_GRAPH_WIDTH generations in the top-right corner, scaled to the largest one
+pub fn draw_population_graph(pixels: &mut Pixels, buffer_width: u32, buffer_height: u32, stats: &Statistics) {
+    let frame = pixels.frame_mut();
+    let (graph_w, graph_h) = (config::STATS_GRAPH_WIDTH.min(buffer_width), config::STATS_GRAPH_HEIGHT.min(buffer_height));
+    let left = buffer_width - graph_w;
+    let populations: Vec<u64> = stats.rows().rev().take(graph_w as usize).map(|row| row.population).collect();
+    let max = populations.iter().copied().max().unwrap_or_default().max(1);
+
+    for x in 0..graph_w {
+        // Newest generation on the right
+        let height = match populations.get((graph_w - 1 - x) as usize) {
+            Some(&population) => ((population as f64 / max as f64) * (graph_h - 1) as f64).round() as u32 + 1,
+            None => 0,
+        };
+        for y in 0..graph_h {
+            let color = if graph_h - y <= height { config::COLOR_STATS_GRAPH } else { config::COLOR_STATS_BACKGROUND };
+            let pixel_idx = ((y * buffer_width + left + x) * 4) as usize;
+            frame[pixel_idx..pixel_idx + 4].copy_from_slice(&color.to_be_bytes());
+        }
+    }
 }
 
 // Draw error message overlay on the screen
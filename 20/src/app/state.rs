@@ -1,23 +1,107 @@
 // src/app/state.rs
 
 // use crate::prelude::*;
-use crate::{Result, app::perfs, config, gol::utils}; // see lib.rs
+use crate::{
+    Result,
+    app::perfs,
+    config,
+    gol::{
+        bitlife::BitBoard,
+        census::Census,
+        engine::{DenseBoard, LifeEngine},
+        hashlife::HashLife,
+        history::{self, History},
+        life,
+        lifespan::Lifespan,
+        ltl,
+        period::{PeriodDetector, Periodicity},
+        rule::Rule,
+        ruletable,
+        soup::{Soup, Symmetry},
+        sparse::SparseBoard,
+        stats::Statistics,
+        tally::{Signature, Tally},
+        topology::{MAX_BOARD, Topology, TopologyKind},
+        utils::{self, PatternMetadata},
+        workers::Workers,
+    },
+}; // see lib.rs
 
 use pixels::{Pixels, PixelsBuilder, SurfaceTexture, wgpu};
 use std::path::{Path, PathBuf};
 // use std::time::Duration;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use winit::keyboard::ModifiersState;
 use winit::window::Window;
 
+// Simulation back-end used by App::step()
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Engine {
+    Dense,     // gol::engine::DenseBoard, gol::life::step_life() on Vec<bool>
+    BitPacked, // gol::bitlife::BitBoard, 64 cells per u64
+    HashLife,  // gol::hashlife::HashLife, 2^step_exponent() generations per step
+    Sparse,    // gol::sparse::SparseBoard, 64x64 tiles allocated around live cells
+}
+
+impl Engine {
+    pub const ALL: [Engine; 4] = [Engine::Dense, Engine::BitPacked, Engine::HashLife, Engine::Sparse];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Engine::Dense => "dense",
+            Engine::BitPacked => "packed",
+            Engine::HashLife => "hashlife",
+            Engine::Sparse => "sparse",
+        }
+    }
+
+    // Unbounded engines simulate an infinite plane, the board is only used by the bounded ones
+    pub fn is_unbounded(&self) -> bool {
+        matches!(self, Engine::HashLife | Engine::Sparse)
+    }
+
+    pub fn supports(&self, rule: &Rule) -> bool {
+        match self {
+            Engine::Dense => true,
+            Engine::BitPacked => rule.is_moore_totalistic(),
+            Engine::HashLife => HashLife::supports(rule),
+            Engine::Sparse => SparseBoard::supports(rule),
+        }
+    }
+}
+
+// Where load_pattern() puts the pattern
+// Golly's coordinates have their origin at the center of the board (see App::origin())
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Placement {
+    Auto,     // Absolute when the file declares a position, Centred otherwise
+    Centred,  // Centred on the board, whatever the file says
+    Absolute, // Top-left corner at the #P / #R / #CXRLE Pos position of the file (0,0 when it has none), like Golly
+    Cursor,   // Centred on the cell under the mouse
+}
+
+impl Placement {
+    pub const ALL: [Placement; 4] = [Placement::Auto, Placement::Centred, Placement::Absolute, Placement::Cursor];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Placement::Auto => "auto",
+            Placement::Centred => "centred",
+            Placement::Absolute => "absolute",
+            Placement::Cursor => "cursor",
+        }
+    }
+}
+
 pub struct App {
     pub window: Option<&'static Window>,
     pub pixels: Option<Pixels<'static>>,
     pub last_frame: Instant,
-    pub board_width: u32,         // FIXED board size (simulation grid)
-    pub board_height: u32,        // FIXED board size (simulation grid)
-    pub board_current: Vec<bool>, // current grid of cells
-    pub board_next: Vec<bool>,    // next grid of cells
+    pub board_width: u32,           // FIXED board size (simulation grid), unless the rule declares a bounded grid
+    pub board_height: u32,          // FIXED board size (simulation grid), unless the rule declares a bounded grid
+    pub dense: DenseBoard,          // Engine::Dense board, also the copy of the cells rendered for every bounded engine
+    pub board_states: Vec<u8>,      // cell states (0 dead, 1 alive, 2.. dying) when the rule is not Life-like: Generations, Larger than Life, rule tables (dense engine only)
+    pub board_states_next: Vec<u8>, // next cell states
     pub full_screen: bool,
     pub pending_resize: Option<(u32, u32)>,
     pub surface_w: u32,                       // window size in pixels
@@ -28,24 +112,57 @@ pub struct App {
     pub perf_metrics: perfs::PerformanceMetrics,
     pub zoom_level: f32, // Current zoom level (1.0 = default, affects display only)
     pub zoom_max: f32,   // Maximum zoom level (dynamically calculated)
-    pub camera_x: f32,   // Camera position (center of view in board coordinates)
-    pub camera_y: f32,   // Camera position (center of view in board coordinates)
+    pub camera_x: f64,   // Camera position (center of view in board coordinates, f64 so far away cells stay exact)
+    pub camera_y: f64,   // Camera position (center of view in board coordinates, f64 so far away cells stay exact)
     // Panning state
-    pub is_panning: bool,                   // Is left mouse button held for panning?
-    pub last_mouse_pos: Option<(f32, f32)>, // Last mouse position for delta calculation
-    pub modifiers: ModifiersState,          // Current keyboard modifiers (Ctrl, Shift, Alt)
+    pub is_panning: bool,                        // Is left mouse button held for panning?
+    pub last_mouse_pos: Option<(f32, f32)>,      // Last mouse position for delta calculation
+    pub modifiers: ModifiersState,               // Current keyboard modifiers (Ctrl, Shift, Alt)
+    pub rule: Rule,                              // Active rule (B3/S23 unless the pattern says otherwise)
+    pub engine: Engine,                          // Which algorithm computes the next generation
+    pub packed: BitBoard,                        // Bit-packed copy of the board used by Engine::BitPacked
+    pub dense_stale: bool,                       // The bit-packed board is ahead of the dense one (see sync_dense())
+    pub workers: Workers,                        // Worker threads stepping horizontal bands, started by set_threads() (none = event-loop thread only)
+    pub hashlife: HashLife,                      // Unbounded quadtree universe used by Engine::HashLife, 2^step_exponent() generations per step
+    pub sparse: SparseBoard,                     // Unbounded tiled universe used by Engine::Sparse
+    pub view_cells: Vec<bool>,                   // Visible window of an unbounded universe, refreshed before each render
+    pub show_active_tiles: bool,                 // Overlay the tiles the dense engine computed during the last step
+    pub generation: u64,                         // Generation on screen (0 when the pattern was loaded)
+    pub history: History,                        // Previous generations, see step_back() and jump_to_timeline()
+    pub paused: bool,                            // The simulation waits while the history is reviewed
+    pub scrubbing: bool,                         // Left button held on the timeline bar
+    pub cursor_pos: Option<(f32, f32)>,          // Last known mouse position in window pixels
+    pub speed: f64,                              // Target generations per second (steps per second with HashLife)
+    pub warp: bool,                              // Step as fast as possible, render at the display rate
+    pub step_credit: f64,                        // Steps due but not run yet (fraction of a step in slow motion)
+    pub last_advance: Instant,                   // When advance() last ran
+    pub measured_speed: f64,                     // Generations per second actually computed
+    pub speed_sample: (Instant, u64),            // Start (time, generation) of the measured_speed sample
+    pub title: String,                           // Window title last set, see update_title()
+    pub stats: Statistics,                       // Population, births, deaths and bounding box of the last generations
+    pub stats_csv: PathBuf,                      // Where export_stats() writes them (--stats-csv)
+    pub show_stats_graph: bool,                  // Overlay the population of the last generations
+    pub period_detector: PeriodDetector,         // Hashes of the last generations, see step()
+    pub periodicity: Option<(u64, Periodicity)>, // Generation where the pattern first repeated, and how
+    pub soup: Soup,                              // Density, symmetry and seed of the last soup (S key)
+    pub soup_size: Option<u32>,                  // Side of the centred square the soup fills, None for the whole board
+    pub selection: Option<(i64, i64, i64, i64)>, // Cells selected with Shift+drag (min_x, min_y, max_x, max_y), the soup fills them instead
+    pub selecting: Option<(i64, i64)>,           // Cell where the Shift+drag started, while the left button is held
+    pub metadata: PatternMetadata,               // Name, originator and comments of the pattern loaded
+    pub placement: Placement,                    // Where the next pattern loaded goes
 }
 
 impl App {
-    pub fn try_new(path: &Path) -> Result<Self> {
+    pub fn try_new(path: &Path, placement: Placement) -> Result<Self> {
         let mut app = Self {
             window: None,
             pixels: None,
             last_frame: Instant::now(),
             board_width: config::BOARD_WIDTH,   // FIXED board size
             board_height: config::BOARD_HEIGHT, // FIXED board size
-            board_current: vec![false; (config::BOARD_WIDTH * config::BOARD_HEIGHT) as usize],
-            board_next: vec![false; (config::BOARD_WIDTH * config::BOARD_HEIGHT) as usize],
+            dense: DenseBoard::new(config::BOARD_WIDTH, config::BOARD_HEIGHT),
+            board_states: vec![0; (config::BOARD_WIDTH * config::BOARD_HEIGHT) as usize],
+            board_states_next: vec![0; (config::BOARD_WIDTH * config::BOARD_HEIGHT) as usize],
             full_screen: false,
             pending_resize: None,
             surface_w: 0, // size of the window
@@ -56,11 +173,47 @@ impl App {
             perf_metrics: perfs::PerformanceMetrics::new(config::PERF_SAMPLE_SIZE),                 // Average on 60 frames
             zoom_level: 1.0,                                                                        // Default zoom
             zoom_max: (config::WINDOW_WIDTH.min(config::WINDOW_HEIGHT) / config::CELL_SIZE) as f32, // Initial zoom_max
-            camera_x: (config::BOARD_WIDTH / 2) as f32,                                             // Start centered on board
-            camera_y: (config::BOARD_HEIGHT / 2) as f32,                                            // Start centered on board
+            camera_x: (config::BOARD_WIDTH / 2) as f64,                                             // Start centered on board
+            camera_y: (config::BOARD_HEIGHT / 2) as f64,                                            // Start centered on board
             is_panning: false,                                                                      // Not panning initially
             last_mouse_pos: None,                                                                   // No mouse position yet
             modifiers: ModifiersState::empty(),                                                     // No modifiers initially
+            rule: Rule::life(),                                                                     // Conway's Life until a pattern says otherwise
+            engine: Engine::Dense,
+            packed: BitBoard::new(config::BOARD_WIDTH, config::BOARD_HEIGHT),
+            dense_stale: false,
+            workers: Workers::single(),
+            hashlife: HashLife::new(Rule::life()),
+            sparse: SparseBoard::new(),
+            view_cells: Vec::new(),
+            show_active_tiles: false,
+            generation: 0,
+            history: History::new(config::HISTORY_BUDGET_MB << 20, config::HISTORY_KEYFRAME_INTERVAL),
+            paused: false,
+            scrubbing: false,
+            cursor_pos: None,
+            speed: config::SPEED_DEFAULT,
+            warp: false,
+            step_credit: 0.0,
+            last_advance: Instant::now(),
+            measured_speed: 0.0,
+            speed_sample: (Instant::now(), 0),
+            title: String::new(),
+            stats: Statistics::new(config::STATS_CAPACITY),
+            stats_csv: PathBuf::from(config::STATS_CSV_PATH),
+            show_stats_graph: false,
+            period_detector: PeriodDetector::new(config::PERIOD_WINDOW),
+            periodicity: None,
+            soup: Soup {
+                density: config::SOUP_DENSITY,
+                symmetry: Symmetry::C1,
+                seed: 0,
+            },
+            soup_size: Some(config::SOUP_SIZE),
+            selection: None,
+            selecting: None,
+            metadata: PatternMetadata::default(),
+            placement,
         };
 
         // Now, do the fallible work
@@ -136,16 +289,778 @@ impl App {
 
     // call by WindowEvent::KeyboardInput when  user press `o`
     pub fn load_pattern(&mut self, path: &Path) -> Result<()> {
+        let (states, width, height, metadata) = utils::read_pattern(path)?;
+        match metadata.title() {
+            Some(title) => log::info!("{} pattern file loaded: {}", path.display(), title),
+            None => log::info!("{} pattern file loaded", path.display()),
+        }
+        metadata.comments.iter().for_each(|comment| log::info!("  {}", comment));
+
+        // Patterns carry their own rule (HighLife, Seeds, Day & Night...) and maybe a bounded grid
+        self.set_rule(metadata.rule.clone())?;
+        let centred = ((self.board_width as i64 - width as i64) / 2, (self.board_height as i64 - height as i64) / 2);
+        let declared = metadata.position.map(|(x, y)| (self.origin().0 + x, self.origin().1 + y));
+        let top_left = match self.placement {
+            Placement::Auto => declared.unwrap_or(centred),
+            Placement::Centred => centred,
+            Placement::Absolute => declared.unwrap_or(self.origin()),
+            Placement::Cursor => self.cursor_cell().map_or(centred, |(x, y)| (x - width as i64 / 2, y - height as i64 / 2)),
+        };
+        let generation = metadata.generation.unwrap_or(0);
+        self.metadata = metadata;
+        self.place_cells(&states, width, height, top_left, generation);
+
+        // The pattern may be far from the board center
+        if top_left != centred && self.placement != Placement::Cursor {
+            self.camera_x = top_left.0 as f64 + width as f64 / 2.0;
+            self.camera_y = top_left.1 as f64 + height as f64 / 2.0;
+            self.clamp_camera();
+        }
+
+        Ok(())
+    }
+
+    // Board cell of Golly's (0, 0): the center of the board
+    pub fn origin(&self) -> (i64, i64) {
+        ((self.board_width / 2) as i64, (self.board_height / 2) as i64)
+    }
+
+    // Board cell under the mouse, None before the mouse entered the window
+    pub fn cursor_cell(&self) -> Option<(i64, i64)> {
+        let (x, y) = self.cursor_pos?;
+        let cell_size = config::CELL_SIZE as f64 * self.zoom_level as f64;
+        let cell_x = self.camera_x + (x as f64 - self.surface_w as f64 / 2.0) / cell_size;
+        let cell_y = self.camera_y + (y as f64 - self.surface_h as f64 / 2.0) / cell_size;
+        Some((cell_x.floor() as i64, cell_y.floor() as i64))
+    }
+
+    // Call by WindowEvent::KeyboardInput when user press `p`
+    pub fn cycle_placement(&mut self) {
+        let current = Placement::ALL.iter().position(|&p| p == self.placement).unwrap_or_default();
+        self.placement = Placement::ALL[(current + 1) % Placement::ALL.len()];
+        log::info!("Placement of the next pattern: {}", self.placement.name());
+    }
+
+    // Call by WindowEvent::KeyboardInput when user press `Ctrl+S`: the cells on screen, cropped to their bounding box
+    // The extension of the file name picks the format: .cells plaintext (no rule, position or generation there),
+    // .lif Life 1.05 (B/S rules only), .life Life 1.06 (cells only), RLE otherwise
+    pub fn save_pattern(&mut self, path: &Path) -> Result<()> {
+        let cells = self.snapshot();
+        let (Some(min_x), Some(max_x)) = (cells.iter().map(|cell| cell.0).min(), cells.iter().map(|cell| cell.0).max()) else {
+            return Err("Nothing to save: the board is empty".into());
+        };
+        // Sorted by (y, x)
+        let (min_y, max_y) = (cells[0].1, cells[cells.len() - 1].1);
+        let (width, height) = ((max_x - min_x + 1) as u32, (max_y - min_y + 1) as u32);
+        let mut states = vec![0; width as usize * height as usize];
+        for (x, y, state) in cells {
+            states[(y - min_y) as usize * width as usize + (x - min_x) as usize] = state;
+        }
+
+        // Position and generation like Golly writes them, then the name, originator and comments of the pattern loaded
+        let (origin_x, origin_y) = self.origin();
+        let name = self.metadata.name.clone();
+        let name = name.or_else(|| path.file_stem().map(|stem| stem.to_string_lossy().to_string())).unwrap_or_default();
+        let position = (min_x - origin_x, min_y - origin_y);
+        let mut comments = vec![('C', format!("XRLE Pos={},{} Gen={}", position.0, position.1, self.generation)), ('N', name)];
+        comments.extend(self.metadata.originator.iter().map(|originator| ('O', originator.clone())));
+        comments.extend(self.metadata.comments.iter().map(|comment| ('C', comment.clone())));
+        let extension = path.extension().and_then(|e| e.to_str()).map(|e| e.to_ascii_lowercase());
+        if matches!(extension.as_deref(), Some("cells" | "life")) && self.rule != Rule::life() {
+            log::warn!("{} is saved without its rule {}, it will be read as Conway's Life", path.display(), self.rule);
+        }
+        match extension.as_deref() {
+            Some("cells") => utils::write_cells(path, &states, width, height, &comments[1..])?,
+            Some("lif") => utils::write_life105(path, &states, width, height, &self.rule, position, &comments[1..])?,
+            Some("life") => utils::write_life106(path, &states, width, height, position)?,
+            _ => utils::write_rle(path, &states, width, height, &self.rule, &comments)?,
+        }
+        log::info!("Generation {} saved to {} ({}x{})", self.generation, path.display(), width, height);
+        Ok(())
+    }
+
+    // Call by WindowEvent::KeyboardInput when user press `s` (new seed) and with --soup (--seed)
+    // The soup fills the selection, else the centred soup_size x soup_size square, or the whole board, with the current rule
+    pub fn load_soup(&mut self, seed: Option<u64>) -> Result<()> {
+        self.soup.seed = seed.unwrap_or_else(rand::random);
+        let (width, height, corner) = match self.selection {
+            Some((min_x, min_y, max_x, max_y)) => {
+                let (width, height) = (max_x - min_x + 1, max_y - min_y + 1);
+                if width > MAX_BOARD as i64 || height > MAX_BOARD as i64 {
+                    return Err(format!("Selection too large for a soup: {}x{} (at most {} cells a side)", width, height, MAX_BOARD).into());
+                }
+                (width as u32, height as u32, (min_x, min_y))
+            }
+            None => {
+                let (width, height) = self.soup_size.map_or((self.board_width, self.board_height), |size| (size, size));
+                if width > MAX_BOARD || height > MAX_BOARD {
+                    return Err(format!("Soup too large: {}x{} (at most {} cells a side)", width, height, MAX_BOARD).into());
+                }
+                (width, height, ((self.board_width as i64 - width as i64) / 2, (self.board_height as i64 - height as i64) / 2))
+            }
+        };
+        let cells = self.soup.generate(width, height)?;
+        log::info!("Soup {}x{}: {}", width, height, self.soup);
+        self.metadata = PatternMetadata {
+            name: Some(format!("soup {}", self.soup.seed)),
+            comments: vec![format!("{}x{} soup, {}", width, height, self.soup)],
+            rule: self.rule.clone(),
+            ..PatternMetadata::default()
+        };
+
+        let states: Vec<u8> = cells.into_iter().map(u8::from).collect();
+        self.place_cells(&states, width, height, corner, 0);
+        Ok(())
+    }
+
+    // Call by WindowEvent::MouseInput when user press the left button with `Shift`: the selection starts at the cell under the mouse
+    pub fn start_selection(&mut self) {
+        if let Some((x, y)) = self.cursor_cell() {
+            self.selecting = Some((x, y));
+            self.selection = Some((x, y, x, y));
+        }
+    }
+
+    // Call by WindowEvent::CursorMoved while selecting: from the cell where it started to the one under the mouse
+    pub fn update_selection(&mut self) {
+        if let (Some((x0, y0)), Some((x1, y1))) = (self.selecting, self.cursor_cell()) {
+            self.selection = Some((x0.min(x1), y0.min(y1), x0.max(x1), y0.max(y1)));
+        }
+    }
+
+    // Call by WindowEvent::MouseInput when user release the left button after a Shift+drag
+    pub fn end_selection(&mut self) {
+        self.selecting = None;
+        if let Some((min_x, min_y, max_x, max_y)) = self.selection {
+            let (origin_x, origin_y) = self.origin();
+            log::info!(
+                "Selection {}x{} at x = {}, y = {}: S fills it with a soup, Escape clears it",
+                max_x - min_x + 1,
+                max_y - min_y + 1,
+                min_x - origin_x,
+                min_y - origin_y
+            );
+        }
+    }
+
+    // Call by WindowEvent::KeyboardInput when user press `Escape`, false when nothing was selected
+    pub fn clear_selection(&mut self) -> bool {
+        self.selecting = None;
+        self.selection.take().is_some()
+    }
+
+    // Replace the board with the cells (states, row by row), their top-left corner at `(x0, y0)`
+    // A new timeline starts at `generation`
+    fn place_cells(&mut self, states: &[u8], width: u32, height: u32, (x0, y0): (i64, i64), generation: u64) {
         // clear the board because a simulation may be in progress
-        self.board_current.fill(false);
+        self.dense.clear();
+        self.dense_stale = false;
+        self.board_states.fill(0);
+        let cells: Vec<bool> = states.iter().map(|&s| s == 1).collect();
+
+        // Unbounded engines take the whole pattern (nothing is cropped), the bounded ones what falls on the board
+        if self.engine.is_unbounded() {
+            self.backend_mut(self.engine).import_region(&cells, x0, y0, width, height);
+        } else {
+            // utils::place_pattern_centered(&mut self.board_current, self.board_width, self.board_height, &cells, width, height)?;
+            utils::place_pattern_at(self.dense.cells_mut(), self.board_width, self.board_height, &cells, width, height, x0, y0);
+            if !self.rule.is_life_like() {
+                utils::place_pattern_at(&mut self.board_states, self.board_width, self.board_height, states, width, height, x0, y0);
+            }
+            log::debug!("place_cells(): Call place_pattern_at() with buffer size = {}x{} at ({}, {}).", width, height, x0, y0);
+            self.sync_engine(Engine::Dense);
+        }
+
+        // A new timeline starts
+        self.generation = generation;
+        self.paused = false;
+        self.history.clear();
+        self.stats.clear();
+        self.period_detector.clear();
+        self.periodicity = None;
+        self.record_generation();
+    }
+
+    // Advance one generation with the selected engine, record its statistics and look for a repeat
+    // Bounded engines leave the result on the dense board, unbounded ones keep it in their own universe (see update_view())
+    pub fn step(&mut self) {
+        // Going on from a generation of the history: its future is replaced by the one computed now
+        if self.history.newest().is_some_and(|newest| newest > self.generation) {
+            self.history.truncate(self.generation);
+        }
+
+        let tally = match self.engine {
+            Engine::Dense if !self.rule.is_life_like() => {
+                if self.rule.rule_table().is_some() {
+                    ruletable::step_table_parallel(&self.board_states, &mut self.board_states_next, self.board_width, self.board_height, &self.rule, &self.workers);
+                } else if self.rule.larger_than_life().is_some() {
+                    ltl::step_ltl_parallel(&self.board_states, &mut self.board_states_next, self.board_width, self.board_height, &self.rule, &self.workers);
+                } else {
+                    life::step_generations_parallel(&self.board_states, &mut self.board_states_next, self.board_width, self.board_height, &self.rule, &self.workers);
+                }
+                std::mem::swap(&mut self.board_states, &mut self.board_states_next);
+                // The dense board keeps the live cells (state 1)
+                for (cell, &state) in self.dense.cells_mut().iter_mut().zip(&self.board_states) {
+                    *cell = state == 1;
+                }
+                // Every cell was stepped, the previous states are still there to count births and deaths against
+                Tally::of_step(&self.board_states_next, &self.board_states, self.dense.frame())
+            }
+            _ => {
+                // Not backend_mut(): the rule is borrowed at the same time
+                let engine: &mut dyn LifeEngine = match self.engine {
+                    Engine::Dense => &mut self.dense,
+                    Engine::BitPacked => &mut self.packed,
+                    Engine::HashLife => &mut self.hashlife,
+                    Engine::Sparse => &mut self.sparse,
+                };
+                engine.step(&self.rule, &self.workers);
+                // Unpacked once per frame, by sync_dense(), not after each of its generations
+                self.dense_stale = self.engine == Engine::BitPacked;
+                engine.tally()
+            }
+        };
+
+        let span = if self.engine == Engine::HashLife { 1 << self.hashlife.step_exponent() } else { 1 };
+        self.generation += span;
+        self.stats.record_step(self.generation, &tally, span);
+        // Every generation stepped, whatever the speed: HashLife steps of 2^k generations are a gap, see gol::period
+        self.detect_period(tally.signature());
+    }
+
+    // Call by WindowEvent::RedrawRequested: run the generations due since the last frame
+    // Stepping stops when the frame time is used up so the display keeps its rate, the steps left are dropped
+    // Every generation gets its statistics, only the one shown at the end of the frame goes to the history
+    pub fn advance(&mut self) {
+        let now = Instant::now();
+        let elapsed = now - self.last_advance;
+        self.last_advance = now;
+        if self.paused {
+            self.step_credit = 0.0;
+            return;
+        }
+
+        let due = if self.warp {
+            u64::MAX
+        } else {
+            self.step_credit += elapsed.as_secs_f64() * self.speed;
+            let due = self.step_credit.floor();
+            self.step_credit -= due;
+            due as u64
+        };
+        let deadline = now + config::FRAME_DURATION;
+        let mut stepped = false;
+        for _ in 0..due {
+            let step_start = Instant::now();
+            self.step();
+            self.perf_metrics.record_step(step_start.elapsed());
+            stepped = true;
+            if Instant::now() >= deadline {
+                break;
+            }
+        }
+        if stepped {
+            self.record_generation();
+        }
+    }
+
+    // Headless run (--detect): step until the pattern repeats or `max_generations` is reached
+    pub fn run_until_repeat(&mut self, max_generations: u64) -> Option<(u64, Periodicity)> {
+        while self.periodicity.is_none() && self.generation < max_generations {
+            self.step();
+            self.record_generation();
+        }
+        self.periodicity
+    }
+
+    // Call by WindowEvent::KeyboardInput when user press `Space`
+    // Running again from a generation of the history replaces its future
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+        self.step_credit = 0.0;
+        log::info!("{} at generation {}", if self.paused { "Paused" } else { "Running" }, self.generation);
+    }
+
+    // Call by WindowEvent::KeyboardInput when user press `n`: one step, then wait
+    pub fn single_step(&mut self) {
+        self.paused = true;
+        self.step();
+        self.record_generation();
+    }
+
+    // Call by WindowEvent::KeyboardInput when user press `,` (factor 0.5) or `.` (factor 2)
+    pub fn change_speed(&mut self, factor: f64) {
+        self.set_speed(self.speed * factor);
+    }
+
+    pub fn set_speed(&mut self, speed: f64) {
+        self.speed = speed.clamp(config::SPEED_MIN, config::SPEED_MAX);
+        self.warp = false;
+        log::info!("Speed: {} generations per second ({:.2} per frame)", self.speed, self.speed / config::FPS as f64);
+    }
+
+    // Call by WindowEvent::KeyboardInput when user press `w`
+    pub fn toggle_warp(&mut self) {
+        self.warp = !self.warp;
+        log::info!("Warp {}", if self.warp { "on: as many generations as a frame allows" } else { "off" });
+    }
+
+    // Generation and speed in the window title, the measured speed is refreshed every TITLE_REFRESH_SECS
+    pub fn update_title(&mut self) {
+        let (since, start_generation) = self.speed_sample;
+        let elapsed = since.elapsed();
+        if elapsed >= Duration::from_secs_f64(config::TITLE_REFRESH_SECS) || self.generation < start_generation {
+            self.measured_speed = self.generation.saturating_sub(start_generation) as f64 / elapsed.as_secs_f64();
+            self.speed_sample = (Instant::now(), self.generation);
+        }
+
+        let status = if self.paused {
+            "paused".to_string()
+        } else if self.warp {
+            format!("warp {:.0} gen/s", self.measured_speed)
+        } else {
+            format!("{:.0}/{} gen/s", self.measured_speed, self.speed)
+        };
+        let mut title = match self.metadata.title() {
+            Some(pattern) => format!("{} | {} | generation {} | {}", config::TITLE, pattern, self.generation, status),
+            None => format!("{} | generation {} | {}", config::TITLE, self.generation, status),
+        };
+        // Cell under the mouse in Golly's coordinates
+        if let Some((x, y)) = self.cursor_cell() {
+            title.push_str(&format!(" | x = {}, y = {}", x - self.origin().0, y - self.origin().1));
+        }
+        if let Some((_, periodicity)) = self.periodicity {
+            title.push_str(&format!(" | {}", periodicity));
+        }
+        if title != self.title {
+            if let Some(window) = self.window {
+                window.set_title(&title);
+            }
+            self.title = title;
+        }
+    }
+
+    // 0 disables the history
+    pub fn set_history_budget(&mut self, megabytes: usize) {
+        self.history = History::new(megabytes << 20, config::HISTORY_KEYFRAME_INTERVAL);
+        log::info!("History budget: {} MiB", megabytes);
+        if let Some(cells) = self.small_snapshot() {
+            self.history.record(self.generation, cells);
+        }
+    }
+
+    // Cells of the generation on screen, sorted like gol::history expects
+    fn snapshot(&self) -> Vec<history::Cell> {
+        if !self.rule.is_life_like() {
+            let width = self.board_width as usize;
+            return self
+                .board_states
+                .iter()
+                .enumerate()
+                .filter(|&(_, &state)| state != 0)
+                .map(|(i, &state)| ((i % width) as i64, (i / width) as i64, state))
+                .collect();
+        }
+        let mut cells: Vec<history::Cell> = self.backend(self.engine).live_cells().into_iter().map(|(x, y)| (x, y, 1)).collect();
+        history::sort_cells(&mut cells);
+        cells
+    }
+
+    // Cells of the generation on screen, None when there are too many of them to take a snapshot every frame
+    fn small_snapshot(&self) -> Option<Vec<history::Cell>> {
+        let small = !self.rule.is_life_like() || self.backend(self.engine).population() <= config::HISTORY_MAX_POPULATION;
+        small.then(|| self.snapshot())
+    }
+
+    // Tally of the generation on screen, the engines do not keep the dying states of multi-state rules
+    fn tally(&self) -> Tally {
+        if self.rule.is_life_like() {
+            self.backend(self.engine).tally()
+        } else {
+            Tally::of_cells(&self.board_states, self.dense.frame())
+        }
+    }
 
-        let (cells, width, height) = utils::read_rle(path)?;
-        log::info!("{} pattern file loaded", path.display());
+    // History and bounding box of the generation on screen, its statistics row and period detection if it was not stepped to
+    fn record_generation(&mut self) {
+        let engine = self.backend(self.engine);
+        let (population, bounding_box) = (engine.population(), engine.bounding_box());
+        if self.stats.latest().is_some_and(|row| row.generation == self.generation) {
+            self.stats.set_bounding_box(self.generation, bounding_box);
+        } else {
+            self.stats.record_start(self.generation, population, bounding_box);
+            self.detect_period(self.tally().signature());
+        }
 
-        // utils::place_pattern_centered(&mut self.board_current, self.board_width, self.board_height, &cells, width, height)?;
-        utils::place_pattern_centered(&mut self.board_current, self.board_width, self.board_height, &cells, width, height);
-        log::debug!("load_pattern(): Call place_pattern_centered() with buffer size = {}x{}.", width, height);
+        let Some(cells) = self.small_snapshot() else {
+            if !self.history.is_empty() {
+                log::warn!("History stopped: more than {} live cells", config::HISTORY_MAX_POPULATION);
+                self.history.clear();
+            }
+            return;
+        };
+        if self.history.is_enabled() {
+            self.history.record(self.generation, cells);
+        }
+    }
+
+    // Log the first repeat (still life, oscillator, spaceship) and when it changes
+    fn detect_period(&mut self, signature: Option<Signature>) {
+        let found = self.period_detector.observe(self.generation, signature);
+        if found != self.periodicity.map(|(_, periodicity)| periodicity) {
+            self.periodicity = found.map(|periodicity| (self.generation, periodicity));
+            if let Some(periodicity) = found {
+                log::info!("Generation {}: {}", self.generation, periodicity);
+            }
+        }
+    }
+
+    // Put a recorded generation back on screen, the simulation is paused
+    fn restore(&mut self, generation: u64) {
+        let Some(cells) = self.history.cells_at(generation) else {
+            return;
+        };
+        self.stats.truncate(generation);
+        // Its future is gone, what repeats will be found again
+        self.period_detector.clear();
+        self.periodicity = None;
+        if !self.rule.is_life_like() {
+            self.board_states.fill(0);
+            let dense = self.dense.cells_mut();
+            dense.fill(false);
+            for (x, y, state) in cells.into_iter().filter(|&(x, y, _)| x < self.board_width as i64 && y < self.board_height as i64) {
+                let i = (y * self.board_width as i64 + x) as usize;
+                self.board_states[i] = state;
+                dense[i] = state == 1;
+            }
+        } else {
+            let engine = self.backend_mut(self.engine);
+            engine.clear();
+            cells.into_iter().for_each(|(x, y, _)| engine.set_cell(x, y, true));
+            self.dense_stale = self.engine == Engine::BitPacked;
+        }
+        self.generation = generation;
+        self.paused = true;
+    }
+
+    // Call by WindowEvent::KeyboardInput when user press `Ctrl+E` (and on exit with --stats-csv)
+    pub fn export_stats(&mut self) {
+        match self.stats.save_csv(&self.stats_csv) {
+            Ok(()) => log::info!("Statistics of {} generations written to {}", self.stats.len(), self.stats_csv.display()),
+            Err(e) => {
+                let error_msg = format!("Failed to export statistics: {}", e);
+                log::error!("{}", error_msg);
+                self.set_error(error_msg, 5);
+            }
+        }
+    }
+
+    // Call by WindowEvent::KeyboardInput when user press `c`: objects on the board, in the log and in CENSUS_PATH
+    pub fn take_census(&mut self) {
+        if !self.rule.is_life_like() {
+            self.set_error(format!("No census for {}: multi-state rule", self.rule), 5);
+            return;
+        }
+        let cells = self.backend(self.engine).live_cells();
+        let result = Census::take(&cells, &self.rule, config::CENSUS_MAX_GENERATIONS).and_then(|census| {
+            let path = Path::new(config::CENSUS_PATH);
+            census.save(path)?;
+            log::info!("Census of generation {}: {} objects, written to {}", self.generation, census.object_count(), path.display());
+            let mut table = Vec::new();
+            census.write_table(&mut table)?;
+            String::from_utf8_lossy(&table).lines().for_each(|line| log::info!("{}", line));
+            Ok(())
+        });
+        if let Err(e) = result {
+            let error_msg = format!("Census failed: {}", e);
+            log::error!("{}", error_msg);
+            self.set_error(error_msg, 5);
+        }
+    }
+
+    // Call by WindowEvent::KeyboardInput when user press `l` (and --lifespan): run a copy of the board until it is periodic
+    // The board on screen does not change, the generations of the report are counted from the current one
+    pub fn analyse_lifespan(&mut self, max_generations: u64) -> Option<Lifespan> {
+        if !self.rule.is_life_like() {
+            self.set_error(format!("No lifespan analysis for {}: multi-state rule", self.rule), 5);
+            return None;
+        }
+        log::info!("Lifespan analysis from generation {} (at most {} generations)...", self.generation, max_generations);
+        match Lifespan::analyse(&self.backend(self.engine).live_cells(), &self.rule, max_generations) {
+            Ok(lifespan) => {
+                lifespan.to_string().lines().for_each(|line| log::info!("Lifespan: {}", line));
+                Some(lifespan)
+            }
+            Err(e) => {
+                let error_msg = format!("Lifespan analysis failed: {}", e);
+                log::error!("{}", error_msg);
+                self.set_error(error_msg, 5);
+                None
+            }
+        }
+    }
+
+    // Call by WindowEvent::KeyboardInput when user press `g`
+    pub fn toggle_stats_graph(&mut self) {
+        self.show_stats_graph = !self.show_stats_graph;
+    }
+
+    // Call by WindowEvent::KeyboardInput when user press `Backspace`
+    pub fn step_back(&mut self) {
+        match self.history.previous(self.generation) {
+            Some(generation) => self.restore(generation),
+            None => log::info!("No generation recorded before {}", self.generation),
+        }
+    }
+
+    // Call by WindowEvent::KeyboardInput when user press `Home`
+    pub fn jump_to_oldest(&mut self) {
+        if let Some(generation) = self.history.oldest() {
+            self.restore(generation);
+        }
+    }
+
+    // Call by WindowEvent::KeyboardInput when user press `End`: back to the newest generation, the simulation goes on
+    pub fn resume(&mut self) {
+        if let Some(generation) = self.history.newest()
+            && generation != self.generation
+        {
+            self.restore(generation);
+        }
+        self.paused = false;
+    }
+
+    // Jump to the recorded generation at `fraction` (0.0 oldest, 1.0 newest) of the timeline bar
+    pub fn jump_to_timeline(&mut self, fraction: f32) {
+        let (Some(oldest), Some(newest)) = (self.history.oldest(), self.history.newest()) else {
+            return;
+        };
+        let target = oldest + ((newest - oldest) as f64 * fraction.clamp(0.0, 1.0) as f64).round() as u64;
+        if let Some(generation) = self.history.nearest(target)
+            && generation != self.generation
+        {
+            self.restore(generation);
+        }
+        self.paused = true;
+    }
+
+    // Where a window position falls on the timeline bar (drawn at the bottom of the buffer while paused), None when outside of it
+    pub fn timeline_fraction(&self, (x, y): (f32, f32)) -> Option<f32> {
+        if !self.paused || self.surface_w == 0 || self.surface_h == 0 {
+            return None;
+        }
+        let buffer_y = y / self.surface_h as f32 * self.board_height as f32;
+        (buffer_y >= (self.board_height - config::TIMELINE_HEIGHT.min(self.board_height)) as f32).then(|| x / self.surface_w as f32)
+    }
+
+    // Simulation back-end behind an Engine
+    pub fn backend(&self, engine: Engine) -> &dyn LifeEngine {
+        match engine {
+            Engine::Dense => &self.dense,
+            Engine::BitPacked => &self.packed,
+            Engine::HashLife => &self.hashlife,
+            Engine::Sparse => &self.sparse,
+        }
+    }
+
+    pub fn backend_mut(&mut self, engine: Engine) -> &mut dyn LifeEngine {
+        match engine {
+            Engine::Dense => &mut self.dense,
+            Engine::BitPacked => &mut self.packed,
+            Engine::HashLife => &mut self.hashlife,
+            Engine::Sparse => &mut self.sparse,
+        }
+    }
 
+    pub fn set_engine(&mut self, engine: Engine) {
+        if !engine.supports(&self.rule) {
+            log::warn!(
+                "The {} engine cannot run {} (B0, bounded grids, Generations, non-totalistic, hexagonal and von Neumann rules are not supported by every engine), keeping {}",
+                engine.name(),
+                self.rule,
+                self.engine.name()
+            );
+            return;
+        }
+        if engine != self.engine {
+            log::info!("Engine switched: {} -> {}", self.engine.name(), engine.name());
+            let previous = self.engine;
+            self.engine = engine;
+            self.sync_engine(previous);
+            self.clamp_camera();
+        }
+    }
+
+    // Call by WindowEvent::KeyboardInput when user press `e`
+    // Switch to the next engine able to run the rule, the cells go across (see sync_engine())
+    pub fn cycle_engine(&mut self) {
+        let current = Engine::ALL.iter().position(|&e| e == self.engine).unwrap_or_default();
+        let next = (1..Engine::ALL.len()).map(|i| Engine::ALL[(current + i) % Engine::ALL.len()]).find(|e| e.supports(&self.rule));
+        match next {
+            Some(engine) => self.set_engine(engine),
+            None => log::info!("Only the {} engine can run {}", self.engine.name(), self.rule),
+        }
+    }
+
+    // 0 means one thread per available core
+    // The worker threads are started here, once, and reused by every step
+    pub fn set_threads(&mut self, threads: usize) {
+        let threads = if threads == 0 {
+            std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
+        } else {
+            threads
+        };
+        self.workers = Workers::new(threads).unwrap_or_else(|e| {
+            log::error!("{}, stepping on the event-loop thread", e);
+            Workers::single()
+        });
+        log::info!("Stepping with {} thread(s)", self.workers.threads());
+    }
+
+    // Copy the cells held by the `from` engine into the active one
+    // (Engine::Dense when the dense board was modified outside of step())
+    // - board -> board     : the dense board is shared, the bit-packed one reloads it
+    // - board -> universe  : the board becomes the window [0, board_width) x [0, board_height) of the universe
+    // - universe -> board  : that same window is copied back, cells outside of it are lost
+    // - universe -> universe : every live cell goes across
+    fn sync_engine(&mut self, from: Engine) {
+        // Bounded engines are mirrored on the dense board
+        self.sync_dense();
+        let from = if from.is_unbounded() { from } else { Engine::Dense };
+        if from == self.engine {
+            return;
+        }
+        if from.is_unbounded() && self.engine.is_unbounded() {
+            let cells = self.backend(from).live_cells();
+            let to = self.backend_mut(self.engine);
+            to.clear();
+            cells.into_iter().for_each(|(x, y)| to.set_cell(x, y, true));
+            return;
+        }
+
+        let (width, height) = (self.board_width, self.board_height);
+        let mut window = vec![false; (width * height) as usize];
+        self.backend(from).export_region(&mut window, 0, 0, width, height);
+        self.backend_mut(self.engine).import_region(&window, 0, 0, width, height);
+        if self.engine == Engine::BitPacked {
+            self.dense.import_region(&window, 0, 0, width, height);
+        }
+    }
+
+    // Unpack the bit-packed board into the dense one when it went on without it
+    // Call by WindowEvent::RedrawRequested before drawing, and before anything reading the dense board
+    pub fn sync_dense(&mut self) {
+        if self.dense_stale {
+            self.packed.write_cells(self.dense.cells_mut());
+            self.dense_stale = false;
+        }
+    }
+
+    // Copy the part of an unbounded universe under the camera into view_cells and return its (x, y, width, height)
+    pub fn update_view(&mut self) -> (i64, i64, u32, u32) {
+        let visible_width = self.surface_w as f64 / (config::CELL_SIZE as f64 * self.zoom_level as f64);
+        let visible_height = self.surface_h as f64 / (config::CELL_SIZE as f64 * self.zoom_level as f64);
+        let x0 = (self.camera_x - visible_width / 2.0).floor() as i64;
+        let y0 = (self.camera_y - visible_height / 2.0).floor() as i64;
+        let width = visible_width.ceil() as u32 + 1;
+        let height = visible_height.ceil() as u32 + 1;
+
+        let mut view = std::mem::take(&mut self.view_cells);
+        view.resize((width * height) as usize, false);
+        self.backend(self.engine).export_region(&mut view, x0, y0, width, height);
+        self.view_cells = view;
+        (x0, y0, width, height)
+    }
+
+    // Live cells of the active engine, None when everything died
+    pub fn bounding_box(&self) -> Option<(i64, i64, i64, i64)> {
+        self.backend(self.engine).bounding_box()
+    }
+
+    // Call by WindowEvent::KeyboardInput when user press `a`
+    pub fn toggle_active_tiles(&mut self) {
+        self.show_active_tiles = !self.show_active_tiles;
+        log::info!("Active tiles overlay: {} (dense engine only)", if self.show_active_tiles { "on" } else { "off" });
+    }
+
+    // Call by WindowEvent::KeyboardInput when user press `[` or `]`
+    pub fn change_step_exponent(&mut self, delta: i32) {
+        let exponent = (self.hashlife.step_exponent() as i32 + delta).clamp(0, config::HASHLIFE_MAX_STEP_EXPONENT as i32) as u8;
+        self.hashlife.set_step_exponent(exponent);
+        log::info!("Step exponent: 2^{} generations per step (HashLife engine only)", exponent);
+        if exponent > 0 {
+            log::info!("Still lifes, oscillators and spaceships are only detected one generation at a time (step exponent 0)");
+        }
+    }
+
+    // Make `rule` the active rule and resize the board if its topology declares another size
+    // When the board cannot be resized, the rule and the board stay as they were
+    pub fn set_rule(&mut self, rule: Rule) -> Result<()> {
+        let (width, height) = rule.topology().board_size(config::BOARD_WIDTH, config::BOARD_HEIGHT);
+        if width != self.board_width || height != self.board_height {
+            self.resize_board(width, height)?;
+        }
+
+        let entering_states = !rule.is_life_like() && self.rule.is_life_like();
+        if rule != self.rule {
+            log::info!("Rule switched: {} -> {}", self.rule, rule);
+        }
+        self.rule = rule;
+        if !self.engine.supports(&self.rule) {
+            log::warn!("The {} engine cannot run {}, falling back to the dense engine", self.engine.name(), self.rule);
+            self.set_engine(Engine::Dense);
+        }
+        if entering_states {
+            // Live cells become state 1, nothing is dying yet
+            self.sync_dense();
+            for (state, &alive) in self.board_states.iter_mut().zip(self.dense.cells()) {
+                *state = alive as u8;
+            }
+        }
+        Ok(())
+    }
+
+    // Call by WindowEvent::KeyboardInput when user press `t`
+    // Cycle plane -> torus -> Klein bottle -> cross-surface -> sphere, keeping the board (and its cells)
+    pub fn cycle_topology(&mut self) {
+        let mut kind = Topology::next_kind(self.rule.topology().kind);
+        if kind == TopologyKind::Sphere && self.board_width != self.board_height {
+            log::info!("Sphere skipped: the board ({}x{}) is not square", self.board_width, self.board_height);
+            kind = Topology::next_kind(kind);
+        }
+        let topology = Topology::new(kind, self.board_width, self.board_height);
+        // Same size as the board, nothing to resize
+        if let Err(e) = self.set_rule(self.rule.clone().with_topology(topology)) {
+            log::error!("Failed to change the topology: {}", e);
+        }
+    }
+
+    // Reallocate the boards (cells are lost) and the rendering buffer which matches the board size
+    // On error (size too large, buffer not resized) the old board is kept
+    fn resize_board(&mut self, width: u32, height: u32) -> Result<()> {
+        let cells = (width as usize)
+            .checked_mul(height as usize)
+            .filter(|_| width > 0 && height > 0 && width <= MAX_BOARD && height <= MAX_BOARD)
+            .ok_or_else(|| -> crate::Error { format!("Invalid board size {}x{} (1 to {} cells a side)", width, height, MAX_BOARD).into() })?;
+        if let Some(pixels) = &mut self.pixels {
+            pixels
+                .resize_buffer(width, height)
+                .map_err(|e| -> crate::Error { format!("Failed to resize the rendering buffer to {}x{}: {}", width, height, e).into() })?;
+        }
+
+        self.board_width = width;
+        self.board_height = height;
+        self.dense = DenseBoard::new(width, height);
+        self.board_states = vec![0; cells];
+        self.board_states_next = vec![0; cells];
+        self.packed = BitBoard::new(width, height);
+        self.dense_stale = false;
+
+        self.camera_x = width as f64 / 2.0;
+        self.camera_y = height as f64 / 2.0;
+        self.clamp_camera();
+
+        log::info!("Board resized to {}x{}", width, height);
         Ok(())
     }
 
@@ -205,8 +1120,8 @@ impl App {
     // Positive delta_x moves camera right (board appears to move left)
     // Positive delta_y moves camera down (board appears to move up)
     pub fn pan_camera(&mut self, delta_x: f32, delta_y: f32) {
-        self.camera_x += delta_x;
-        self.camera_y += delta_y;
+        self.camera_x += delta_x as f64;
+        self.camera_y += delta_y as f64;
         self.clamp_camera();
 
         log::debug!("Camera panned to ({:.1}, {:.1})", self.camera_x, self.camera_y);
@@ -214,30 +1129,40 @@ impl App {
 
     // Clamp camera position to keep it within board bounds
     // Camera cannot move beyond the board edges (considering the visible viewport)
+    // With an unbounded engine the camera can go anywhere the pattern goes (its bounding box, plus half a screen)
     fn clamp_camera(&mut self) {
         // Calculate how many cells are visible in current viewport
-        let visible_width = self.surface_w as f32 / (config::CELL_SIZE as f32 * self.zoom_level);
-        let visible_height = self.surface_h as f32 / (config::CELL_SIZE as f32 * self.zoom_level);
+        let visible_width = self.surface_w as f64 / (config::CELL_SIZE as f64 * self.zoom_level as f64);
+        let visible_height = self.surface_h as f64 / (config::CELL_SIZE as f64 * self.zoom_level as f64);
 
         // Calculate camera limits (half of visible area from edges)
         let half_visible_w = visible_width / 2.0;
         let half_visible_h = visible_height / 2.0;
 
+        if self.engine.is_unbounded() {
+            // An empty universe leaves the camera where it is
+            if let Some((min_x, min_y, max_x, max_y)) = self.bounding_box() {
+                self.camera_x = self.camera_x.clamp(min_x as f64 - half_visible_w, (max_x + 1) as f64 + half_visible_w);
+                self.camera_y = self.camera_y.clamp(min_y as f64 - half_visible_h, (max_y + 1) as f64 + half_visible_h);
+            }
+            return;
+        }
+
         // Handle special case: viewport larger than board
         // In this case, center the camera and don't allow movement
-        if visible_width >= self.board_width as f32 {
-            self.camera_x = self.board_width as f32 / 2.0;
+        if visible_width >= self.board_width as f64 {
+            self.camera_x = self.board_width as f64 / 2.0;
         } else {
             let min_x = half_visible_w;
-            let max_x = self.board_width as f32 - half_visible_w;
+            let max_x = self.board_width as f64 - half_visible_w;
             self.camera_x = self.camera_x.clamp(min_x, max_x);
         }
 
-        if visible_height >= self.board_height as f32 {
-            self.camera_y = self.board_height as f32 / 2.0;
+        if visible_height >= self.board_height as f64 {
+            self.camera_y = self.board_height as f64 / 2.0;
         } else {
             let min_y = half_visible_h;
-            let max_y = self.board_height as f32 - half_visible_h;
+            let max_y = self.board_height as f64 - half_visible_h;
             self.camera_y = self.camera_y.clamp(min_y, max_y);
         }
     }
@@ -10,7 +10,8 @@ pub const DEFAULT_PATTERN_PATH: &str = r"rle/linepuffer.rle";
 pub const TITLE: &str = "step_20: Add Panning";
 
 // Board dimensions (FIXED - does not change with zoom or window resize)
-// This is the simulation grid size
+// This is the simulation grid size of the dense and packed engines, and the rendering buffer size
+// The sparse and HashLife engines are unbounded, the board is then only where patterns are centred
 pub const BOARD_WIDTH: u32 = 1280; //Large enough for most patterns 2560x1600
 pub const BOARD_HEIGHT: u32 = 800;
 
@@ -22,11 +23,52 @@ pub const WINDOW_HEIGHT: u32 = 800;
 pub const ZOOM_FACTOR: f32 = 1.15; // Exponential zoom increment (1.15 = +15% per mouse wheel notch)
 pub const ZOOM_MIN: f32 = 0.1; // Minimum zoom level (allows seeing ~10x more cells)
 // ZOOM_MAX is calculated dynamically: min(window_w, window_h) / CELL_SIZE
+pub const HEX_MIN_CELL_SIZE: f32 = 6.0; // Hexagonal rules: draw hexagons once cells are this many buffer pixels wide (sheared squares below)
 
 // Performances
 pub const PERF_SAMPLE_SIZE: usize = 60; // How many frames to average
 pub const PERF_LOG_INTERVAL_SECS: u64 = 1; // Display frequency  (seconds)
 
+// HashLife configuration
+pub const HASHLIFE_MAX_STEP_EXPONENT: u8 = 40; // At most 2^40 generations per step (keeps coordinates far from i64 limits)
+
+// Speed configuration (generations per second, independent of the display rate)
+pub const SPEED_DEFAULT: f64 = 60.0; // One generation per frame at 60 FPS (--speed)
+pub const SPEED_MIN: f64 = 1.0; // Slow motion, `,` halves the speed down to here
+pub const SPEED_MAX: f64 = 61_440.0; // 1024 generations per frame, `.` doubles the speed up to here
+pub const TITLE_REFRESH_SECS: f64 = 0.5; // How often the measured speed in the window title is updated
+
+// History configuration (step back with Backspace, scrub the timeline)
+pub const HISTORY_BUDGET_MB: usize = 64; // Memory for the previous generations, the oldest ones are dropped beyond it (--history-mb)
+pub const HISTORY_KEYFRAME_INTERVAL: u32 = 32; // One full generation every 32 recorded ones, the others only store the cells that changed
+pub const HISTORY_MAX_POPULATION: u64 = 250_000; // Larger patterns are not recorded (no history), taking their snapshot would cost more than the step
+pub const TIMELINE_HEIGHT: u32 = 8; // Height of the timeline bar drawn while paused (buffer pixels)
+pub const COLOR_TIMELINE: u32 = 0x303048FF; // Timeline bar: the recorded generations
+pub const COLOR_TIMELINE_CURSOR: u32 = 0xF0A040FF; // Timeline bar: the generation on screen
+
+// Oscillator and spaceship detection
+pub const PERIOD_WINDOW: usize = 4096; // Generations remembered, a longer period is not detected
+
+// Object census (C key)
+pub const CENSUS_PATH: &str = "census.txt"; // The census table is written here
+pub const CENSUS_MAX_GENERATIONS: u64 = 1024; // Each object runs alone at most this long, then it is "unsettled"
+
+// Lifespan analysis (L key, --lifespan)
+pub const LIFESPAN_MAX_GENERATIONS: u64 = 100_000; // The L key gives up after this many generations
+
+// Random soups (S key, --soup)
+pub const SOUP_SIZE: u32 = 16; // Side of the centred square filled by the S key, 16x16 like apgsearch
+pub const SOUP_DENSITY: f64 = 0.5; // Probability of a live cell
+pub const COLOR_SELECTION: u32 = 0x40A0F0FF; // Blue - outline of the cells selected with Shift+drag, the next soup fills them
+
+// Statistics configuration
+pub const STATS_CAPACITY: usize = 10_000; // Generations kept in the rolling statistics
+pub const STATS_CSV_PATH: &str = "stats.csv"; // Ctrl+E writes the statistics here unless --stats-csv says otherwise
+pub const STATS_GRAPH_WIDTH: u32 = 240; // Population graph overlay (buffer pixels), one column per generation
+pub const STATS_GRAPH_HEIGHT: u32 = 80;
+pub const COLOR_STATS_GRAPH: u32 = 0x80E080FF; // Green - population curve
+pub const COLOR_STATS_BACKGROUND: u32 = 0x202030FF; // Dark blue - graph background
+
 // Panning configuration
 pub const PAN_STEP: f32 = 20.0; // Number of cells to move per arrow key press
 
@@ -34,3 +76,8 @@ pub const PAN_STEP: f32 = 20.0; // Number of cells to move per arrow key press
 pub const COLOR_CELL_ALIVE: u32 = 0xFFFFFFFF; // White - living cells
 pub const COLOR_CELL_DEAD: u32 = 0x101010FF; // Very dark gray - dead cells inside board
 pub const COLOR_OUT_OF_BOUNDS: u32 = 0x1A1A2EFF; // Dark blue-gray - area outside board bounds
+pub const COLOR_DYING_FIRST: u32 = 0xF0A040FF; // Orange - first dying state of a Generations rule (state 2)
+pub const COLOR_DYING_LAST: u32 = 0x402010FF; // Dark brown - last dying state, the gradient runs between the two
+pub const COLOR_SEAM: u32 = 0xD08030FF; // Orange - edges joined by a torus, Klein bottle, cross-surface or sphere
+pub const COLOR_GHOST_ALIVE: u32 = 0x606070FF; // Gray - wrapped copies of living cells seen beyond a seam
+pub const COLOR_ACTIVE_TILE: u32 = 0x183018FF; // Dark green - dead cells of the tiles computed by the last step (active tiles overlay)
@@ -1,4 +1,21 @@
 // src/gol.rs
 
+pub mod bitlife;
+pub mod census;
+pub mod engine;
+pub mod hashlife;
+pub mod hensel;
+pub mod history;
 pub mod life;
+pub mod lifespan;
+pub mod ltl;
+pub mod period;
+pub mod rule;
+pub mod ruletable;
+pub mod soup;
+pub mod sparse;
+pub mod stats;
+pub mod tally;
+pub mod topology;
 pub mod utils;
+pub mod workers;
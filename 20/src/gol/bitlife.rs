@@ -0,0 +1,414 @@
+// gol/bitlife.rs
+
+use crate::gol::rule::Rule;
+use crate::gol::tally::{Frame, Tally};
+use crate::gol::workers::Workers;
+
+// Bit-packed board: 64 cells per u64, row-major, cell x of a row is bit (x % 64) of word (x / 64)
+// Neighbour counts are computed 64 cells at a time with bitwise full adders, so one step
+// costs a few dozen logic operations per word instead of eight lookups per cell.
+// Bits beyond `width` in the last word of a row are always 0.
+pub struct BitBoard {
+    width: u32,
+    height: u32,
+    words_per_row: usize,
+    current: Vec<u64>,
+    next: Vec<u64>,
+    tally: Tally, // births and deaths counted word by word after each step
+    frame: Frame, // of the whole board, for the hash of the tally
+}
+
+impl BitBoard {
+    pub fn new(width: u32, height: u32) -> Self {
+        let words_per_row = (width as usize).div_ceil(64);
+        Self {
+            width,
+            height,
+            words_per_row,
+            current: vec![0; words_per_row * height as usize],
+            next: vec![0; words_per_row * height as usize],
+            tally: Tally::default(),
+            frame: Frame::new(0, 0, width, height),
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    // Pack a dense row-major board (same layout as App::board_current)
+    pub fn load_cells(&mut self, cells: &[bool]) {
+        debug_assert_eq!(cells.len(), (self.width * self.height) as usize);
+        let w = self.width as usize;
+        for (y, row) in cells.chunks_exact(w).enumerate() {
+            let words = &mut self.current[y * self.words_per_row..(y + 1) * self.words_per_row];
+            for (i, chunk) in row.chunks(64).enumerate() {
+                let mut word = 0u64;
+                for (bit, &alive) in chunk.iter().enumerate() {
+                    word |= (alive as u64) << bit;
+                }
+                words[i] = word;
+            }
+        }
+        self.tally = Tally::default();
+        for (y, words) in self.current.chunks_exact(self.words_per_row.max(1)).enumerate() {
+            for (i, &word) in words.iter().enumerate() {
+                self.tally.change_word(&self.frame, (i * 64) as i64, y as i64, 0, word);
+            }
+        }
+        self.tally.start_step();
+    }
+
+    // Unpack into a dense row-major board
+    pub fn write_cells(&self, cells: &mut [bool]) {
+        debug_assert_eq!(cells.len(), (self.width * self.height) as usize);
+        let w = self.width as usize;
+        for (y, row) in cells.chunks_exact_mut(w).enumerate() {
+            let words = &self.current[y * self.words_per_row..(y + 1) * self.words_per_row];
+            for (i, chunk) in row.chunks_mut(64).enumerate() {
+                let word = words[i];
+                for (bit, cell) in chunk.iter_mut().enumerate() {
+                    *cell = (word >> bit) & 1 == 1;
+                }
+            }
+        }
+    }
+
+    pub fn get(&self, x: u32, y: u32) -> bool {
+        let word = self.current[y as usize * self.words_per_row + x as usize / 64];
+        (word >> (x % 64)) & 1 == 1
+    }
+
+    pub fn set(&mut self, x: u32, y: u32, alive: bool) {
+        let word = &mut self.current[y as usize * self.words_per_row + x as usize / 64];
+        self.tally.change(&self.frame, x as i64, y as i64, ((*word >> (x % 64)) & 1) as u8, alive as u8);
+        if alive {
+            *word |= 1 << (x % 64);
+        } else {
+            *word &= !(1 << (x % 64));
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.current.fill(0);
+        self.tally = Tally::default();
+    }
+
+    pub fn population(&self) -> u64 {
+        self.current.iter().map(|w| w.count_ones() as u64).sum()
+    }
+
+    pub fn tally(&self) -> Tally {
+        self.tally
+    }
+
+    // (min_x, min_y, max_x, max_y) of the live cells, None when everything died
+    // Read from the words, the padding bits beyond the width are always 0
+    pub fn bounding_box(&self) -> Option<(i64, i64, i64, i64)> {
+        let mut result: Option<(i64, i64, i64, i64)> = None;
+        for (y, words) in self.current.chunks_exact(self.words_per_row.max(1)).enumerate() {
+            let (Some(first), Some(last)) = (words.iter().position(|&w| w != 0), words.iter().rposition(|&w| w != 0)) else {
+                continue;
+            };
+            let min_x = (first * 64 + words[first].trailing_zeros() as usize) as i64;
+            let max_x = (last * 64 + 63 - words[last].leading_zeros() as usize) as i64;
+            let y = y as i64;
+            result = Some(match result {
+                None => (min_x, y, max_x, y),
+                Some((x0, y0, x1, _)) => (x0.min(min_x), y0, x1.max(max_x), y),
+            });
+        }
+        result
+    }
+
+    // Coordinates of the live cells, row by row, one bit at a time only in the words holding some
+    pub fn live_cells(&self) -> Vec<(i64, i64)> {
+        let mut cells = Vec::with_capacity(self.population() as usize);
+        for (y, words) in self.current.chunks_exact(self.words_per_row.max(1)).enumerate() {
+            for (i, &word) in words.iter().enumerate() {
+                let mut bits = word;
+                while bits != 0 {
+                    cells.push(((i * 64) as i64 + bits.trailing_zeros() as i64, y as i64));
+                    bits &= bits - 1;
+                }
+            }
+        }
+        cells
+    }
+
+    // Compute one generation with the rule (and the topology it carries)
+    pub fn step(&mut self, rule: &Rule) {
+        self.step_parallel(rule, &Workers::single());
+    }
+
+    // Same as step() with the rows split into one band per worker, computed in parallel
+    pub fn step_parallel(&mut self, rule: &Rule, workers: &Workers) {
+        let (w, h) = (self.width as usize, self.height as usize);
+        if w == 0 || h == 0 {
+            return;
+        }
+        let wpr = self.words_per_row;
+        let halo = self.halo(rule);
+        let transition = Transition::new(rule);
+
+        let threads = workers.threads();
+        if threads <= 1 {
+            step_rows(&self.current, &mut self.next, 0, w, h, wpr, &halo, &transition);
+        } else {
+            let rows_per_band = h.div_ceil(threads);
+            let current = &self.current;
+            let (halo, transition) = (&halo, &transition);
+            workers.scope(|scope| {
+                for (i, band) in self.next.chunks_mut(rows_per_band * wpr).enumerate() {
+                    scope.spawn(move |_| step_rows(current, band, i * rows_per_band, w, h, wpr, halo, transition));
+                }
+            });
+        }
+
+        self.tally.start_step();
+        for (y, (before, after)) in self.current.chunks_exact(wpr).zip(self.next.chunks_exact(wpr)).enumerate() {
+            for (i, (&before, &after)) in before.iter().zip(after).enumerate() {
+                self.tally.change_word(&self.frame, (i * 64) as i64, y as i64, before, after);
+            }
+        }
+        std::mem::swap(&mut self.current, &mut self.next);
+    }
+
+    // Rows just above and below the board, and the cells just left/right of every row
+    // (index 0 is row -1, index h + 1 is row h). All zero on a plane.
+    fn halo(&self, rule: &Rule) -> Halo {
+        let (w, h, wpr) = (self.width as usize, self.height as usize, self.words_per_row);
+        let topology = rule.topology();
+        let mut halo_top = vec![0u64; wpr];
+        let mut halo_bottom = vec![0u64; wpr];
+        let mut halo_left = vec![0u64; h + 2];
+        let mut halo_right = vec![0u64; h + 2];
+        if topology.wraps() {
+            let cell = |x: i64, y: i64| -> u64 {
+                match topology.map(x, y, self.width, self.height) {
+                    Some((x, y)) => self.get(x, y) as u64,
+                    None => 0,
+                }
+            };
+            for x in 0..w {
+                halo_top[x / 64] |= cell(x as i64, -1) << (x % 64);
+                halo_bottom[x / 64] |= cell(x as i64, h as i64) << (x % 64);
+            }
+            for (i, y) in (-1..=h as i64).enumerate() {
+                halo_left[i] = cell(-1, y);
+                halo_right[i] = cell(w as i64, y);
+            }
+        }
+        Halo {
+            top: halo_top,
+            bottom: halo_bottom,
+            left: halo_left,
+            right: halo_right,
+        }
+    }
+}
+
+// Cells just outside the board, as seen through the topology
+struct Halo {
+    top: Vec<u64>,
+    bottom: Vec<u64>,
+    left: Vec<u64>,
+    right: Vec<u64>,
+}
+
+// Compute the rows [first_row, first_row + band rows) of the next generation into `band`
+#[allow(clippy::too_many_arguments)]
+fn step_rows(current: &[u64], band: &mut [u64], first_row: usize, w: usize, h: usize, wpr: usize, halo: &Halo, transition: &Transition) {
+    let last_bit = (w - 1) % 64;
+    let last_mask = if last_bit == 63 { u64::MAX } else { (1u64 << (last_bit + 1)) - 1 };
+
+    for (band_y, next_row) in band.chunks_exact_mut(wpr).enumerate() {
+        let y = first_row + band_y;
+        let above: &[u64] = if y == 0 { &halo.top } else { &current[(y - 1) * wpr..y * wpr] };
+        let row = &current[y * wpr..(y + 1) * wpr];
+        let below: &[u64] = if y + 1 == h { &halo.bottom } else { &current[(y + 1) * wpr..(y + 2) * wpr] };
+        let edges = [(halo.left[y], halo.right[y]), (halo.left[y + 1], halo.right[y + 1]), (halo.left[y + 2], halo.right[y + 2])];
+
+        for (i, next_word) in next_row.iter_mut().enumerate() {
+            let (a_w, a, a_e) = shifted(above, i, last_bit, edges[0]);
+            let (c_w, c, c_e) = shifted(row, i, last_bit, edges[1]);
+            let (b_w, b, b_e) = shifted(below, i, last_bit, edges[2]);
+
+            // Carry-save adder tree: 8 one-bit inputs -> 4-bit count (s3 s2 s1 s0)
+            let (sa, ca) = full_add(a_w, a, a_e);
+            let (sb, cb) = full_add(b_w, b, b_e);
+            let (sc, cc) = (c_w ^ c_e, c_w & c_e);
+            let (s0, c2) = full_add(sa, sb, sc);
+            let (t, c4a) = full_add(ca, cb, cc);
+            let (s1, c4b) = (t ^ c2, t & c2);
+            let (s2, s3) = (c4a ^ c4b, c4a & c4b);
+
+            let mut word = transition.apply(c, [s0, s1, s2, s3]);
+            if i + 1 == wpr {
+                word &= last_mask;
+            }
+            *next_word = word;
+        }
+    }
+}
+
+// Birth/survival conditions as lists of neighbour counts, evaluated on bit-planes (shared with gol::sparse)
+pub(crate) struct Transition {
+    birth: Vec<u8>,
+    survival: Vec<u8>,
+}
+
+impl Transition {
+    pub(crate) fn new(rule: &Rule) -> Self {
+        Self {
+            birth: (0..=8).filter(|&n| rule.is_birth(n)).collect(),
+            survival: (0..=8).filter(|&n| rule.is_survival(n)).collect(),
+        }
+    }
+
+    // alive: current cells, count: neighbour count bit-planes (bit k of count n is in count[k])
+    #[inline]
+    pub(crate) fn apply(&self, alive: u64, count: [u64; 4]) -> u64 {
+        let equals = |n: u8| -> u64 {
+            let mut m = u64::MAX;
+            for (k, plane) in count.iter().enumerate() {
+                m &= if (n >> k) & 1 == 1 { *plane } else { !*plane };
+            }
+            m
+        };
+        let born = self.birth.iter().fold(0u64, |acc, &n| acc | equals(n));
+        let survives = self.survival.iter().fold(0u64, |acc, &n| acc | equals(n));
+        (!alive & born) | (alive & survives)
+    }
+}
+
+// --- helpers ----------------------------------------------------------------
+
+// Word i of a row together with the same word shifted so that bit x holds cell x-1 (west) or x+1 (east)
+// `edges` holds the cells just outside the row (left of x=0, right of x=width-1)
+#[inline]
+fn shifted(row: &[u64], i: usize, last_bit: usize, edges: (u64, u64)) -> (u64, u64, u64) {
+    let word = row[i];
+    let from_left = if i == 0 { edges.0 } else { row[i - 1] >> 63 };
+    let west = (word << 1) | from_left;
+    let east = if i + 1 == row.len() {
+        (word >> 1) | (edges.1 << last_bit)
+    } else {
+        (word >> 1) | (row[i + 1] << 63)
+    };
+    (west, word, east)
+}
+
+// (sum, carry) of three one-bit inputs, 64 lanes at a time
+#[inline]
+pub(crate) fn full_add(a: u64, b: u64, c: u64) -> (u64, u64) {
+    let t = a ^ b;
+    (t ^ c, (a & b) | (t & c))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gol::life::{step_life, step_life_parallel};
+    use crate::gol::topology::{Topology, TopologyKind};
+    use crate::gol::utils::{place_pattern_centered, read_rle};
+
+    // Run both engines side by side and compare every generation
+    fn assert_same_generations(cells: &[bool], width: u32, height: u32, rule: &Rule, generations: usize, label: &str) {
+        let mut current = cells.to_vec();
+        let mut next = vec![false; cells.len()];
+        let mut packed = BitBoard::new(width, height);
+        packed.load_cells(cells);
+        let mut unpacked = vec![false; cells.len()];
+
+        for generation in 1..=generations {
+            step_life(&current, &mut next, width, height, rule);
+            std::mem::swap(&mut current, &mut next);
+            packed.step(rule);
+            packed.write_cells(&mut unpacked);
+            assert!(unpacked == current, "{label}: engines differ at generation {generation}");
+            let live: Vec<(i64, i64)> = (0..current.len()).filter(|&i| current[i]).map(|i| ((i % width as usize) as i64, (i / width as usize) as i64)).collect();
+            let bounds = (!live.is_empty()).then(|| {
+                let (xs, ys) = (live.iter().map(|c| c.0), live.iter().map(|c| c.1));
+                (xs.clone().min().unwrap(), ys.clone().min().unwrap(), xs.max().unwrap(), ys.max().unwrap())
+            });
+            assert_eq!(packed.live_cells(), live, "{label}: live cells differ at generation {generation}");
+            assert_eq!(packed.bounding_box(), bounds, "{label}: bounding boxes differ at generation {generation}");
+        }
+    }
+
+    #[test]
+    fn same_generations_as_step_life_for_every_rle_pattern() {
+        let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../rle");
+        for entry in std::fs::read_dir(&dir).expect("rle/ directory") {
+            let path = entry.unwrap().path();
+            if path.extension().and_then(|e| e.to_str()) != Some("rle") {
+                continue;
+            }
+            let (cells, pw, ph, rule) = read_rle(&path).unwrap();
+            if !rule.is_moore_totalistic() {
+                continue; // rule tables like WireWorld only run on the dense engine
+            }
+
+            // Leave room to grow, but keep big patterns (cropped like in the app) fast enough for a debug build
+            let (bw, bh) = ((pw + 64).min(600), (ph + 64).min(600));
+            let mut board = vec![false; (bw * bh) as usize];
+            place_pattern_centered(&mut board, bw, bh, &cells, pw, ph);
+
+            assert_same_generations(&board, bw, bh, &rule, 40, &path.display().to_string());
+        }
+    }
+
+    #[test]
+    fn same_generations_on_wrapped_topologies() {
+        // Odd width so the last word is partial, and a glider soup crossing every edge
+        let (w, h) = (70u32, 66u32);
+        let mut board = vec![false; (w * h) as usize];
+        for (i, cell) in board.iter_mut().enumerate() {
+            *cell = (i * 7919 + i / 13) % 5 < 2;
+        }
+
+        for kind in [TopologyKind::Plane, TopologyKind::Torus, TopologyKind::KleinBottle, TopologyKind::CrossSurface] {
+            let rule = Rule::life().with_topology(Topology::new(kind, w, h));
+            assert_same_generations(&board, w, h, &rule, 30, &format!("{kind:?}"));
+        }
+
+        let square = vec![true; 64 * 64];
+        let sphere = Rule::parse("B36/S23:S64").unwrap();
+        assert_same_generations(&square, 64, 64, &sphere, 30, "Sphere");
+    }
+
+    #[test]
+    fn parallel_bands_match_single_thread() {
+        // 7 bands of uneven height, torus so that bands also read across the seams
+        let (w, h) = (130u32, 97u32);
+        let rule = Rule::parse("B3/S23:T130,97").unwrap();
+        let mut serial = vec![false; (w * h) as usize];
+        for (i, cell) in serial.iter_mut().enumerate() {
+            *cell = (i * 7919 + i / 17) % 3 == 0;
+        }
+        let mut parallel = serial.clone();
+        let mut next = vec![false; serial.len()];
+
+        let mut packed = BitBoard::new(w, h);
+        packed.load_cells(&serial);
+        let mut unpacked = vec![false; serial.len()];
+
+        let workers = Workers::new(7).unwrap();
+        for generation in 1..=20 {
+            step_life(&serial, &mut next, w, h, &rule);
+            std::mem::swap(&mut serial, &mut next);
+            step_life_parallel(&parallel, &mut next, w, h, &rule, &workers);
+            std::mem::swap(&mut parallel, &mut next);
+            assert!(parallel == serial, "dense bands differ at generation {generation}");
+
+            packed.step_parallel(&rule, &workers);
+            packed.write_cells(&mut unpacked);
+            assert!(unpacked == serial, "packed bands differ at generation {generation}");
+        }
+    }
+}
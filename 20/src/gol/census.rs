@@ -0,0 +1,294 @@
+// gol/census.rs
+
+use crate::Result;
+use crate::gol::{
+    engine::LifeEngine,
+    period::{PeriodDetector, Periodicity},
+    rule::Rule,
+    sparse::SparseBoard,
+};
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use std::path::Path;
+
+// apgsearch-style census of the objects on the board:
+// - live cells closer than 3 (Chebyshev distance <= 2) belong to the same object, so a pseudo still life
+//   such as a bi-block is one object here (apgsearch splits them further)
+// - each object runs alone on a sparse board until it repeats (see gol::period)
+// - its name is the apgcode: xs<population> for still lifes, xp<period> for oscillators, xq<period> for spaceships,
+//   then the smallest extended Wechsler encoding over every phase and orientation ("xs4_33" is the block)
+
+// Objects larger than this are counted but not encoded (like apgsearch does)
+const MAX_ENCODED_SIZE: i64 = 40;
+
+const WECHSLER_DIGITS: &[u8; 36] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+
+// Common objects, by apgcode
+const NAMES: [(&str, &str); 15] = [
+    ("xs4_33", "block"),
+    ("xs4_252", "tub"),
+    ("xs5_253", "boat"),
+    ("xs6_356", "ship"),
+    ("xs6_696", "beehive"),
+    ("xs7_2596", "loaf"),
+    ("xs8_6996", "pond"),
+    ("xp2_7", "blinker"),
+    ("xp2_7e", "toad"),
+    ("xp2_318c", "beacon"),
+    ("xp15_4r4z4r4", "pentadecathlon"),
+    ("xq4_153", "glider"),
+    ("xq4_6frc", "LWSS"),
+    ("xq4_27dee6", "MWSS"),
+    ("xq4_27deee6", "HWSS"),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CensusEntry {
+    pub code: String,               // apgcode, or "unsettled_<population>" when the object did not repeat in time
+    pub name: Option<&'static str>, // common name of the apgcode
+    pub periodicity: Option<Periodicity>,
+    pub count: u64,
+}
+
+// Entries sorted by decreasing count, then by apgcode
+pub struct Census {
+    pub entries: Vec<CensusEntry>,
+}
+
+impl Census {
+    // Split the live cells into objects and classify each of them, `max_generations` per object
+    pub fn take(cells: &[(i64, i64)], rule: &Rule, max_generations: u64) -> Result<Self> {
+        if !SparseBoard::supports(rule) {
+            return Err(format!("The census runs objects on the sparse engine, it cannot run {}", rule).into());
+        }
+        let mut counts: HashMap<String, (Option<Periodicity>, u64)> = HashMap::new();
+        for object in split_objects(cells) {
+            let (code, periodicity) = classify(&object, rule, max_generations);
+            counts.entry(code).or_insert((periodicity, 0)).1 += 1;
+        }
+
+        let mut entries: Vec<CensusEntry> = counts
+            .into_iter()
+            .map(|(code, (periodicity, count))| CensusEntry {
+                name: name_of(&code),
+                code,
+                periodicity,
+                count,
+            })
+            .collect();
+        entries.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.code.cmp(&b.code)));
+        Ok(Self { entries })
+    }
+
+    pub fn object_count(&self) -> u64 {
+        self.entries.iter().map(|entry| entry.count).sum()
+    }
+
+    // One line per kind of object: count, apgcode, name, what it does
+    pub fn write_table(&self, mut out: impl Write) -> std::io::Result<()> {
+        writeln!(out, "{:>8}  {:<24}  {:<16}  kind", "count", "apgcode", "name")?;
+        for entry in &self.entries {
+            let kind = entry.periodicity.map(|periodicity| periodicity.to_string()).unwrap_or_else(|| "unsettled".to_string());
+            writeln!(out, "{:>8}  {:<24}  {:<16}  {}", entry.count, entry.code, entry.name.unwrap_or("-"), kind)?;
+        }
+        Ok(())
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let file = std::fs::File::create(path).map_err(|e| -> crate::Error { format!("Cannot create {}: {}", path.display(), e).into() })?;
+        self.write_table(std::io::BufWriter::new(file))
+            .map_err(|e| -> crate::Error { format!("Cannot write {}: {}", path.display(), e).into() })?;
+        Ok(())
+    }
+}
+
+// Connected groups of cells, two cells are connected when |dx| <= 2 and |dy| <= 2
+pub fn split_objects(cells: &[(i64, i64)]) -> Vec<Vec<(i64, i64)>> {
+    let mut unvisited: HashSet<(i64, i64)> = cells.iter().copied().collect();
+    let mut objects = Vec::new();
+    for &start in cells {
+        if !unvisited.remove(&start) {
+            continue;
+        }
+        let mut object = vec![start];
+        let mut next = 0;
+        while next < object.len() {
+            let (x, y) = object[next];
+            next += 1;
+            for dy in -2..=2 {
+                for dx in -2..=2 {
+                    let neighbour = (x + dx, y + dy);
+                    if unvisited.remove(&neighbour) {
+                        object.push(neighbour);
+                    }
+                }
+            }
+        }
+        objects.push(object);
+    }
+    objects
+}
+
+// Common name of an apgcode ("xq4_153" is the glider)
+pub fn name_of(code: &str) -> Option<&'static str> {
+    NAMES.iter().find(|(known, _)| *known == code).map(|&(_, name)| name)
+}
+
+// apgcode of an object run in isolation, and what it turned out to be (None when it did not repeat)
+pub fn classify(object: &[(i64, i64)], rule: &Rule, max_generations: u64) -> (String, Option<Periodicity>) {
+    let mut board = SparseBoard::new();
+    object.iter().for_each(|&(x, y)| LifeEngine::set_cell(&mut board, x, y, true));
+    let mut detector = PeriodDetector::new(max_generations as usize + 1);
+    let mut periodicity = None;
+    for generation in 0..=max_generations {
+        periodicity = detector.observe(generation, board.tally().signature());
+        if periodicity.is_some() {
+            break;
+        }
+        board.step(rule);
+    }
+
+    let Some(periodicity) = periodicity else {
+        return (format!("unsettled_{}", object.len()), None);
+    };
+    let prefix = match periodicity {
+        Periodicity::Extinct => return ("xs0_0".to_string(), Some(periodicity)),
+        Periodicity::StillLife => format!("xs{}", LifeEngine::population(&board)),
+        Periodicity::Oscillator { period } => format!("xp{}", period),
+        Periodicity::Spaceship { period, .. } => format!("xq{}", period),
+    };
+
+    // The board is on a repeated phase, the smallest encoding of the period phases is the name
+    let mut best: Option<String> = None;
+    for _ in 0..periodicity.period() {
+        let Some(encoding) = wechsler(&LifeEngine::live_cells(&board)) else {
+            return (format!("{}_#", prefix), Some(periodicity));
+        };
+        if best.as_ref().is_none_or(|best| (encoding.len(), &encoding) < (best.len(), best)) {
+            best = Some(encoding);
+        }
+        board.step(rule);
+    }
+    (format!("{}_{}", prefix, best.unwrap_or_default()), Some(periodicity))
+}
+
+// Smallest extended Wechsler encoding of the cells over the 8 orientations, None when the bounding box is too large
+fn wechsler(cells: &[(i64, i64)]) -> Option<String> {
+    let min_x = cells.iter().map(|cell| cell.0).min()?;
+    let min_y = cells.iter().map(|cell| cell.1).min()?;
+    let width = cells.iter().map(|cell| cell.0).max()? - min_x + 1;
+    let height = cells.iter().map(|cell| cell.1).max()? - min_y + 1;
+    if width > MAX_ENCODED_SIZE || height > MAX_ENCODED_SIZE {
+        return None;
+    }
+    let alive: HashSet<(i64, i64)> = cells.iter().map(|&(x, y)| (x - min_x, y - min_y)).collect();
+
+    // (columns, rows, origin, column step, row step) of each orientation
+    let (w, h) = (width - 1, height - 1);
+    let orientations = [
+        (width, height, (0, 0), (1, 0), (0, 1)),
+        (width, height, (w, 0), (-1, 0), (0, 1)),
+        (width, height, (0, h), (1, 0), (0, -1)),
+        (width, height, (w, h), (-1, 0), (0, -1)),
+        (height, width, (0, 0), (0, 1), (1, 0)),
+        (height, width, (w, 0), (0, 1), (-1, 0)),
+        (height, width, (0, h), (0, -1), (1, 0)),
+        (height, width, (w, h), (0, -1), (-1, 0)),
+    ];
+    orientations
+        .iter()
+        .map(|&(columns, rows, (ox, oy), (cx, cy), (rx, ry))| {
+            let mut encoding = String::new();
+            // Strips of 5 rows separated by 'z', one digit per column (top row in the lowest bit), runs of empty columns shortened
+            for strip in 0..(rows + 4) / 5 {
+                if strip > 0 {
+                    encoding.push('z');
+                }
+                let mut zeroes = 0;
+                for column in 0..columns {
+                    let digit = (0..5).fold(0, |digit, row| {
+                        let row = strip * 5 + row;
+                        let cell = (ox + cx * column + rx * row, oy + cy * column + ry * row);
+                        digit | ((alive.contains(&cell) as usize) << (row - strip * 5))
+                    });
+                    if digit == 0 {
+                        zeroes += 1;
+                        continue;
+                    }
+                    push_zeroes(&mut encoding, zeroes);
+                    zeroes = 0;
+                    encoding.push(WECHSLER_DIGITS[digit] as char);
+                }
+            }
+            encoding
+        })
+        .min_by(|a, b| a.len().cmp(&b.len()).then_with(|| a.cmp(b)))
+}
+
+// 0, w (2 zeroes), x (3 zeroes), y0..yz (4 to 39 zeroes)
+fn push_zeroes(encoding: &mut String, mut zeroes: usize) {
+    while zeroes > 0 {
+        let run = zeroes.min(39);
+        match run {
+            1 => encoding.push('0'),
+            2 => encoding.push('w'),
+            3 => encoding.push('x'),
+            _ => {
+                encoding.push('y');
+                encoding.push(WECHSLER_DIGITS[run - 4] as char);
+            }
+        }
+        zeroes -= run;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(pattern: &[(i64, i64)], x0: i64, y0: i64) -> Vec<(i64, i64)> {
+        pattern.iter().map(|&(x, y)| (x + x0, y + y0)).collect()
+    }
+
+    #[test]
+    fn common_objects_have_their_apgcode() {
+        let rule = Rule::life();
+        let code = |pattern: &[(i64, i64)]| classify(pattern, &rule, 64).0;
+        assert_eq!(code(&[(0, 0), (1, 0), (0, 1), (1, 1)]), "xs4_33");
+        assert_eq!(code(&[(1, 0), (2, 0), (0, 1), (3, 1), (1, 2), (2, 2)]), "xs6_696");
+        assert_eq!(code(&[(1, 0), (2, 0), (0, 1), (3, 1), (1, 2), (3, 2), (2, 3)]), "xs7_2596");
+        assert_eq!(code(&[(0, 0), (1, 0), (0, 1), (2, 1), (1, 2)]), "xs5_253");
+        assert_eq!(code(&[(0, 1), (1, 1), (2, 1)]), "xp2_7");
+        assert_eq!(code(&[(1, 0), (2, 0), (3, 0), (0, 1), (1, 1), (2, 1)]), "xp2_7e");
+        assert_eq!(code(&[(0, 0), (1, 0), (0, 1), (3, 2), (2, 3), (3, 3)]), "xp2_318c");
+        assert_eq!(code(&[(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]), "xq4_153");
+        assert_eq!(code(&[(1, 0), (4, 0), (0, 1), (0, 2), (4, 2), (0, 3), (1, 3), (2, 3), (3, 3)]), "xq4_6frc");
+        // Two cells die
+        assert_eq!(code(&[(0, 0), (1, 0)]), "xs0_0");
+    }
+
+    #[test]
+    fn census_of_separate_objects() {
+        let block = [(0, 0), (1, 0), (0, 1), (1, 1)];
+        let blinker = [(0, 1), (1, 1), (2, 1)];
+        let mut cells = at(&block, 0, 0);
+        cells.extend(at(&block, 10, 0));
+        cells.extend(at(&block, 100, -40));
+        cells.extend(at(&blinker, 0, 10));
+        // 2 cells away from the first block: one object with it
+        cells.extend(at(&[(3, 0)], 0, 0));
+        assert_eq!(split_objects(&cells).len(), 4);
+
+        let census = Census::take(&cells, &Rule::life(), 64).unwrap();
+        assert_eq!(census.object_count(), 4);
+        let summary: Vec<(&str, u64, Option<&str>)> = census.entries.iter().map(|e| (e.code.as_str(), e.count, e.name)).collect();
+        assert_eq!(summary[0], ("xs4_33", 2, Some("block")));
+        assert_eq!(summary[1], ("xp2_7", 1, Some("blinker")));
+
+        let mut table = Vec::new();
+        census.write_table(&mut table).unwrap();
+        let table = String::from_utf8(table).unwrap();
+        assert!(table.lines().nth(1).unwrap().contains("xs4_33"));
+        assert_eq!(table.lines().count(), 4);
+    }
+}
@@ -0,0 +1,473 @@
+// gol/engine.rs
+
+use crate::gol::{
+    bitlife::BitBoard,
+    hashlife::HashLife,
+    life::{self, ActiveTiles},
+    rule::Rule,
+    sparse::SparseBoard,
+    tally::{Frame, Tally},
+    workers::Workers,
+};
+
+// Interface shared by the simulation back-ends so the app can swap them at runtime
+// - Coordinates are signed 64-bit: unbounded engines (HashLife, sparse) accept any cell,
+//   bounded ones (dense, bit-packed) cover [0, width) x [0, height) and ignore the cells outside
+// - Regions are dense row-major boards of `width * height` cells whose top-left cell is (x0, y0)
+// - Only the two states of Life-like rules are held, multi-state rules stay on App::board_states
+pub trait LifeEngine {
+    fn name(&self) -> &'static str;
+
+    // Advance the engine by one step (2^k generations for HashLife) with the rule (and the topology it carries)
+    // Bounded and sparse engines split the work between the `workers`
+    fn step(&mut self, rule: &Rule, workers: &Workers);
+
+    fn get_cell(&self, x: i64, y: i64) -> bool;
+
+    fn set_cell(&mut self, x: i64, y: i64, alive: bool);
+
+    fn population(&self) -> u64;
+
+    // (min_x, min_y, max_x, max_y) of the live cells, inclusive, None when everything died
+    fn bounding_box(&self) -> Option<(i64, i64, i64, i64)>;
+
+    // Population and births/deaths of the last step, counted while stepping (see gol::tally)
+    fn tally(&self) -> Tally;
+
+    fn clear(&mut self);
+
+    // Replace every cell with the region, nothing of the previous content is kept
+    fn import_region(&mut self, cells: &[bool], x0: i64, y0: i64, width: u32, height: u32);
+
+    // Copy the window [x0, x0 + width) x [y0, y0 + height) into `out` (dead cells outside the engine)
+    fn export_region(&self, out: &mut [bool], x0: i64, y0: i64, width: u32, height: u32);
+
+    // Coordinates of every live cell (no particular order)
+    fn live_cells(&self) -> Vec<(i64, i64)> {
+        let Some((min_x, min_y, max_x, max_y)) = self.bounding_box() else {
+            return Vec::new();
+        };
+        (min_y..=max_y).flat_map(|y| (min_x..=max_x).map(move |x| (x, y))).filter(|&(x, y)| self.get_cell(x, y)).collect()
+    }
+}
+
+// Fixed board, one bool per cell, stepped by gol::life::step_life_active() (only the tiles around the last changes)
+// Also the copy of the board the app renders whichever bounded engine runs
+pub struct DenseBoard {
+    width: u32,
+    height: u32,
+    current: Vec<bool>,
+    next: Vec<bool>,
+    tiles: ActiveTiles,
+    rule: Option<Rule>,   // rule of the last step, the tiles are recomputed when it changes
+    tally: Option<Tally>, // None once cells_mut() handed the cells out, counted again by the next step
+    frame: Frame,         // of the whole board, for the hash of the tally
+}
+
+impl DenseBoard {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            current: vec![false; (width * height) as usize],
+            next: vec![false; (width * height) as usize],
+            tiles: ActiveTiles::new(width, height),
+            rule: None,
+            tally: Some(Tally::default()),
+            frame: Frame::new(0, 0, width, height),
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    // Row-major cells, same layout as gol::life expects
+    pub fn cells(&self) -> &[bool] {
+        &self.current
+    }
+
+    pub fn cells_mut(&mut self) -> &mut [bool] {
+        self.tiles.invalidate();
+        self.tally = None;
+        &mut self.current
+    }
+
+    pub fn frame(&self) -> &Frame {
+        &self.frame
+    }
+
+    // Tiles computed by the last step
+    pub fn active_tiles(&self) -> &ActiveTiles {
+        &self.tiles
+    }
+
+    fn index(&self, x: i64, y: i64) -> Option<usize> {
+        (x >= 0 && y >= 0 && x < self.width as i64 && y < self.height as i64).then(|| (y * self.width as i64 + x) as usize)
+    }
+}
+
+impl LifeEngine for DenseBoard {
+    fn name(&self) -> &'static str {
+        "dense"
+    }
+
+    fn step(&mut self, rule: &Rule, workers: &Workers) {
+        if self.rule.as_ref() != Some(rule) {
+            self.rule = Some(rule.clone());
+            self.tiles.invalidate();
+        }
+        let mut tally = self.tally.take().unwrap_or_else(|| Tally::of_cells(&self.current, &self.frame));
+        tally.start_step();
+        life::step_life_active(&self.current, &mut self.next, self.width, self.height, rule, workers, &mut self.tiles);
+        // Only the cells of the changed tiles can be born or die
+        for (columns, rows) in self.tiles.changed_tiles() {
+            for y in rows {
+                let row = (y * self.width) as usize;
+                for x in columns.clone() {
+                    let (before, after) = (self.current[row + x as usize], self.next[row + x as usize]);
+                    if before != after {
+                        tally.change(&self.frame, x as i64, y as i64, before as u8, after as u8);
+                    }
+                }
+            }
+        }
+        self.tally = Some(tally);
+        std::mem::swap(&mut self.current, &mut self.next);
+    }
+
+    fn get_cell(&self, x: i64, y: i64) -> bool {
+        self.index(x, y).is_some_and(|i| self.current[i])
+    }
+
+    fn set_cell(&mut self, x: i64, y: i64, alive: bool) {
+        if let Some(i) = self.index(x, y) {
+            if let Some(tally) = &mut self.tally {
+                tally.change(&self.frame, x, y, self.current[i] as u8, alive as u8);
+            }
+            self.current[i] = alive;
+            self.tiles.invalidate();
+        }
+    }
+
+    fn population(&self) -> u64 {
+        match self.tally {
+            Some(tally) => tally.population,
+            None => self.current.iter().filter(|&&alive| alive).count() as u64,
+        }
+    }
+
+    fn bounding_box(&self) -> Option<(i64, i64, i64, i64)> {
+        bounding_box_of(&self.current, self.width)
+    }
+
+    fn tally(&self) -> Tally {
+        self.tally.unwrap_or_else(|| Tally::of_cells(&self.current, &self.frame))
+    }
+
+    fn clear(&mut self) {
+        self.current.fill(false);
+        self.tiles.invalidate();
+        self.tally = Some(Tally::default());
+    }
+
+    fn import_region(&mut self, cells: &[bool], x0: i64, y0: i64, width: u32, height: u32) {
+        self.clear();
+        copy_region(cells, x0, y0, width, height, &mut self.current, 0, 0, self.width, self.height);
+        self.tally = Some(Tally::of_cells(&self.current, &self.frame));
+    }
+
+    fn export_region(&self, out: &mut [bool], x0: i64, y0: i64, width: u32, height: u32) {
+        out.fill(false);
+        copy_region(&self.current, 0, 0, self.width, self.height, out, x0, y0, width, height);
+    }
+
+    // Row by row, already in the (y, x) order gol::history expects
+    fn live_cells(&self) -> Vec<(i64, i64)> {
+        let width = self.width.max(1) as usize;
+        self.current
+            .iter()
+            .enumerate()
+            .filter(|&(_, &alive)| alive)
+            .map(|(i, _)| ((i % width) as i64, (i / width) as i64))
+            .collect()
+    }
+}
+
+impl LifeEngine for BitBoard {
+    fn name(&self) -> &'static str {
+        "packed"
+    }
+
+    fn step(&mut self, rule: &Rule, workers: &Workers) {
+        self.step_parallel(rule, workers);
+    }
+
+    fn get_cell(&self, x: i64, y: i64) -> bool {
+        x >= 0 && y >= 0 && x < self.width() as i64 && y < self.height() as i64 && self.get(x as u32, y as u32)
+    }
+
+    fn set_cell(&mut self, x: i64, y: i64, alive: bool) {
+        if x >= 0 && y >= 0 && x < self.width() as i64 && y < self.height() as i64 {
+            self.set(x as u32, y as u32, alive);
+        }
+    }
+
+    fn population(&self) -> u64 {
+        BitBoard::population(self)
+    }
+
+    fn bounding_box(&self) -> Option<(i64, i64, i64, i64)> {
+        BitBoard::bounding_box(self)
+    }
+
+    fn tally(&self) -> Tally {
+        BitBoard::tally(self)
+    }
+
+    fn clear(&mut self) {
+        BitBoard::clear(self);
+    }
+
+    fn import_region(&mut self, cells: &[bool], x0: i64, y0: i64, width: u32, height: u32) {
+        let mut board = vec![false; (self.width() * self.height()) as usize];
+        copy_region(cells, x0, y0, width, height, &mut board, 0, 0, self.width(), self.height());
+        self.load_cells(&board);
+    }
+
+    fn export_region(&self, out: &mut [bool], x0: i64, y0: i64, width: u32, height: u32) {
+        let mut board = vec![false; (self.width() * self.height()) as usize];
+        self.write_cells(&mut board);
+        out.fill(false);
+        copy_region(&board, 0, 0, self.width(), self.height(), out, x0, y0, width, height);
+    }
+
+    fn live_cells(&self) -> Vec<(i64, i64)> {
+        BitBoard::live_cells(self)
+    }
+}
+
+impl LifeEngine for HashLife {
+    fn name(&self) -> &'static str {
+        "hashlife"
+    }
+
+    // HashLife jumps 2^step_exponent() generations at once, the workers are not used
+    fn step(&mut self, rule: &Rule, _workers: &Workers) {
+        self.step_pow2(rule, self.step_exponent());
+    }
+
+    fn get_cell(&self, x: i64, y: i64) -> bool {
+        HashLife::get_cell(self, x, y)
+    }
+
+    fn set_cell(&mut self, x: i64, y: i64, alive: bool) {
+        HashLife::set_cell(self, x, y, alive);
+    }
+
+    fn population(&self) -> u64 {
+        HashLife::population(self)
+    }
+
+    fn bounding_box(&self) -> Option<(i64, i64, i64, i64)> {
+        HashLife::bounding_box(self)
+    }
+
+    fn tally(&self) -> Tally {
+        HashLife::tally(self)
+    }
+
+    fn clear(&mut self) {
+        HashLife::clear(self);
+    }
+
+    fn import_region(&mut self, cells: &[bool], x0: i64, y0: i64, width: u32, height: u32) {
+        self.load_cells(cells, width, height, x0, y0);
+    }
+
+    fn export_region(&self, out: &mut [bool], x0: i64, y0: i64, width: u32, height: u32) {
+        self.write_region(out, x0, y0, width, height);
+    }
+
+    fn live_cells(&self) -> Vec<(i64, i64)> {
+        HashLife::live_cells(self)
+    }
+}
+
+impl LifeEngine for SparseBoard {
+    fn name(&self) -> &'static str {
+        "sparse"
+    }
+
+    fn step(&mut self, rule: &Rule, workers: &Workers) {
+        self.step_parallel(rule, workers);
+    }
+
+    fn get_cell(&self, x: i64, y: i64) -> bool {
+        SparseBoard::get_cell(self, x, y)
+    }
+
+    fn set_cell(&mut self, x: i64, y: i64, alive: bool) {
+        SparseBoard::set_cell(self, x, y, alive);
+    }
+
+    fn population(&self) -> u64 {
+        SparseBoard::population(self)
+    }
+
+    fn bounding_box(&self) -> Option<(i64, i64, i64, i64)> {
+        SparseBoard::bounding_box(self)
+    }
+
+    fn tally(&self) -> Tally {
+        SparseBoard::tally(self)
+    }
+
+    fn clear(&mut self) {
+        SparseBoard::clear(self);
+    }
+
+    fn import_region(&mut self, cells: &[bool], x0: i64, y0: i64, width: u32, height: u32) {
+        self.load_cells(cells, width, height, x0, y0);
+    }
+
+    fn export_region(&self, out: &mut [bool], x0: i64, y0: i64, width: u32, height: u32) {
+        self.write_region(out, x0, y0, width, height);
+    }
+
+    fn live_cells(&self) -> Vec<(i64, i64)> {
+        SparseBoard::live_cells(self)
+    }
+}
+
+// Copy the cells a source region shares with a destination region (both row-major, placed by their top-left cell)
+#[allow(clippy::too_many_arguments)]
+fn copy_region(src: &[bool], src_x: i64, src_y: i64, src_w: u32, src_h: u32, dst: &mut [bool], dst_x: i64, dst_y: i64, dst_w: u32, dst_h: u32) {
+    let (x_start, x_end) = (src_x.max(dst_x), (src_x + src_w as i64).min(dst_x + dst_w as i64));
+    let (y_start, y_end) = (src_y.max(dst_y), (src_y + src_h as i64).min(dst_y + dst_h as i64));
+    if x_start >= x_end {
+        return;
+    }
+    for y in y_start..y_end {
+        let from = ((y - src_y) * src_w as i64 + x_start - src_x) as usize;
+        let to = ((y - dst_y) * dst_w as i64 + x_start - dst_x) as usize;
+        let len = (x_end - x_start) as usize;
+        dst[to..to + len].copy_from_slice(&src[from..from + len]);
+    }
+}
+
+// Bounding box of the live cells of a row-major board
+fn bounding_box_of(cells: &[bool], width: u32) -> Option<(i64, i64, i64, i64)> {
+    if width == 0 {
+        return None;
+    }
+    let mut result: Option<(i64, i64, i64, i64)> = None;
+    for (y, row) in cells.chunks_exact(width as usize).enumerate() {
+        let (Some(first), Some(last)) = (row.iter().position(|&c| c), row.iter().rposition(|&c| c)) else {
+            continue;
+        };
+        let y = y as i64;
+        result = Some(match result {
+            None => (first as i64, y, last as i64, y),
+            Some((x0, y0, x1, _)) => (x0.min(first as i64), y0, x1.max(last as i64), y),
+        });
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    // Glider heading south-east, top-left cell at (x, y)
+    const GLIDER: [bool; 9] = [false, true, false, false, false, true, true, true, true];
+
+    fn engines() -> Vec<Box<dyn LifeEngine>> {
+        vec![
+            Box::new(DenseBoard::new(40, 30)),
+            Box::new(BitBoard::new(40, 30)),
+            Box::new(HashLife::new(Rule::life())),
+            Box::new(SparseBoard::new()),
+        ]
+    }
+
+    #[test]
+    fn every_engine_runs_the_same_glider() {
+        let rule = Rule::life();
+        let workers = Workers::new(2).unwrap();
+        let mut reference: Option<Vec<(i64, i64)>> = None;
+        for mut engine in engines() {
+            engine.import_region(&GLIDER, 5, 7, 3, 3);
+            assert_eq!(engine.population(), 5, "{}", engine.name());
+            assert_eq!(engine.bounding_box(), Some((5, 7, 7, 9)), "{}", engine.name());
+            for _ in 0..8 {
+                engine.step(&rule, &workers);
+            }
+            // Two glider periods: one cell further right and down twice
+            assert_eq!(engine.bounding_box(), Some((7, 9, 9, 11)), "{}", engine.name());
+            let mut cells = engine.live_cells();
+            cells.sort_unstable();
+            assert_eq!(*reference.get_or_insert_with(|| cells.clone()), cells, "{}", engine.name());
+        }
+    }
+
+    #[test]
+    fn every_engine_counts_births_and_deaths() {
+        // R-pentomino and a lone cell dying at once, the R stays on the 40x30 board for 30 generations
+        const R_PENTOMINO: [bool; 9] = [false, true, true, true, true, false, false, true, false];
+        let rule = Rule::life();
+        let workers = Workers::new(2).unwrap();
+        for mut engine in engines() {
+            engine.import_region(&R_PENTOMINO, 18, 13, 3, 3);
+            engine.set_cell(1, 1, true);
+            for generation in 1..=30 {
+                let before: HashSet<(i64, i64)> = engine.live_cells().into_iter().collect();
+                engine.step(&rule, &workers);
+                let after: HashSet<(i64, i64)> = engine.live_cells().into_iter().collect();
+                let tally = engine.tally();
+                let expected = (after.len() as u64, after.difference(&before).count() as u64, before.difference(&after).count() as u64);
+                assert_eq!((tally.population, tally.births, tally.deaths), expected, "{} generation {}", engine.name(), generation);
+                // The hash kept while stepping is the one of the cells
+                let mut cells = Tally::default();
+                after.iter().for_each(|&(x, y)| cells.change_at(x, y, 0, 1));
+                assert_eq!(tally.signature(), cells.signature(), "{} generation {}", engine.name(), generation);
+            }
+        }
+
+        // Cells written behind the dense board's back are counted again by the next step
+        let mut dense = DenseBoard::new(40, 30);
+        dense.cells_mut()[205..208].fill(true);
+        dense.step(&rule, &workers);
+        let tally = dense.tally();
+        assert_eq!((tally.population, tally.births, tally.deaths), (3, 2, 2));
+    }
+
+    #[test]
+    fn cells_survive_a_trip_through_every_engine() {
+        let mut engines = engines();
+        engines[0].import_region(&GLIDER, 10, 4, 3, 3);
+        engines[0].set_cell(39, 29, true);
+        engines[0].set_cell(40, 29, true); // off the board, ignored
+        for i in 1..engines.len() {
+            let (mut window, mut copy) = (vec![false; 40 * 30], vec![false; 40 * 30]);
+            engines[i - 1].export_region(&mut window, 0, 0, 40, 30);
+            engines[i].import_region(&window, 0, 0, 40, 30);
+            engines[i].export_region(&mut copy, 0, 0, 40, 30);
+            assert_eq!(window, copy, "{}", engines[i].name());
+            assert_eq!(engines[i].population(), 6, "{}", engines[i].name());
+            assert!(engines[i].get_cell(39, 29) && !engines[i].get_cell(40, 29), "{}", engines[i].name());
+        }
+
+        let sparse = &mut engines[3];
+        sparse.set_cell(-1000, 5000, true);
+        assert_eq!(sparse.bounding_box(), Some((-1000, 4, 39, 5000)));
+        sparse.clear();
+        assert_eq!(sparse.population(), 0);
+        assert_eq!(sparse.bounding_box(), None);
+    }
+}
@@ -0,0 +1,632 @@
+// gol/hashlife.rs
+
+use crate::gol::rule::Rule;
+use crate::gol::tally::Tally;
+use crate::gol::topology::Topology;
+use std::collections::HashMap;
+
+// HashLife (Gosper): the universe is a quadtree whose identical sub-trees are shared (hash-consing)
+// and the future of every node is memoized, so regular patterns can jump 2^k generations at once.
+// See https://johnhw.github.io/hashlife/index.md.html
+//
+// - Level 0 nodes are single cells, a level k node is a 2^k x 2^k square
+// - successor(node, j) is the centre half of a level k node advanced 2^j generations (j <= k-2)
+// - The universe is unbounded (signed 64-bit coordinates), the board is only a window on it
+
+type NodeId = u32;
+
+const DEAD: NodeId = 0;
+const ALIVE: NodeId = 1;
+
+// Rebuild the node store when it grows past this many nodes (32 bytes each)
+const GC_THRESHOLD: usize = 1 << 22;
+
+#[derive(Debug, Clone, Copy)]
+struct Node {
+    nw: NodeId,
+    ne: NodeId,
+    sw: NodeId,
+    se: NodeId,
+    level: u8,
+    population: u64,
+}
+
+pub struct HashLife {
+    rule: Rule,
+    nodes: Vec<Node>,
+    index: HashMap<[NodeId; 4], NodeId>,    // canonical node for 4 children
+    results: HashMap<(NodeId, u8), NodeId>, // memoized successor(node, j)
+    tallies: HashMap<NodeId, Tally>,        // memoized tally of a node's cells, relative to its top-left corner
+    empty: Vec<NodeId>,                     // empty[k] is the empty node of level k
+    root: NodeId,
+    origin_x: i64, // universe coordinates of the root's top-left cell
+    origin_y: i64,
+    generation: u64,
+    step_exponent: u8, // LifeEngine::step() advances 2^step_exponent generations
+    tally: Tally,      // births and deaths of the last step are net changes over its 2^k generations
+}
+
+impl HashLife {
+    pub fn new(rule: Rule) -> Self {
+        let leaf = |population| Node {
+            nw: DEAD,
+            ne: DEAD,
+            sw: DEAD,
+            se: DEAD,
+            level: 0,
+            population,
+        };
+        let mut life = Self {
+            rule,
+            nodes: vec![leaf(0), leaf(1)],
+            index: HashMap::new(),
+            results: HashMap::new(),
+            tallies: HashMap::new(),
+            empty: vec![DEAD],
+            root: DEAD,
+            origin_x: 0,
+            origin_y: 0,
+            generation: 0,
+            step_exponent: 0,
+            tally: Tally::default(),
+        };
+        life.clear();
+        life
+    }
+
+    // HashLife needs a Life-like B/S rule on the 8 surrounding cells (see Rule::is_moore_totalistic()) whose empty universe stays empty (no B0) and has no edge to glue (no bounded grid)
+    pub fn supports(rule: &Rule) -> bool {
+        rule.is_moore_totalistic() && !rule.is_birth(0) && rule.topology() == Topology::plane()
+    }
+
+    pub fn clear(&mut self) {
+        self.root = self.empty(3);
+        self.origin_x = -4;
+        self.origin_y = -4;
+        self.generation = 0;
+        self.tally = Tally::default();
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub fn population(&self) -> u64 {
+        self.nodes[self.root as usize].population
+    }
+
+    pub fn tally(&self) -> Tally {
+        self.tally
+    }
+
+    pub fn step_exponent(&self) -> u8 {
+        self.step_exponent
+    }
+
+    pub fn set_step_exponent(&mut self, exponent: u8) {
+        self.step_exponent = exponent;
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    // Replace the universe with a dense row-major pattern whose top-left cell lands on (offset_x, offset_y)
+    pub fn load_cells(&mut self, cells: &[bool], width: u32, height: u32, offset_x: i64, offset_y: i64) {
+        debug_assert_eq!(cells.len(), (width * height) as usize);
+        let mut level = 3u8;
+        while (1u64 << level) < width.max(height) as u64 {
+            level += 1;
+        }
+        self.root = self.build(level, 0, 0, cells, width as i64, height as i64);
+        self.origin_x = offset_x;
+        self.origin_y = offset_y;
+        self.generation = 0;
+        self.tally = self.node_tally(self.root).moved(offset_x, offset_y);
+    }
+
+    // Copy the universe window [x0, x0 + width) x [y0, y0 + height) into a dense row-major board
+    pub fn write_region(&self, out: &mut [bool], x0: i64, y0: i64, width: u32, height: u32) {
+        debug_assert_eq!(out.len(), (width * height) as usize);
+        out.fill(false);
+        let region = (x0, y0, width as i64, height as i64);
+        self.write_node(self.root, self.origin_x, self.origin_y, out, region);
+    }
+
+    pub fn get_cell(&self, x: i64, y: i64) -> bool {
+        let mut id = self.root;
+        let (mut nx, mut ny) = (self.origin_x, self.origin_y);
+        let size = 1i64 << self.level(id);
+        if x < nx || y < ny || x >= nx + size || y >= ny + size {
+            return false;
+        }
+        while self.level(id) > 0 {
+            let node = self.nodes[id as usize];
+            let half = 1i64 << (node.level - 1);
+            let (right, bottom) = (x >= nx + half, y >= ny + half);
+            id = match (right, bottom) {
+                (false, false) => node.nw,
+                (true, false) => node.ne,
+                (false, true) => node.sw,
+                (true, true) => node.se,
+            };
+            nx += if right { half } else { 0 };
+            ny += if bottom { half } else { 0 };
+        }
+        id == ALIVE
+    }
+
+    pub fn set_cell(&mut self, x: i64, y: i64, alive: bool) {
+        loop {
+            let size = 1i64 << self.level(self.root);
+            if x >= self.origin_x && y >= self.origin_y && x < self.origin_x + size && y < self.origin_y + size {
+                break;
+            }
+            self.expand();
+        }
+        self.tally.change_at(x, y, self.get_cell(x, y) as u8, alive as u8);
+        self.root = self.set_in(self.root, x - self.origin_x, y - self.origin_y, alive);
+    }
+
+    // Coordinates of every live cell (no particular order)
+    pub fn live_cells(&self) -> Vec<(i64, i64)> {
+        let mut cells = Vec::with_capacity(self.population() as usize);
+        self.collect_cells(self.root, self.origin_x, self.origin_y, &mut cells);
+        cells
+    }
+
+    // (min_x, min_y, max_x, max_y) of the live cells, inclusive, None when the universe is empty
+    pub fn bounding_box(&self) -> Option<(i64, i64, i64, i64)> {
+        let mut memo = HashMap::new();
+        self.bbox(self.root, &mut memo)
+            .map(|(x0, y0, x1, y1)| (self.origin_x + x0, self.origin_y + y0, self.origin_x + x1, self.origin_y + y1))
+    }
+
+    // Advance 2^exponent generations in one call
+    pub fn step_pow2(&mut self, rule: &Rule, exponent: u8) {
+        if *rule != self.rule {
+            // Memoized futures depend on the rule
+            self.rule = rule.clone();
+            self.results.clear();
+        }
+
+        let (before, before_level, before_x) = (self.root, self.level(self.root), self.origin_x);
+
+        // Make sure nothing can escape the centre half that successor() returns:
+        // the pattern has to sit in the centre quarter of a root of level >= exponent + 3
+        while self.level(self.root) < exponent + 2 || !self.is_padded() {
+            self.expand();
+        }
+        self.expand();
+
+        let level = self.level(self.root);
+        self.root = self.successor(self.root, exponent);
+        self.origin_x += 1i64 << (level - 2);
+        self.origin_y += 1i64 << (level - 2);
+        self.generation = self.generation.saturating_add(1u64 << exponent);
+
+        self.shrink();
+        // Births and deaths from the cells both roots have in common, before the node ids change
+        debug_assert_eq!(before_x + (1i64 << (before_level - 1)), self.origin_x + (1i64 << (self.level(self.root) - 1)));
+        let common = self.common(before, self.root);
+        self.tally = self.node_tally(self.root).moved(self.origin_x, self.origin_y);
+        self.tally.births = self.population() - common;
+        self.tally.deaths = self.node(before).population - common;
+        if self.nodes.len() > GC_THRESHOLD {
+            self.collect_garbage();
+        }
+    }
+
+    // --- quadtree plumbing --------------------------------------------------
+
+    fn level(&self, id: NodeId) -> u8 {
+        self.nodes[id as usize].level
+    }
+
+    fn node(&self, id: NodeId) -> Node {
+        self.nodes[id as usize]
+    }
+
+    // Canonical node with these 4 children
+    fn join(&mut self, nw: NodeId, ne: NodeId, sw: NodeId, se: NodeId) -> NodeId {
+        let key = [nw, ne, sw, se];
+        if let Some(&id) = self.index.get(&key) {
+            return id;
+        }
+        let population = [nw, ne, sw, se].iter().map(|&c| self.nodes[c as usize].population).sum();
+        let id = self.nodes.len() as NodeId;
+        self.nodes.push(Node {
+            nw,
+            ne,
+            sw,
+            se,
+            level: self.level(nw) + 1,
+            population,
+        });
+        self.index.insert(key, id);
+        id
+    }
+
+    fn empty(&mut self, level: u8) -> NodeId {
+        while self.empty.len() <= level as usize {
+            let e = *self.empty.last().unwrap();
+            let bigger = self.join(e, e, e, e);
+            self.empty.push(bigger);
+        }
+        self.empty[level as usize]
+    }
+
+    // Double the root size, keeping it centred
+    fn expand(&mut self) {
+        let shift = 1i64 << (self.level(self.root) - 1);
+        self.root = self.grown(self.root);
+        self.origin_x -= shift;
+        self.origin_y -= shift;
+    }
+
+    // Node of twice the size with `id` in its centre
+    fn grown(&mut self, id: NodeId) -> NodeId {
+        let node = self.node(id);
+        let e = self.empty(node.level - 1);
+        let nw = self.join(e, e, e, node.nw);
+        let ne = self.join(e, e, node.ne, e);
+        let sw = self.join(e, node.sw, e, e);
+        let se = self.join(node.se, e, e, e);
+        self.join(nw, ne, sw, se)
+    }
+
+    // Live cells two roots with the same centre have in common (every root is, see expand(), successor() and shrink())
+    fn common(&mut self, mut a: NodeId, mut b: NodeId) -> u64 {
+        while self.level(a) < self.level(b) {
+            a = self.grown(a);
+        }
+        while self.level(b) < self.level(a) {
+            b = self.grown(b);
+        }
+        self.intersection(a, b, &mut HashMap::new())
+    }
+
+    // Live cells of two nodes of the same level at the same place, memoized per pair (the unchanged sub-trees are shared)
+    fn intersection(&self, a: NodeId, b: NodeId, memo: &mut HashMap<(NodeId, NodeId), u64>) -> u64 {
+        let (node_a, node_b) = (self.node(a), self.node(b));
+        if a == b {
+            return node_a.population;
+        }
+        if node_a.population == 0 || node_b.population == 0 || node_a.level == 0 {
+            return 0;
+        }
+        if let Some(&count) = memo.get(&(a, b)) {
+            return count;
+        }
+        let count = self.intersection(node_a.nw, node_b.nw, memo)
+            + self.intersection(node_a.ne, node_b.ne, memo)
+            + self.intersection(node_a.sw, node_b.sw, memo)
+            + self.intersection(node_a.se, node_b.se, memo);
+        memo.insert((a, b), count);
+        count
+    }
+
+    // Tally of the cells of a node relative to its top-left corner, memoized per node like bbox() (until the next GC)
+    fn node_tally(&mut self, id: NodeId) -> Tally {
+        let node = self.node(id);
+        if node.population == 0 {
+            return Tally::default();
+        }
+        if node.level == 0 {
+            return Tally::of_cell(0, 0);
+        }
+        if let Some(&tally) = self.tallies.get(&id) {
+            return tally;
+        }
+        let (nw, ne, sw, se) = (self.node_tally(node.nw), self.node_tally(node.ne), self.node_tally(node.sw), self.node_tally(node.se));
+        let tally = Tally::of_quadrants(&nw, &ne, &sw, &se, 1i64 << (node.level - 1));
+        self.tallies.insert(id, tally);
+        tally
+    }
+
+    // Halve the root while all live cells stay in its centre half (keeps the tree small between steps)
+    fn shrink(&mut self) {
+        while self.level(self.root) > 3 && self.is_padded() {
+            let level = self.level(self.root);
+            self.root = self.centre(self.root);
+            self.origin_x += 1i64 << (level - 2);
+            self.origin_y += 1i64 << (level - 2);
+        }
+    }
+
+    // True when every live cell is inside the centre half of the root
+    fn is_padded(&self) -> bool {
+        let root = self.node(self.root);
+        if root.level < 2 {
+            return false;
+        }
+        let (nw, ne, sw, se) = (self.node(root.nw), self.node(root.ne), self.node(root.sw), self.node(root.se));
+        let outer = [nw.nw, nw.ne, nw.sw, ne.nw, ne.ne, ne.se, sw.nw, sw.sw, sw.se, se.ne, se.sw, se.se];
+        outer.iter().all(|&id| self.nodes[id as usize].population == 0)
+    }
+
+    // Centre half of a node, no time evolution
+    fn centre(&mut self, id: NodeId) -> NodeId {
+        let n = self.node(id);
+        let (nw, ne, sw, se) = (self.node(n.nw), self.node(n.ne), self.node(n.sw), self.node(n.se));
+        self.join(nw.se, ne.sw, sw.ne, se.nw)
+    }
+
+    // Centre half of a level k node advanced 2^j generations (k >= 2, j <= k - 2)
+    fn successor(&mut self, id: NodeId, j: u8) -> NodeId {
+        let node = self.node(id);
+        debug_assert!(node.level >= 2 && j <= node.level - 2);
+
+        if node.population == 0 {
+            return self.empty(node.level - 1);
+        }
+        if let Some(&result) = self.results.get(&(id, j)) {
+            return result;
+        }
+
+        let result = if node.level == 2 {
+            self.base_case(id)
+        } else {
+            let (nw, ne, sw, se) = (self.node(node.nw), self.node(node.ne), self.node(node.sw), self.node(node.se));
+
+            // The 9 overlapping sub-squares of level k-1
+            let n00 = node.nw;
+            let n01 = self.join(nw.ne, ne.nw, nw.se, ne.sw);
+            let n02 = node.ne;
+            let n10 = self.join(nw.sw, nw.se, sw.nw, sw.ne);
+            let n11 = self.join(nw.se, ne.sw, sw.ne, se.nw);
+            let n12 = self.join(ne.sw, ne.se, se.nw, se.ne);
+            let n20 = node.sw;
+            let n21 = self.join(sw.ne, se.nw, sw.se, se.sw);
+            let n22 = node.se;
+            let squares = [n00, n01, n02, n10, n11, n12, n20, n21, n22];
+
+            // First half: full speed (2^(k-3) generations) or no time at all for smaller steps
+            let full_speed = j == node.level - 2;
+            let mut r = [DEAD; 9];
+            for (i, &square) in squares.iter().enumerate() {
+                r[i] = if full_speed { self.successor(square, node.level - 3) } else { self.centre(square) };
+            }
+
+            // Second half: the remaining 2^(k-3) generations (full speed) or the whole 2^j
+            let second = if full_speed { node.level - 3 } else { j };
+            let q_nw = self.join(r[0], r[1], r[3], r[4]);
+            let q_ne = self.join(r[1], r[2], r[4], r[5]);
+            let q_sw = self.join(r[3], r[4], r[6], r[7]);
+            let q_se = self.join(r[4], r[5], r[7], r[8]);
+            let a = self.successor(q_nw, second);
+            let b = self.successor(q_ne, second);
+            let c = self.successor(q_sw, second);
+            let d = self.successor(q_se, second);
+            self.join(a, b, c, d)
+        };
+
+        self.results.insert((id, j), result);
+        result
+    }
+
+    // Level 2 node (4x4 cells): centre 2x2 after one generation
+    fn base_case(&mut self, id: NodeId) -> NodeId {
+        let node = self.node(id);
+        let mut cells = [[false; 4]; 4];
+        for (quadrant, &child) in [node.nw, node.ne, node.sw, node.se].iter().enumerate() {
+            let c = self.node(child);
+            let (qx, qy) = ((quadrant % 2) * 2, (quadrant / 2) * 2);
+            cells[qy][qx] = c.nw == ALIVE;
+            cells[qy][qx + 1] = c.ne == ALIVE;
+            cells[qy + 1][qx] = c.sw == ALIVE;
+            cells[qy + 1][qx + 1] = c.se == ALIVE;
+        }
+
+        let mut next = [DEAD; 4];
+        for (i, (x, y)) in [(1usize, 1usize), (2, 1), (1, 2), (2, 2)].into_iter().enumerate() {
+            let mut n = 0u8;
+            for dy in 0..3 {
+                for dx in 0..3 {
+                    if (dx, dy) != (1, 1) && cells[y + dy - 1][x + dx - 1] {
+                        n += 1;
+                    }
+                }
+            }
+            next[i] = if self.rule.next_state(cells[y][x], n) { ALIVE } else { DEAD };
+        }
+        self.join(next[0], next[1], next[2], next[3])
+    }
+
+    // Build the node of `level` whose top-left cell is pattern cell (x, y)
+    fn build(&mut self, level: u8, x: i64, y: i64, cells: &[bool], width: i64, height: i64) -> NodeId {
+        let size = 1i64 << level;
+        if x >= width || y >= height || x + size <= 0 || y + size <= 0 {
+            return self.empty(level);
+        }
+        if level == 0 {
+            return if cells[(y * width + x) as usize] { ALIVE } else { DEAD };
+        }
+        let half = size / 2;
+        let nw = self.build(level - 1, x, y, cells, width, height);
+        let ne = self.build(level - 1, x + half, y, cells, width, height);
+        let sw = self.build(level - 1, x, y + half, cells, width, height);
+        let se = self.build(level - 1, x + half, y + half, cells, width, height);
+        self.join(nw, ne, sw, se)
+    }
+
+    // Copy of `id` with cell (x, y) (relative to the node) set
+    fn set_in(&mut self, id: NodeId, x: i64, y: i64, alive: bool) -> NodeId {
+        let node = self.node(id);
+        if node.level == 0 {
+            return if alive { ALIVE } else { DEAD };
+        }
+        let half = 1i64 << (node.level - 1);
+        let (mut nw, mut ne, mut sw, mut se) = (node.nw, node.ne, node.sw, node.se);
+        match (x >= half, y >= half) {
+            (false, false) => nw = self.set_in(nw, x, y, alive),
+            (true, false) => ne = self.set_in(ne, x - half, y, alive),
+            (false, true) => sw = self.set_in(sw, x, y - half, alive),
+            (true, true) => se = self.set_in(se, x - half, y - half, alive),
+        }
+        self.join(nw, ne, sw, se)
+    }
+
+    // Recursive copy of the live cells of `id` (top-left at nx, ny) that fall in `region` (x, y, w, h)
+    fn write_node(&self, id: NodeId, nx: i64, ny: i64, out: &mut [bool], region: (i64, i64, i64, i64)) {
+        let node = self.node(id);
+        let (rx, ry, rw, rh) = region;
+        let size = 1i64 << node.level;
+        if node.population == 0 || nx >= rx + rw || ny >= ry + rh || nx + size <= rx || ny + size <= ry {
+            return;
+        }
+        if node.level == 0 {
+            out[((ny - ry) * rw + (nx - rx)) as usize] = true;
+            return;
+        }
+        let half = size / 2;
+        self.write_node(node.nw, nx, ny, out, region);
+        self.write_node(node.ne, nx + half, ny, out, region);
+        self.write_node(node.sw, nx, ny + half, out, region);
+        self.write_node(node.se, nx + half, ny + half, out, region);
+    }
+
+    fn collect_cells(&self, id: NodeId, nx: i64, ny: i64, cells: &mut Vec<(i64, i64)>) {
+        let node = self.node(id);
+        if node.population == 0 {
+            return;
+        }
+        if node.level == 0 {
+            cells.push((nx, ny));
+            return;
+        }
+        let half = 1i64 << (node.level - 1);
+        self.collect_cells(node.nw, nx, ny, cells);
+        self.collect_cells(node.ne, nx + half, ny, cells);
+        self.collect_cells(node.sw, nx, ny + half, cells);
+        self.collect_cells(node.se, nx + half, ny + half, cells);
+    }
+
+    // Bounding box relative to the node's top-left corner, memoized per node (shared sub-trees are visited once)
+    fn bbox(&self, id: NodeId, memo: &mut HashMap<NodeId, Option<(i64, i64, i64, i64)>>) -> Option<(i64, i64, i64, i64)> {
+        let node = self.node(id);
+        if node.population == 0 {
+            return None;
+        }
+        if node.level == 0 {
+            return Some((0, 0, 0, 0));
+        }
+        if let Some(&b) = memo.get(&id) {
+            return b;
+        }
+        let half = 1i64 << (node.level - 1);
+        let mut result: Option<(i64, i64, i64, i64)> = None;
+        for (child, dx, dy) in [(node.nw, 0, 0), (node.ne, half, 0), (node.sw, 0, half), (node.se, half, half)] {
+            if let Some((x0, y0, x1, y1)) = self.bbox(child, memo) {
+                let b = (x0 + dx, y0 + dy, x1 + dx, y1 + dy);
+                result = Some(match result {
+                    None => b,
+                    Some(r) => (r.0.min(b.0), r.1.min(b.1), r.2.max(b.2), r.3.max(b.3)),
+                });
+            }
+        }
+        memo.insert(id, result);
+        result
+    }
+
+    // Keep only the nodes reachable from the root (memoized results are dropped)
+    fn collect_garbage(&mut self) {
+        let before = self.nodes.len();
+        let old_nodes = std::mem::take(&mut self.nodes);
+        self.nodes = old_nodes[..2].to_vec();
+        self.index.clear();
+        self.results.clear();
+        self.tallies.clear();
+        self.empty = vec![DEAD];
+
+        let mut remap: HashMap<NodeId, NodeId> = HashMap::from([(DEAD, DEAD), (ALIVE, ALIVE)]);
+        self.root = self.copy_node(&old_nodes, self.root, &mut remap);
+        log::debug!("HashLife GC: {} -> {} nodes", before, self.nodes.len());
+    }
+
+    fn copy_node(&mut self, old_nodes: &[Node], id: NodeId, remap: &mut HashMap<NodeId, NodeId>) -> NodeId {
+        if let Some(&new_id) = remap.get(&id) {
+            return new_id;
+        }
+        let node = old_nodes[id as usize];
+        let nw = self.copy_node(old_nodes, node.nw, remap);
+        let ne = self.copy_node(old_nodes, node.ne, remap);
+        let sw = self.copy_node(old_nodes, node.sw, remap);
+        let se = self.copy_node(old_nodes, node.se, remap);
+        let new_id = self.join(nw, ne, sw, se);
+        remap.insert(id, new_id);
+        new_id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gol::life::step_life;
+    use crate::gol::utils::{place_pattern_centered, read_rle};
+
+    fn glider() -> Vec<bool> {
+        vec![false, true, false, false, false, true, true, true, true]
+    }
+
+    #[test]
+    fn glider_jumps_2_pow_k_generations() {
+        let mut life = HashLife::new(Rule::life());
+        life.load_cells(&glider(), 3, 3, 0, 0);
+        assert_eq!(life.population(), 5);
+        assert_eq!(life.bounding_box(), Some((0, 0, 2, 2)));
+
+        // A glider moves one cell diagonally (down-right) every 4 generations
+        life.step_pow2(&Rule::life(), 10);
+        assert_eq!(life.generation(), 1024);
+        assert_eq!(life.population(), 5);
+        assert_eq!(life.bounding_box(), Some((256, 256, 258, 258)));
+        // Net changes over the jump: the 5 cells are somewhere else
+        assert_eq!((life.tally().births, life.tally().deaths), (5, 5));
+        assert!(life.get_cell(257, 256) && !life.get_cell(256, 256));
+    }
+
+    #[test]
+    fn set_and_get_cells_anywhere() {
+        let mut life = HashLife::new(Rule::life());
+        life.set_cell(-1_000_000, 5, true);
+        life.set_cell(42, -7, true);
+        assert!(life.get_cell(-1_000_000, 5) && life.get_cell(42, -7));
+        assert_eq!(life.population(), 2);
+        assert_eq!(life.bounding_box(), Some((-1_000_000, -7, 42, 5)));
+        life.set_cell(42, -7, false);
+        assert_eq!(life.population(), 1);
+        assert_eq!(life.live_cells(), vec![(-1_000_000, 5)]);
+    }
+
+    #[test]
+    fn same_generations_as_step_life() {
+        // R-pentomino on a board large enough to never touch the edges in 128 generations
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../rle/rpento.rle");
+        let (cells, pw, ph, rule) = read_rle(&path).unwrap();
+        let (bw, bh) = (400u32, 400u32);
+        let mut current = vec![false; (bw * bh) as usize];
+        let mut next = current.clone();
+        place_pattern_centered(&mut current, bw, bh, &cells, pw, ph);
+
+        let mut single = HashLife::new(rule.clone());
+        single.load_cells(&current, bw, bh, 0, 0);
+        let mut jumping = HashLife::new(rule.clone());
+        jumping.load_cells(&current, bw, bh, 0, 0);
+
+        let mut window = vec![false; current.len()];
+        for generation in 1..=128u64 {
+            step_life(&current, &mut next, bw, bh, &rule);
+            std::mem::swap(&mut current, &mut next);
+            single.step_pow2(&rule, 0);
+            single.write_region(&mut window, 0, 0, bw, bh);
+            assert!(window == current, "differs at generation {generation}");
+
+            if generation % 32 == 0 {
+                jumping.step_pow2(&rule, 5);
+                jumping.write_region(&mut window, 0, 0, bw, bh);
+                assert!(window == current, "2^5 jump differs at generation {generation}");
+            }
+        }
+    }
+}
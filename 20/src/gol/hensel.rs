@@ -0,0 +1,231 @@
+// gol/hensel.rs
+
+use crate::Result;
+use std::fmt;
+
+// Isotropic non-totalistic rules in Hensel notation (e.g. "B2-a/S12" or "B3-cnqy/S23-a")
+// Each neighbour count of a rule half may be followed by letters naming which configurations of that
+// many live neighbours are meant ("2a": a corner and an edge touching it), or by '-' and the letters
+// to leave out ("3-cnqy": every 3 neighbour configuration except c, n, q and y).
+// A bare count keeps every configuration, so B/S rules are the totalistic special case.
+//
+// Neighbourhood masks have one bit per neighbour in raster order, the cell itself is left out:
+//   0 1 2
+//   3 . 4
+//   5 6 7
+
+// Offsets of the neighbours, bit i of a mask is the neighbour at NEIGHBOURS[i]
+const NEIGHBOURS: [(isize, isize); 8] = [(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)];
+
+// Letters of each neighbour count up to 4, 5..8 neighbours reuse the letters of 8 - n
+const LETTERS: [&str; 5] = ["", "ce", "ceaikn", "ceaiknjqry", "ceaiknjqrtwyz"];
+
+// One configuration per letter, in the order of LETTERS (Golly's definitions)
+// The configuration of "5c" is the complement of "3c" and so on
+const CONFIGURATIONS: [&[u8]; 5] = [
+    &[],
+    &[1, 2],
+    &[5, 10, 3, 24, 17, 36],
+    &[37, 26, 11, 7, 50, 13, 14, 38, 25, 49],
+    &[165, 90, 15, 29, 51, 39, 58, 54, 27, 53, 57, 46, 60],
+];
+
+// Mask of the live neighbours of (x, y), `get` returns 1 for a live cell
+#[inline]
+pub fn neighbourhood_mask(get: impl Fn(isize, isize) -> u8, x: isize, y: isize) -> u8 {
+    // Spelled out (same order as NEIGHBOURS), the steppers call this for every cell
+    get(x - 1, y - 1) | get(x, y - 1) << 1 | get(x + 1, y - 1) << 2 | get(x - 1, y) << 3 | get(x + 1, y) << 4 | get(x - 1, y + 1) << 5 | get(x, y + 1) << 6 | get(x + 1, y + 1) << 7
+}
+
+// Letters valid after a count of `n` live neighbours
+fn letters(n: u8) -> &'static str {
+    LETTERS[n.min(8 - n) as usize]
+}
+
+// Move every neighbour of `mask` by one of the 8 symmetries of the square (4 rotations, optionally mirrored)
+fn transform(mask: u8, symmetry: usize) -> u8 {
+    let mut out = 0u8;
+    for (bit, &(dx, dy)) in NEIGHBOURS.iter().enumerate() {
+        if mask & (1 << bit) == 0 {
+            continue;
+        }
+        let (mut x, mut y) = if symmetry >= 4 { (-dx, dy) } else { (dx, dy) };
+        for _ in 0..symmetry % 4 {
+            (x, y) = (-y, x);
+        }
+        let target = NEIGHBOURS.iter().position(|&offset| offset == (x, y)).unwrap_or_default();
+        out |= 1 << target;
+    }
+    out
+}
+
+// Smallest mask among the rotations and reflections of `mask`, equal for configurations of the same letter
+fn canonical(mask: u8) -> u8 {
+    (0..8).map(|symmetry| transform(mask, symmetry)).min().unwrap_or(mask)
+}
+
+// Hensel letter of a neighbourhood, None for 0 and 8 live neighbours (a single configuration each)
+pub fn letter(mask: u8) -> Option<char> {
+    let n = mask.count_ones() as u8;
+    // 5..8 neighbours: the letter of the dead neighbours' configuration
+    let reference = if n > 4 { !mask } else { mask };
+    let configurations = CONFIGURATIONS[n.min(8 - n) as usize];
+    let position = configurations.iter().position(|&c| canonical(c) == canonical(reference))?;
+    letters(n).chars().nth(position)
+}
+
+// Set of neighbourhood masks (256 bits), one per rule half
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NeighbourhoodSet([u64; 4]);
+
+impl NeighbourhoodSet {
+    // Every configuration of the given neighbour counts (B/S rules)
+    pub fn from_counts(counts: &[bool; 9]) -> Self {
+        let mut set = Self::default();
+        for mask in 0..=255u8 {
+            if counts[mask.count_ones() as usize] {
+                set.insert(mask);
+            }
+        }
+        set
+    }
+
+    // Parse a rule half without its B/S prefix: "2-a", "3-cnqy4w" or plain digits like "23"
+    // `rule` is the whole rule string, for error messages
+    pub fn parse(half: &str, rule: &str) -> Result<Self> {
+        let mut set = Self::default();
+        let mut chars = half.chars().peekable();
+        while let Some(c) = chars.next() {
+            let n = match c.to_digit(10) {
+                Some(n) if n <= 8 => n as u8,
+                _ => return Err(format!("Invalid rule '{}': unexpected '{}' (neighbour counts are 0-8).", rule, c).into()),
+            };
+            let exclude = chars.next_if_eq(&'-').is_some();
+            let mut named = String::new();
+            while let Some(l) = chars.next_if(|c| c.is_ascii_alphabetic()) {
+                named.push(l.to_ascii_lowercase());
+            }
+            if exclude && named.is_empty() {
+                return Err(format!("Invalid rule '{}': '{}-' must be followed by the letters to exclude.", rule, n).into());
+            }
+            if let Some(bad) = named.chars().find(|&l| !letters(n).contains(l)) {
+                let valid = if letters(n).is_empty() { "none".to_string() } else { letters(n).to_string() };
+                return Err(format!("Invalid rule '{}': '{}{}' is not a Hensel configuration (letters for {} neighbours: {}).", rule, n, bad, n, valid).into());
+            }
+
+            for mask in (0..=255u8).filter(|m| m.count_ones() as u8 == n) {
+                let listed = letter(mask).is_some_and(|l| named.contains(l));
+                if named.is_empty() || listed != exclude {
+                    set.insert(mask);
+                }
+            }
+        }
+        Ok(set)
+    }
+
+    #[inline]
+    pub fn contains(&self, mask: u8) -> bool {
+        self.0[(mask >> 6) as usize] & (1 << (mask & 63)) != 0
+    }
+
+    fn insert(&mut self, mask: u8) {
+        self.0[(mask >> 6) as usize] |= 1 << (mask & 63);
+    }
+
+    // counts[n]: at least one configuration of n live neighbours is in the set
+    pub fn counts(&self) -> [bool; 9] {
+        let mut counts = [false; 9];
+        for mask in (0..=255u8).filter(|&m| self.contains(m)) {
+            counts[mask.count_ones() as usize] = true;
+        }
+        counts
+    }
+
+    // Does every neighbour count have all or none of its configurations?
+    pub fn is_totalistic(&self) -> bool {
+        *self == Self::from_counts(&self.counts())
+    }
+}
+
+// Shortest Hensel form: "2-a3" rather than "2ceikn3"
+impl fmt::Display for NeighbourhoodSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for n in 0..=8u8 {
+            let masks: Vec<u8> = (0..=255u8).filter(|m| m.count_ones() as u8 == n).collect();
+            if !masks.iter().any(|&m| self.contains(m)) {
+                continue;
+            }
+            write!(f, "{}", n)?;
+            let present: String = letters(n).chars().filter(|&l| masks.iter().any(|&m| self.contains(m) && letter(m) == Some(l))).collect();
+            if present.len() == letters(n).len() {
+                continue;
+            }
+            if present.len() * 2 > letters(n).len() {
+                let missing: String = letters(n).chars().filter(|&l| !present.contains(l)).collect();
+                write!(f, "-{}", missing)?;
+            } else {
+                write!(f, "{}", present)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_configuration_has_one_letter() {
+        // Configurations per neighbour count, up to rotations and reflections
+        let expected = [1, 2, 6, 10, 13, 10, 6, 2, 1];
+        for n in 0..=8u8 {
+            let classes: std::collections::HashSet<u8> = (0..=255u8).filter(|m| m.count_ones() as u8 == n).map(canonical).collect();
+            assert_eq!(classes.len(), expected[n as usize], "{n} neighbours");
+            for mask in (0..=255u8).filter(|m| m.count_ones() as u8 == n) {
+                assert_eq!(letter(mask).is_none(), n == 0 || n == 8, "mask {mask:#010b}");
+            }
+        }
+        // Each letter names a different configuration
+        for (n, configurations) in CONFIGURATIONS.iter().enumerate() {
+            let classes: std::collections::HashSet<u8> = configurations.iter().map(|&c| canonical(c)).collect();
+            assert_eq!(classes.len(), LETTERS[n].len());
+            assert!(configurations.iter().all(|c| c.count_ones() as usize == n));
+        }
+    }
+
+    #[test]
+    fn letters_follow_golly() {
+        let mask = |cells: &[usize]| cells.iter().fold(0u8, |m, &bit| m | 1 << bit);
+        assert_eq!(letter(mask(&[0])), Some('c')); // corner
+        assert_eq!(letter(mask(&[6])), Some('e')); // edge
+        assert_eq!(letter(mask(&[0, 1])), Some('a')); // corner + adjacent edge
+        assert_eq!(letter(mask(&[1, 6])), Some('i')); // opposite edges
+        assert_eq!(letter(mask(&[2, 5])), Some('n')); // opposite corners
+        assert_eq!(letter(mask(&[0, 4])), Some('k')); // knight's move apart
+        assert_eq!(letter(mask(&[5, 6, 7])), Some('i')); // a full side
+        assert_eq!(letter(!mask(&[5, 6, 7])), Some('i')); // 5i is the complement of 3i
+    }
+
+    #[test]
+    fn parse_and_display() {
+        let rule = "B2-a/S12";
+        let set = NeighbourhoodSet::parse("2-a", rule).unwrap();
+        assert!(!set.is_totalistic());
+        assert_eq!(set.counts(), [false, false, true, false, false, false, false, false, false]);
+        assert!(set.contains(0b0100_0010) && !set.contains(0b0000_0011));
+        assert_eq!(set.to_string(), "2-a");
+        assert_eq!(NeighbourhoodSet::parse("2cekin", rule).unwrap(), set);
+        assert_eq!(NeighbourhoodSet::parse("3-CNQY", rule).unwrap().to_string(), "3-cnqy");
+        assert_eq!(NeighbourhoodSet::parse("4wz", rule).unwrap().to_string(), "4wz");
+
+        let totalistic = NeighbourhoodSet::parse("23", rule).unwrap();
+        assert!(totalistic.is_totalistic());
+        assert_eq!(NeighbourhoodSet::parse("2ceaikn3", rule).unwrap(), totalistic);
+        assert_eq!(totalistic.to_string(), "23");
+
+        for bad in ["1a", "0c", "8e", "2-", "3x", "9"] {
+            assert!(NeighbourhoodSet::parse(bad, rule).is_err(), "{bad:?}");
+        }
+    }
+}
@@ -0,0 +1,356 @@
+// gol/history.rs
+
+use std::collections::VecDeque;
+
+// Bounded record of the previous generations, so the app can step back and scrub a timeline
+// - A frame is either a keyframe (every cell that is not dead) or a diff with the frame before it
+//   (the cells whose state changed, with their new state, 0 when they died)
+// - Cells are sorted by (y, x) and delta-encoded as zigzag varints: a glider costs a few bytes per generation
+// - When the frames no longer fit in the memory budget, the oldest keyframe and its diffs are dropped
+// - Generations do not have to be consecutive (HashLife jumps 2^k generations per step)
+
+// x, y, state (1 alive, 2.. dying states of Generations rules or rule table states)
+pub type Cell = (i64, i64, u8);
+
+struct Frame {
+    generation: u64,
+    keyframe: bool,
+    data: Vec<u8>,
+}
+
+pub struct History {
+    frames: VecDeque<Frame>,
+    last: Vec<Cell>,        // cells of the newest frame, the next diff is computed against them
+    keyframe_interval: u32, // a keyframe every `keyframe_interval` frames, the rest are diffs
+    since_keyframe: u32,
+    budget: usize, // bytes, 0 disables the history
+    bytes: usize,  // encoded frames
+}
+
+impl History {
+    pub fn new(budget: usize, keyframe_interval: u32) -> Self {
+        Self {
+            frames: VecDeque::new(),
+            last: Vec::new(),
+            keyframe_interval: keyframe_interval.max(1),
+            since_keyframe: 0,
+            budget,
+            bytes: 0,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.frames.clear();
+        self.last = Vec::new();
+        self.since_keyframe = 0;
+        self.bytes = 0;
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.budget > 0
+    }
+
+    pub fn budget(&self) -> usize {
+        self.budget
+    }
+
+    // Memory used: the encoded frames and the decoded newest frame
+    pub fn bytes(&self) -> usize {
+        self.bytes + self.last.len() * std::mem::size_of::<Cell>()
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    pub fn oldest(&self) -> Option<u64> {
+        self.frames.front().map(|frame| frame.generation)
+    }
+
+    pub fn newest(&self) -> Option<u64> {
+        self.frames.back().map(|frame| frame.generation)
+    }
+
+    // Newest recorded generation before `generation`
+    pub fn previous(&self, generation: u64) -> Option<u64> {
+        let i = self.frames.partition_point(|frame| frame.generation < generation);
+        i.checked_sub(1).map(|i| self.frames[i].generation)
+    }
+
+    // Recorded generation closest to `generation`
+    pub fn nearest(&self, generation: u64) -> Option<u64> {
+        let i = self.frames.partition_point(|frame| frame.generation < generation);
+        let after = self.frames.get(i).map(|frame| frame.generation);
+        let before = i.checked_sub(1).map(|i| self.frames[i].generation);
+        match (before, after) {
+            (Some(b), Some(a)) => Some(if generation - b <= a - generation { b } else { a }),
+            (b, a) => b.or(a),
+        }
+    }
+
+    // Record the cells (sorted by (y, x), see sort_cells()) of a generation newer than every recorded one
+    pub fn record(&mut self, generation: u64, cells: Vec<Cell>) {
+        if !self.is_enabled() {
+            return;
+        }
+        debug_assert!(self.newest().is_none_or(|newest| newest < generation));
+        debug_assert!(cells.windows(2).all(|pair| (pair[0].1, pair[0].0) < (pair[1].1, pair[1].0)));
+
+        let keyframe = self.frames.is_empty() || self.since_keyframe + 1 >= self.keyframe_interval;
+        let data = if keyframe { encode(&cells) } else { encode(&diff(&self.last, &cells)) };
+        self.since_keyframe = if keyframe { 0 } else { self.since_keyframe + 1 };
+        self.bytes += data.len();
+        self.frames.push_back(Frame { generation, keyframe, data });
+        self.last = cells;
+
+        // Drop whole keyframe blocks, the oldest frame has to stay a keyframe
+        while self.bytes() > self.budget && self.frames.iter().skip(1).any(|frame| frame.keyframe) {
+            while let Some(frame) = self.frames.pop_front() {
+                self.bytes -= frame.data.len();
+                if self.frames.front().is_some_and(|frame| frame.keyframe) {
+                    break;
+                }
+            }
+        }
+    }
+
+    // Cells of a recorded generation, None when it was not recorded (or dropped)
+    pub fn cells_at(&self, generation: u64) -> Option<Vec<Cell>> {
+        let i = self.frames.binary_search_by_key(&generation, |frame| frame.generation).ok()?;
+        let start = (0..=i).rev().find(|&k| self.frames[k].keyframe)?;
+        let mut cells = decode(&self.frames[start].data);
+        for frame in self.frames.range(start + 1..=i) {
+            cells = apply(&cells, &decode(&frame.data));
+        }
+        Some(cells)
+    }
+
+    // Forget the generations after `generation` (the simulation goes on from there with another future)
+    pub fn truncate(&mut self, generation: u64) {
+        let Some(cells) = self.cells_at(generation) else {
+            self.clear();
+            return;
+        };
+        while self.frames.back().is_some_and(|frame| frame.generation > generation) {
+            if let Some(frame) = self.frames.pop_back() {
+                self.bytes -= frame.data.len();
+            }
+        }
+        let last_keyframe = self.frames.iter().rposition(|frame| frame.keyframe).unwrap_or_default();
+        self.since_keyframe = (self.frames.len() - 1 - last_keyframe) as u32;
+        self.last = cells;
+    }
+}
+
+// Order expected by History::record()
+pub fn sort_cells(cells: &mut [Cell]) {
+    cells.sort_unstable_by_key(|&(x, y, _)| (y, x));
+}
+
+// Cells of `to` that differ from `from`, both sorted by (y, x)
+fn diff(from: &[Cell], to: &[Cell]) -> Vec<Cell> {
+    let key = |c: &Cell| (c.1, c.0);
+    let mut changes = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    loop {
+        match (from.get(i), to.get(j)) {
+            (Some(a), Some(b)) if key(a) == key(b) => {
+                if a.2 != b.2 {
+                    changes.push(*b);
+                }
+                i += 1;
+                j += 1;
+            }
+            (Some(a), Some(b)) if key(a) > key(b) => {
+                changes.push(*b);
+                j += 1;
+            }
+            (Some(a), _) => {
+                changes.push((a.0, a.1, 0));
+                i += 1;
+            }
+            (None, Some(b)) => {
+                changes.push(*b);
+                j += 1;
+            }
+            (None, None) => return changes,
+        }
+    }
+}
+
+// Inverse of diff(): the cells of `from` updated with `changes`
+fn apply(from: &[Cell], changes: &[Cell]) -> Vec<Cell> {
+    let key = |c: &Cell| (c.1, c.0);
+    let mut cells = Vec::with_capacity(from.len() + changes.len());
+    let (mut i, mut j) = (0, 0);
+    loop {
+        match (from.get(i), changes.get(j)) {
+            (Some(a), Some(c)) if key(a) < key(c) => {
+                cells.push(*a);
+                i += 1;
+            }
+            (Some(a), Some(c)) if key(a) == key(c) => {
+                if c.2 != 0 {
+                    cells.push(*c);
+                }
+                i += 1;
+                j += 1;
+            }
+            (_, Some(c)) => {
+                if c.2 != 0 {
+                    cells.push(*c);
+                }
+                j += 1;
+            }
+            (Some(a), None) => {
+                cells.push(*a);
+                i += 1;
+            }
+            (None, None) => return cells,
+        }
+    }
+}
+
+fn encode(cells: &[Cell]) -> Vec<u8> {
+    // Differences wrap around, far away cells (i64::MIN...) still come back
+    let mut data = Vec::with_capacity(cells.len() * 3);
+    let (mut x0, mut y0) = (0i64, 0i64);
+    for &(x, y, state) in cells {
+        push_varint(&mut data, zigzag(y.wrapping_sub(y0)));
+        push_varint(&mut data, zigzag(x.wrapping_sub(x0)));
+        data.push(state);
+        (x0, y0) = (x, y);
+    }
+    data
+}
+
+fn decode(data: &[u8]) -> Vec<Cell> {
+    let mut cells = Vec::new();
+    let (mut x, mut y) = (0i64, 0i64);
+    let mut bytes = data.iter().copied();
+    while let Some(dy) = read_varint(&mut bytes) {
+        let (Some(dx), Some(state)) = (read_varint(&mut bytes), bytes.next()) else {
+            break;
+        };
+        y = y.wrapping_add(unzigzag(dy));
+        x = x.wrapping_add(unzigzag(dx));
+        cells.push((x, y, state));
+    }
+    cells
+}
+
+fn zigzag(n: i64) -> u64 {
+    ((n << 1) ^ (n >> 63)) as u64
+}
+
+fn unzigzag(n: u64) -> i64 {
+    (n >> 1) as i64 ^ -((n & 1) as i64)
+}
+
+fn push_varint(data: &mut Vec<u8>, mut n: u64) {
+    while n >= 0x80 {
+        data.push((n as u8) | 0x80);
+        n >>= 7;
+    }
+    data.push(n as u8);
+}
+
+fn read_varint(bytes: &mut impl Iterator<Item = u8>) -> Option<u64> {
+    let mut n = 0u64;
+    for shift in (0..64).step_by(7) {
+        let byte = bytes.next()?;
+        n |= ((byte & 0x7F) as u64) << shift;
+        if byte < 0x80 {
+            return Some(n);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Cells of a made-up evolution: a row of cells that grows, moves and changes state
+    fn generation(g: i64) -> Vec<Cell> {
+        let mut cells: Vec<Cell> = (0..g % 17).map(|i| (i * 3 - g, g / 2 - 40, (1 + (i + g) % 3) as u8)).collect();
+        cells.push((-1_000_000, 5_000_000_000, 1));
+        sort_cells(&mut cells);
+        cells
+    }
+
+    #[test]
+    fn every_generation_comes_back() {
+        let mut history = History::new(1 << 20, 8);
+        for g in 0..100 {
+            history.record(g as u64 * 2, generation(g));
+        }
+        assert_eq!((history.oldest(), history.newest(), history.len()), (Some(0), Some(198), 100));
+        for g in 0..100 {
+            assert_eq!(history.cells_at(g as u64 * 2), Some(generation(g)), "generation {}", g * 2);
+        }
+        assert_eq!(history.cells_at(3), None);
+        assert_eq!(history.previous(10), Some(8));
+        assert_eq!(history.previous(0), None);
+        assert_eq!((history.nearest(11), history.nearest(500)), (Some(10), Some(198)));
+    }
+
+    #[test]
+    fn diffs_are_smaller_than_keyframes() {
+        let mut row: Vec<Cell> = (0..200).map(|x| (x * 2, 7, 1)).collect();
+        let mut history = History::new(1 << 20, 1000);
+        history.record(0, row.clone());
+        let keyframe = history.bytes;
+        assert!(keyframe >= 600);
+        // One cell starts dying: dy, dx and the state
+        row[100].2 = 2;
+        history.record(1, row.clone());
+        assert_eq!(history.bytes - keyframe, 4);
+        assert_eq!(history.cells_at(1), Some(row));
+
+        let far_away = vec![(-40_000_000_000, 3, 1), (i64::MAX, 3, 1), (i64::MIN, i64::MAX, 5)];
+        assert_eq!(decode(&encode(&far_away)), far_away);
+    }
+
+    #[test]
+    fn budget_drops_the_oldest_keyframes() {
+        let mut history = History::new(600, 4);
+        for g in 0..200 {
+            history.record(g as u64, generation(g));
+            assert!(history.bytes() <= 600 || history.frames.iter().filter(|frame| frame.keyframe).count() == 1);
+        }
+        assert_eq!(history.newest(), Some(199));
+        let oldest = history.oldest().unwrap();
+        assert!(oldest > 0 && history.frames[0].keyframe);
+        for g in oldest..200 {
+            assert_eq!(history.cells_at(g), Some(generation(g as i64)));
+        }
+
+        // A disabled history records nothing
+        let mut disabled = History::new(0, 4);
+        disabled.record(0, generation(3));
+        assert!(disabled.is_empty());
+    }
+
+    #[test]
+    fn truncate_starts_another_future() {
+        let mut history = History::new(1 << 20, 4);
+        for g in 0..30 {
+            history.record(g as u64, generation(g));
+        }
+        history.truncate(13);
+        assert_eq!(history.newest(), Some(13));
+        // The future is different this time
+        for g in 14..30 {
+            history.record(g as u64, generation(g + 100));
+        }
+        for g in 0..30 {
+            let expected = if g <= 13 { generation(g) } else { generation(g + 100) };
+            assert_eq!(history.cells_at(g as u64), Some(expected), "generation {g}");
+        }
+    }
+}
@@ -1,51 +1,288 @@
 // gol/life.rs
 
 // use crate::Result;
+use crate::gol::hensel::neighbourhood_mask;
+use crate::gol::rule::Rule;
+use crate::gol::workers::Workers;
+use std::ops::Range;
 
-// Compute one step: current -> next (row-major, no wrapping).
-// Cells outside the board are considered dead.
-pub fn step_life(board_current: &[bool], board_next: &mut [bool], buffer_width: u32, buffer_height: u32) {
+// Side of the square tiles tracked by ActiveTiles
+pub const TILE_SIZE: u32 = 32;
+
+// Share of active tiles above which step_life_active() scans the whole board, the bookkeeping no longer pays
+const FULL_SCAN_RATIO: f32 = 0.5;
+
+// Compute one step: current -> next (row-major).
+// `rule` gives birth/survival for each neighbour count (B3/S23 for Conway's Life), or for each
+// configuration of the neighbours with isotropic non-totalistic rules (B2-a/S12, see gol::hensel).
+// Hexagonal (B2/S34H) and von Neumann (B13/S012V) rules only count the neighbours of their stencil.
+// Cells outside the board are dead on a plane, otherwise the rule's topology says
+// which board cell lies across the edge (torus, Klein bottle, cross-surface, sphere).
+pub fn step_life(board_current: &[bool], board_next: &mut [bool], buffer_width: u32, buffer_height: u32, rule: &Rule) {
     // if board_current.len() != board_next.len() {
     //     return Err("Current and next board must have the same size.".into());
     // }
     debug_assert_eq!(board_current.len(), board_next.len());
 
+    step_life_rows(board_current, board_next, buffer_width, buffer_height, rule, 0, std::slice::from_ref(&(0..buffer_width)));
+    // Ok(())
+}
+
+// Same as step_life() but the board is split into one horizontal band per worker, computed in parallel
+// Every worker reads the whole current board and writes only its own band of the next one
+pub fn step_life_parallel(board_current: &[bool], board_next: &mut [bool], buffer_width: u32, buffer_height: u32, rule: &Rule, workers: &Workers) {
+    debug_assert_eq!(board_current.len(), board_next.len());
+
+    let threads = workers.threads();
+    if threads <= 1 || buffer_width == 0 || buffer_height < 2 {
+        step_life(board_current, board_next, buffer_width, buffer_height, rule);
+        return;
+    }
+
+    let rows_per_band = (buffer_height as usize).div_ceil(threads);
+    workers.scope(|scope| {
+        for (i, band) in board_next.chunks_mut(rows_per_band * buffer_width as usize).enumerate() {
+            let first_row = (i * rows_per_band) as u32;
+            scope.spawn(move |_| step_life_rows(board_current, band, buffer_width, buffer_height, rule, first_row, std::slice::from_ref(&(0..buffer_width))));
+        }
+    });
+}
+
+// Dirty tiles of a dense board: only the tiles whose cells changed during the last generation and their
+// 8 neighbours can change during the next one, the rest of the board is dead or stable.
+// Cells edited outside of step_life_active() (pattern loaded, engine switched...) must call invalidate().
+pub struct ActiveTiles {
+    width: u32,
+    height: u32,
+    columns: u32,
+    rows: u32,
+    changed: Vec<bool>, // the tile changed during the last generation
+    active: Vec<bool>,  // the tile was computed by the last generation
+    full_scan: bool,    // the next generation has to compute every tile
+}
+
+impl ActiveTiles {
+    pub fn new(width: u32, height: u32) -> Self {
+        let count = (width.div_ceil(TILE_SIZE) * height.div_ceil(TILE_SIZE)) as usize;
+        Self {
+            width,
+            height,
+            columns: width.div_ceil(TILE_SIZE),
+            rows: height.div_ceil(TILE_SIZE),
+            changed: vec![true; count],
+            active: vec![true; count],
+            full_scan: true,
+        }
+    }
+
+    pub fn invalidate(&mut self) {
+        self.full_scan = true;
+    }
+
+    pub fn tile_count(&self) -> usize {
+        self.active.len()
+    }
+
+    // Tiles computed by the last generation
+    pub fn active_count(&self) -> usize {
+        self.active.iter().filter(|&&active| active).count()
+    }
+
+    // Was the tile holding cell (x, y) computed by the last generation?
+    pub fn is_active(&self, x: u32, y: u32) -> bool {
+        self.active[((y / TILE_SIZE) * self.columns + x / TILE_SIZE) as usize]
+    }
+
+    // Cells (x range, y range) of the tiles that changed during the last generation, the other cells kept their state
+    pub fn changed_tiles(&self) -> impl Iterator<Item = (Range<u32>, Range<u32>)> + '_ {
+        (0..self.changed.len()).filter(|&tile| self.changed[tile]).map(|tile| {
+            let (tx, ty) = (tile as u32 % self.columns, tile as u32 / self.columns);
+            (tx * TILE_SIZE..((tx + 1) * TILE_SIZE).min(self.width), ty * TILE_SIZE..((ty + 1) * TILE_SIZE).min(self.height))
+        })
+    }
+
+    // Activate the changed tiles and their neighbours, return how many tiles are active
+    fn spread(&mut self) -> usize {
+        self.active.fill(false);
+        let (columns, rows) = (self.columns as usize, self.rows as usize);
+        for ty in 0..rows {
+            for tx in (0..columns).filter(|&tx| self.changed[ty * columns + tx]) {
+                for y in ty.saturating_sub(1)..(ty + 2).min(rows) {
+                    self.active[y * columns + tx.saturating_sub(1)..y * columns + (tx + 2).min(columns)].fill(true);
+                }
+            }
+        }
+        self.active_count()
+    }
+
+    // Columns of the active tiles of tile row `ty`
+    fn active_columns(&self, ty: u32, buffer_width: u32) -> Vec<Range<u32>> {
+        (0..self.columns)
+            .filter(|&tx| self.active[(ty * self.columns + tx) as usize])
+            .map(|tx| tx * TILE_SIZE..((tx + 1) * TILE_SIZE).min(buffer_width))
+            .collect()
+    }
+}
+
+// Same as step_life_parallel() but only the active tiles are computed (see ActiveTiles)
+// `board_next` must hold the generation before `board_current`, as left by swapping the boards after each step:
+// the tiles that are not computed already hold their next generation there.
+// Falls back to a full scan after invalidate(), when many tiles are active or when the topology joins the edges
+// (the tiles across a seam would have to be activated too).
+#[allow(clippy::too_many_arguments)]
+pub fn step_life_active(board_current: &[bool], board_next: &mut [bool], buffer_width: u32, buffer_height: u32, rule: &Rule, workers: &Workers, tiles: &mut ActiveTiles) {
+    debug_assert_eq!(board_current.len(), board_next.len());
+    debug_assert_eq!(tiles.tile_count(), (buffer_width.div_ceil(TILE_SIZE) * buffer_height.div_ceil(TILE_SIZE)) as usize);
+
+    let active = tiles.spread();
+    if tiles.full_scan || rule.topology().wraps() || active as f32 > FULL_SCAN_RATIO * tiles.tile_count() as f32 {
+        step_life_parallel(board_current, board_next, buffer_width, buffer_height, rule, workers);
+        tiles.active.fill(true);
+        tiles.full_scan = false;
+    } else if active > 0 {
+        // Bands of whole tile rows, one per thread
+        let threads = workers.threads();
+        let tile_rows_per_band = (tiles.rows as usize).div_ceil(threads);
+        let tiles_ref = &*tiles;
+        let step_band = move |band: &mut [bool], first_tile_row: u32| {
+            let first_row = first_tile_row * TILE_SIZE;
+            for ty in first_tile_row..(first_tile_row + tile_rows_per_band as u32).min(tiles_ref.rows) {
+                let columns = tiles_ref.active_columns(ty, buffer_width);
+                if columns.is_empty() {
+                    continue;
+                }
+                let (top, bottom) = (ty * TILE_SIZE, ((ty + 1) * TILE_SIZE).min(buffer_height));
+                let rows = &mut band[((top - first_row) * buffer_width) as usize..((bottom - first_row) * buffer_width) as usize];
+                step_life_rows(board_current, rows, buffer_width, buffer_height, rule, top, &columns);
+            }
+        };
+        if threads <= 1 {
+            step_band(board_next, 0);
+        } else {
+            workers.scope(|scope| {
+                for (i, band) in board_next.chunks_mut(tile_rows_per_band * (TILE_SIZE * buffer_width) as usize).enumerate() {
+                    scope.spawn(move |_| step_band(band, (i * tile_rows_per_band) as u32));
+                }
+            });
+        }
+    }
+
+    // Which of the computed tiles changed
+    for ty in 0..tiles.rows {
+        for tx in 0..tiles.columns {
+            let tile = (ty * tiles.columns + tx) as usize;
+            if !tiles.active[tile] {
+                tiles.changed[tile] = false;
+                continue;
+            }
+            let (left, right) = ((tx * TILE_SIZE) as usize, ((tx + 1) * TILE_SIZE).min(buffer_width) as usize);
+            tiles.changed[tile] = (ty * TILE_SIZE..((ty + 1) * TILE_SIZE).min(buffer_height)).any(|y| {
+                let row = (y * buffer_width) as usize;
+                board_current[row + left..row + right] != board_next[row + left..row + right]
+            });
+        }
+    }
+}
+
+// Compute the rows [first_row, first_row + band rows) of the next generation into `band`, only in the `columns` ranges
+fn step_life_rows(board_current: &[bool], band: &mut [bool], buffer_width: u32, buffer_height: u32, rule: &Rule, first_row: u32, columns: &[Range<u32>]) {
+    let topology = rule.topology();
+    // B/S rules on the 8 surrounding cells only need the neighbour count, the table lookup is for
+    // isotropic non-totalistic rules and the hexagonal / von Neumann neighbourhoods
+    let table = (!rule.is_moore_totalistic()).then(|| rule.neighbourhood_table());
     let get = |x: isize, y: isize| -> u8 {
-        if x < 0 || y < 0 {
-            return 0;
+        if x >= 0 && y >= 0 && (x as usize) < buffer_width as usize && (y as usize) < buffer_height as usize {
+            return board_current[y as usize * buffer_width as usize + x as usize] as u8;
         }
-        let (x, y) = (x as usize, y as usize);
-        if x >= buffer_width as usize || y >= buffer_height as usize {
-            return 0;
+        // Only border cells get here
+        match topology.map(x as i64, y as i64, buffer_width, buffer_height) {
+            Some((x, y)) => board_current[(y * buffer_width + x) as usize] as u8,
+            None => 0,
         }
-        board_current[y * buffer_width as usize + x] as u8
     };
 
-    for y in 0..buffer_height {
-        for x in 0..buffer_width {
-            let xi = x as isize;
-            let yi = y as isize;
-
-            let mut n = 0u8;
-            n += get(xi - 1, yi - 1);
-            n += get(xi, yi - 1);
-            n += get(xi + 1, yi - 1);
-            n += get(xi - 1, yi);
-            n += get(xi + 1, yi);
-            n += get(xi - 1, yi + 1);
-            n += get(xi, yi + 1);
-            n += get(xi + 1, yi + 1);
+    let band_height = (band.len() / buffer_width.max(1) as usize) as u32;
+    for y in first_row..first_row + band_height {
+        for range in columns {
+            for x in range.clone() {
+                let xi = x as isize;
+                let yi = y as isize;
+                let idx: usize = (y * buffer_width + x) as usize;
+                let alive = board_current[idx];
 
+                band[idx - (first_row * buffer_width) as usize] = match &table {
+                    Some(table) => table[alive as usize][neighbourhood_mask(get, xi, yi) as usize],
+                    None => {
+                        let mut n = 0u8;
+                        n += get(xi - 1, yi - 1);
+                        n += get(xi, yi - 1);
+                        n += get(xi + 1, yi - 1);
+                        n += get(xi - 1, yi);
+                        n += get(xi + 1, yi);
+                        n += get(xi - 1, yi + 1);
+                        n += get(xi, yi + 1);
+                        n += get(xi + 1, yi + 1);
+                        rule.next_state(alive, n)
+                    }
+                };
+            }
+        }
+    }
+}
+
+// Generations rules: compute one step on cell states (0 dead, 1 alive, 2.. dying) with `rule.states()` states
+// Only cells in state 1 count as neighbours, the topology is handled like in step_life()
+pub fn step_generations(states_current: &[u8], states_next: &mut [u8], buffer_width: u32, buffer_height: u32, rule: &Rule) {
+    debug_assert_eq!(states_current.len(), states_next.len());
+
+    step_generations_rows(states_current, states_next, buffer_width, buffer_height, rule, 0);
+}
+
+// Same as step_generations() but the board is split into one horizontal band per worker, computed in parallel
+pub fn step_generations_parallel(states_current: &[u8], states_next: &mut [u8], buffer_width: u32, buffer_height: u32, rule: &Rule, workers: &Workers) {
+    debug_assert_eq!(states_current.len(), states_next.len());
+
+    let threads = workers.threads();
+    if threads <= 1 || buffer_width == 0 || buffer_height < 2 {
+        step_generations(states_current, states_next, buffer_width, buffer_height, rule);
+        return;
+    }
+
+    let rows_per_band = (buffer_height as usize).div_ceil(threads);
+    workers.scope(|scope| {
+        for (i, band) in states_next.chunks_mut(rows_per_band * buffer_width as usize).enumerate() {
+            let first_row = (i * rows_per_band) as u32;
+            scope.spawn(move |_| step_generations_rows(states_current, band, buffer_width, buffer_height, rule, first_row));
+        }
+    });
+}
+
+// Compute the rows [first_row, first_row + band rows) of the next generation into `band`
+fn step_generations_rows(states_current: &[u8], band: &mut [u8], buffer_width: u32, buffer_height: u32, rule: &Rule, first_row: u32) {
+    let topology = rule.topology();
+    let table = rule.neighbourhood_table();
+    let get = |x: isize, y: isize| -> u8 {
+        if x >= 0 && y >= 0 && (x as usize) < buffer_width as usize && (y as usize) < buffer_height as usize {
+            return (states_current[y as usize * buffer_width as usize + x as usize] == 1) as u8;
+        }
+        match topology.map(x as i64, y as i64, buffer_width, buffer_height) {
+            Some((x, y)) => (states_current[(y * buffer_width + x) as usize] == 1) as u8,
+            None => 0,
+        }
+    };
+
+    let band_height = (band.len() / buffer_width.max(1) as usize) as u32;
+    for y in first_row..first_row + band_height {
+        for x in 0..buffer_width {
             let idx: usize = (y * buffer_width + x) as usize;
-            let alive = board_current[idx];
+            let state = states_current[idx];
+
+            // Dying cells age whatever their neighbourhood, skip the lookup
+            let alive_next = state < 2 && table[state as usize][neighbourhood_mask(get, x as isize, y as isize) as usize];
 
-            board_next[idx] = match (alive, n) {
-                (true, 2) | (_, 3) => true, // survive with 2; birth/survive with 3
-                _ => false,
-            };
+            band[idx - (first_row * buffer_width) as usize] = rule.generations_transition(state, alive_next);
         }
     }
-    // Ok(())
 }
 
 // pub fn step_life(board_current: &[bool], board_next: &mut [bool], buffer_width: u32, buffer_height: u32) -> Result<()> {
@@ -91,3 +328,148 @@ pub fn step_life(board_current: &[bool], board_next: &mut [bool], buffer_width:
 //     }
 //     Ok(())
 // }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generations_cells_die_through_refractory_states() {
+        // Brian's Brain: two live cells side by side
+        let (w, h) = (6u32, 5u32);
+        let rule = Rule::parse("B2/S/C3").unwrap();
+        let mut current = vec![0u8; (w * h) as usize];
+        current[(2 * w + 2) as usize] = 1;
+        current[(2 * w + 3) as usize] = 1;
+        let mut next = vec![0u8; current.len()];
+
+        step_generations(&current, &mut next, w, h, &rule);
+        let at = |cells: &[u8], x: u32, y: u32| cells[(y * w + x) as usize];
+        // The pair starts dying, the 4 cells above and below it (2 live neighbours each) are born
+        assert_eq!((at(&next, 2, 2), at(&next, 3, 2)), (2, 2));
+        for (x, y) in [(2, 1), (3, 1), (2, 3), (3, 3)] {
+            assert_eq!(at(&next, x, y), 1, "birth at ({x}, {y})");
+        }
+        assert_eq!(next.iter().filter(|&&s| s != 0).count(), 6);
+
+        // Dying cells are dead one generation later, and bands give the same result
+        let mut serial = vec![0u8; current.len()];
+        step_generations(&next, &mut serial, w, h, &rule);
+        assert_eq!((at(&serial, 2, 2), at(&serial, 3, 2)), (0, 0));
+        let mut parallel = vec![0u8; current.len()];
+        step_generations_parallel(&next, &mut parallel, w, h, &rule, &Workers::new(3).unwrap());
+        assert_eq!(parallel, serial);
+    }
+
+    #[test]
+    fn two_state_generations_match_step_life() {
+        let (w, h) = (40u32, 30u32);
+        let rule = Rule::life();
+        let mut cells = vec![false; (w * h) as usize];
+        for (i, cell) in cells.iter_mut().enumerate() {
+            *cell = (i * 7919 + i / 11) % 3 == 0;
+        }
+        let mut states: Vec<u8> = cells.iter().map(|&c| c as u8).collect();
+        let (mut next_cells, mut next_states) = (vec![false; cells.len()], vec![0u8; cells.len()]);
+        for _ in 0..20 {
+            step_life(&cells, &mut next_cells, w, h, &rule);
+            std::mem::swap(&mut cells, &mut next_cells);
+            step_generations(&states, &mut next_states, w, h, &rule);
+            std::mem::swap(&mut states, &mut next_states);
+        }
+        assert!(states.iter().zip(&cells).all(|(&s, &c)| (s == 1) == c));
+    }
+
+    #[test]
+    fn active_tiles_match_a_full_scan() {
+        let (w, h) = (200u32, 150u32);
+        let rule = Rule::life();
+        // A small soup in one corner, a glider flying through the tiles on the other side
+        let mut cells = vec![false; (w * h) as usize];
+        for y in 10..30 {
+            for x in 10..30 {
+                cells[(y * w + x) as usize] = (x * 7919 + y * 104729) % 3 == 0;
+            }
+        }
+        for (x, y) in [(121, 60), (122, 61), (120, 62), (121, 62), (122, 62)] {
+            cells[(y * w + x) as usize] = true;
+        }
+
+        let (mut full, mut full_next) = (cells.clone(), vec![false; cells.len()]);
+        let (mut active, mut active_next) = (cells, vec![false; full.len()]);
+        let mut tiles = ActiveTiles::new(w, h);
+        let workers = [Workers::single(), Workers::new(2).unwrap(), Workers::new(3).unwrap()];
+        for generation in 0..120 {
+            step_life(&full, &mut full_next, w, h, &rule);
+            std::mem::swap(&mut full, &mut full_next);
+            step_life_active(&active, &mut active_next, w, h, &rule, &workers[generation % 3], &mut tiles);
+            std::mem::swap(&mut active, &mut active_next);
+            assert_eq!(active, full, "generation {generation}");
+        }
+        assert!(tiles.active_count() < tiles.tile_count() / 2);
+
+        // A still life: everything is computed once, then nothing
+        let mut board = vec![false; full.len()];
+        for (x, y) in [(50, 50), (51, 50), (50, 51), (51, 51)] {
+            board[(y * w + x) as usize] = true;
+        }
+        let block = board.clone();
+        let mut next = vec![false; board.len()];
+        tiles.invalidate();
+        for _ in 0..3 {
+            step_life_active(&board, &mut next, w, h, &rule, &Workers::single(), &mut tiles);
+            std::mem::swap(&mut board, &mut next);
+        }
+        assert_eq!(board, block);
+        assert_eq!(tiles.active_count(), 0);
+    }
+
+    #[test]
+    fn isotropic_rule_looks_at_the_configuration() {
+        let (w, h) = (7u32, 7u32);
+        let births = |cells: &[(u32, u32)], rule: &str| -> Vec<(u32, u32)> {
+            let mut current = vec![false; (w * h) as usize];
+            for &(x, y) in cells {
+                current[(y * w + x) as usize] = true;
+            }
+            let mut next = vec![false; current.len()];
+            step_life(&current, &mut next, w, h, &Rule::parse(rule).unwrap());
+            (0..w * h).filter(|&i| next[i as usize]).map(|i| (i % w, i / w)).collect()
+        };
+
+        // Two cells side by side: the 4 cells above and below see a corner and its adjacent edge (2a)
+        let pair = [(2, 3), (3, 3)];
+        assert_eq!(births(&pair, "B2/S"), vec![(2, 2), (3, 2), (2, 4), (3, 4)]);
+        assert_eq!(births(&pair, "B2a/S"), births(&pair, "B2/S"));
+        assert!(births(&pair, "B2-a/S").is_empty());
+
+        // Two cells one apart: opposite edges (2i) in the middle, same side corners (2c) above and below
+        let apart = [(2, 3), (4, 3)];
+        assert_eq!(births(&apart, "B2-a/S"), vec![(3, 2), (3, 3), (3, 4)]);
+        assert_eq!(births(&apart, "B2i/S"), vec![(3, 3)]);
+    }
+
+    #[test]
+    fn hexagonal_and_von_neumann_stencils() {
+        let (w, h) = (5u32, 5u32);
+        let next_centre = |cells: &[(u32, u32)], rule: &str| -> bool {
+            let mut current = vec![false; (w * h) as usize];
+            for &(x, y) in cells {
+                current[(y * w + x) as usize] = true;
+            }
+            let mut next = vec![false; current.len()];
+            step_life(&current, &mut next, w, h, &Rule::parse(rule).unwrap());
+            next[(2 * w + 2) as usize]
+        };
+
+        // NE and SW are not neighbours on the hexagonal grid, the 4 diagonals not in von Neumann's
+        let anti_diagonal = [(3, 1), (1, 3)];
+        let vertical = [(2, 1), (2, 3)];
+        let main_diagonal = [(1, 1), (3, 3)];
+        assert!(next_centre(&anti_diagonal, "B2/S"));
+        assert!(!next_centre(&anti_diagonal, "B2/SH"));
+        assert!(next_centre(&vertical, "B2/SH") && next_centre(&main_diagonal, "B2/SH"));
+        assert!(next_centre(&vertical, "B2/SV") && !next_centre(&main_diagonal, "B2/SV"));
+        assert!(!next_centre(&[(1, 1), (3, 3), (2, 1)], "B2/SV"));
+    }
+}
@@ -0,0 +1,229 @@
+// gol/lifespan.rs
+
+use crate::Result;
+use crate::gol::{
+    census,
+    engine::LifeEngine,
+    period::{PeriodDetector, Periodicity},
+    rule::Rule,
+    sparse::SparseBoard,
+};
+use std::collections::BTreeMap;
+use std::collections::hash_map::DefaultHasher;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+// Lifespan of a methuselah: how long until it is periodic, and what it left.
+// Spaceships escaping the pattern would keep it from ever repeating, so an object small enough, classified as
+// a spaceship, and out of the bounding box of everything else on the side it flies to is removed and counted.
+// The generation found that way is when the remains repeated for the first time, maybe later than the real
+// stabilization (a spaceship still close to them when they settled): a second run from the start hashes only
+// the area of the remains and finds the first generation of the final cycle.
+
+// Objects up to this population are checked for escaping spaceships
+const ESCAPE_MAX_POPULATION: usize = 32;
+// Distance between an escaping spaceship and the rest of the pattern before it is removed
+const ESCAPE_MARGIN: i64 = 16;
+// Spaceships of a longer period are not removed
+const ESCAPE_MAX_PERIOD: u64 = 16;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lifespan {
+    pub periodicity: Option<Periodicity>, // None when the limit was reached first
+    pub stabilization: u64,               // First generation of the final cycle (the limit when not stable)
+    pub population: u64,                  // Population then, escaped spaceships included
+    pub max_population: u64,
+    pub max_population_generation: u64,
+    pub bounding_box: Option<(i64, i64, i64, i64)>, // What is left, escaped spaceships excluded
+    pub escaped: BTreeMap<String, u64>,             // Escaped spaceships by apgcode
+}
+
+impl Lifespan {
+    // Run the cells until they are periodic, at most `max_generations` generations
+    // Generations are counted from the cells given (generation 0)
+    pub fn analyse(cells: &[(i64, i64)], rule: &Rule, max_generations: u64) -> Result<Self> {
+        if !SparseBoard::supports(rule) {
+            return Err(format!("The lifespan analysis runs on the sparse engine, it cannot run {}", rule).into());
+        }
+        let mut board = SparseBoard::new();
+        cells.iter().for_each(|&(x, y)| LifeEngine::set_cell(&mut board, x, y, true));
+        let mut detector = PeriodDetector::new(max_generations as usize + 1);
+        let mut escaped: BTreeMap<String, u64> = BTreeMap::new();
+        let mut escaped_population = 0;
+        let (mut max_population, mut max_population_generation) = (0, 0);
+
+        let mut generation = 0;
+        let periodicity = loop {
+            escaped_population += remove_escaping(&mut board, rule, &mut escaped);
+            let population = LifeEngine::population(&board) + escaped_population;
+            if population > max_population {
+                (max_population, max_population_generation) = (population, generation);
+            }
+            if let Some(periodicity) = detector.observe(generation, board.tally().signature()) {
+                break Some(periodicity);
+            }
+            if generation == max_generations {
+                break None;
+            }
+            board.step(rule);
+            generation += 1;
+        };
+
+        let mut lifespan = Self {
+            periodicity,
+            stabilization: generation,
+            population: LifeEngine::population(&board) + escaped_population,
+            max_population,
+            max_population_generation,
+            bounding_box: LifeEngine::bounding_box(&board),
+            escaped,
+        };
+        if let Some(periodicity @ (Periodicity::StillLife | Periodicity::Oscillator { .. })) = periodicity {
+            lifespan.stabilization = first_cycle(cells, rule, &mut board, generation, periodicity.period());
+        } else if let Some(periodicity) = periodicity {
+            lifespan.stabilization = generation - periodicity.period();
+        }
+        Ok(lifespan)
+    }
+}
+
+impl fmt::Display for Lifespan {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.periodicity {
+            Some(periodicity) => writeln!(f, "stabilized at generation {}: {}", self.stabilization, periodicity)?,
+            None => writeln!(f, "not stable after {} generations", self.stabilization)?,
+        }
+        let escaped: u64 = self.escaped.values().sum();
+        write!(f, "final population {}", self.population)?;
+        if escaped > 0 {
+            let list: Vec<String> = self.escaped.iter().map(|(code, count)| format!("{} {}", count, census::name_of(code).unwrap_or(code))).collect();
+            write!(f, " ({} escaped: {})", escaped, list.join(", "))?;
+        }
+        writeln!(f)?;
+        writeln!(f, "maximum population {} at generation {}", self.max_population, self.max_population_generation)?;
+        match self.bounding_box {
+            Some((x0, y0, x1, y1)) => write!(f, "bounding box ({}, {}) - ({}, {}), {}x{}", x0, y0, x1, y1, x1 - x0 + 1, y1 - y0 + 1),
+            None => write!(f, "bounding box empty"),
+        }
+    }
+}
+
+// Remove the spaceships flying away from the rest of the pattern, return their population
+fn remove_escaping(board: &mut SparseBoard, rule: &Rule, escaped: &mut BTreeMap<String, u64>) -> u64 {
+    let Some((min_x, min_y, max_x, max_y)) = LifeEngine::bounding_box(board) else {
+        return 0;
+    };
+    let objects = census::split_objects(&LifeEngine::live_cells(board));
+    if objects.len() < 2 {
+        return 0;
+    }
+    let bounds: Vec<(i64, i64, i64, i64)> = objects.iter().map(|object| bounds_of(object)).collect();
+
+    let mut removed = 0;
+    for (i, object) in objects.iter().enumerate() {
+        // Only what lies on an edge of the pattern can be leaving it
+        let (x0, y0, x1, y1) = bounds[i];
+        if object.len() > ESCAPE_MAX_POPULATION || (x0 != min_x && y0 != min_y && x1 != max_x && y1 != max_y) {
+            continue;
+        }
+        let Some((rest_x0, rest_y0, rest_x1, rest_y1)) = bounds
+            .iter()
+            .enumerate()
+            .filter(|&(j, _)| j != i)
+            .map(|(_, &b)| b)
+            .reduce(|a, b| (a.0.min(b.0), a.1.min(b.1), a.2.max(b.2), a.3.max(b.3)))
+        else {
+            continue;
+        };
+        let (code, periodicity) = census::classify(object, rule, ESCAPE_MAX_PERIOD);
+        let Some(Periodicity::Spaceship { dx, dy, .. }) = periodicity else {
+            continue;
+        };
+        let leaving = (dx > 0 && x0 > rest_x1 + ESCAPE_MARGIN) || (dx < 0 && x1 < rest_x0 - ESCAPE_MARGIN) || (dy > 0 && y0 > rest_y1 + ESCAPE_MARGIN) || (dy < 0 && y1 < rest_y0 - ESCAPE_MARGIN);
+        if leaving {
+            object.iter().for_each(|&(x, y)| LifeEngine::set_cell(board, x, y, false));
+            *escaped.entry(code).or_default() += 1;
+            removed += object.len() as u64;
+        }
+    }
+    removed
+}
+
+fn bounds_of(cells: &[(i64, i64)]) -> (i64, i64, i64, i64) {
+    cells
+        .iter()
+        .fold((i64::MAX, i64::MAX, i64::MIN, i64::MIN), |(x0, y0, x1, y1), &(x, y)| (x0.min(x), y0.min(y), x1.max(x), y1.max(y)))
+}
+
+// First generation from which the area of the remains repeats every `period` generations
+// `board` holds the remains at `generation`, they do not move (still life or oscillator)
+fn first_cycle(cells: &[(i64, i64)], rule: &Rule, board: &mut SparseBoard, generation: u64, period: u64) -> u64 {
+    let Some(mut area) = LifeEngine::bounding_box(board) else {
+        return generation;
+    };
+    for _ in 0..period {
+        board.step(rule);
+        if let Some((x0, y0, x1, y1)) = LifeEngine::bounding_box(board) {
+            area = (area.0.min(x0), area.1.min(y0), area.2.max(x1), area.3.max(y1));
+        }
+    }
+
+    // Hash of the area, every generation from the start (nothing removed this time)
+    let mut board = SparseBoard::new();
+    cells.iter().for_each(|&(x, y)| LifeEngine::set_cell(&mut board, x, y, true));
+    let mut hashes = Vec::with_capacity(generation as usize + 1);
+    for _ in 0..=generation {
+        let mut inside: Vec<(i64, i64)> = LifeEngine::live_cells(&board)
+            .into_iter()
+            .filter(|&(x, y)| x >= area.0 - 1 && y >= area.1 - 1 && x <= area.2 + 1 && y <= area.3 + 1)
+            .collect();
+        inside.sort_unstable_by_key(|&(x, y)| (y, x));
+        let mut hasher = DefaultHasher::new();
+        inside.hash(&mut hasher);
+        hashes.push(hasher.finish());
+        board.step(rule);
+    }
+
+    let period = period as usize;
+    let mut first = generation as usize - period;
+    while first > 0 && hashes[first - 1] == hashes[first - 1 + period] {
+        first -= 1;
+    }
+    first as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn r_pentomino() {
+        let r_pentomino = [(1, 0), (2, 0), (0, 1), (1, 1), (1, 2)];
+        let lifespan = Lifespan::analyse(&r_pentomino, &Rule::life(), 2000).unwrap();
+        assert_eq!(lifespan.stabilization, 1103);
+        assert_eq!(lifespan.periodicity, Some(Periodicity::Oscillator { period: 2 }));
+        assert_eq!(lifespan.population, 116);
+        assert_eq!(lifespan.escaped.get("xq4_153"), Some(&6));
+        assert_eq!(lifespan.max_population, 319);
+        assert!(
+            lifespan
+                .to_string()
+                .starts_with("stabilized at generation 1103: oscillator period 2\nfinal population 116 (6 escaped: 6 glider)\n")
+        );
+    }
+
+    #[test]
+    fn lone_glider_and_limit() {
+        let glider = [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+        let lifespan = Lifespan::analyse(&glider, &Rule::life(), 100).unwrap();
+        assert_eq!((lifespan.periodicity, lifespan.stabilization), (Some(Periodicity::Spaceship { period: 4, dx: 1, dy: 1 }), 0));
+        assert!(lifespan.escaped.is_empty());
+
+        let r_pentomino = [(1, 0), (2, 0), (0, 1), (1, 1), (1, 2)];
+        let lifespan = Lifespan::analyse(&r_pentomino, &Rule::life(), 100).unwrap();
+        assert_eq!((lifespan.periodicity, lifespan.stabilization), (None, 100));
+
+        let lifespan = Lifespan::analyse(&[(0, 0)], &Rule::life(), 100).unwrap();
+        assert_eq!((lifespan.periodicity, lifespan.stabilization, lifespan.bounding_box), (Some(Periodicity::Extinct), 1, None));
+    }
+}
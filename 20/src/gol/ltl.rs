@@ -0,0 +1,375 @@
+// gol/ltl.rs
+
+use crate::Result;
+use crate::gol::rule::Rule;
+use crate::gol::workers::Workers;
+use std::fmt;
+
+// Larger than Life (Kellie Evans), Golly notation: "R5,C0,M1,S34..58,B34..45,NM"
+// - R : range, the neighbourhood spans R cells in every direction
+// - C : number of states, 0 or 2 for two states, more for Generations-like decay
+// - M : 1 when the cell itself is counted with its neighbours
+// - S / B : survival / birth when the live count is in min..max
+// - N : neighbourhood shape, M = Moore (square), N = von Neumann (diamond), C = circular
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Neighbourhood {
+    Moore,
+    VonNeumann,
+    Circular, // cells within a distance of R + 1/2 of the centre (dx^2 + dy^2 <= R^2 + R)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LargerThanLife {
+    pub range: u16,
+    pub middle: bool,
+    pub survival: (u32, u32),
+    pub birth: (u32, u32),
+    pub neighbourhood: Neighbourhood,
+}
+
+// Larger ranges are legal in Golly but would not run interactively on the board
+pub const MAX_RANGE: u16 = 50;
+
+impl LargerThanLife {
+    // Does the rule string use the LtL notation? ("R" followed by a digit)
+    pub fn is_ltl_notation(s: &str) -> bool {
+        let mut chars = s.trim_start().chars();
+        matches!(chars.next(), Some('R' | 'r')) && chars.next().is_some_and(|c| c.is_ascii_digit())
+    }
+
+    // Parse "R5,C0,M1,S34..58,B34..45,NM" into the rule and its number of states (C0 counts as 2)
+    pub fn parse(s: &str) -> Result<(Self, u16)> {
+        let mut range = None;
+        let mut states = 2u16;
+        let mut middle = false;
+        let mut survival = None;
+        let mut birth = None;
+        let mut neighbourhood = Neighbourhood::Moore;
+
+        for field in s.split(',').map(str::trim).filter(|f| !f.is_empty()) {
+            let (key, value) = field.split_at(1);
+            match key.to_ascii_uppercase().as_str() {
+                "R" => range = Some(parse_number(value, s)?),
+                "C" => {
+                    states = match parse_number(value, s)? {
+                        0..=2 => 2,
+                        n if n <= crate::gol::rule::MAX_STATES as u32 => n as u16,
+                        _ => return Err(format!("Invalid rule '{}': at most {} states.", s, crate::gol::rule::MAX_STATES).into()),
+                    }
+                }
+                "M" => middle = parse_number(value, s)? == 1,
+                "S" => survival = Some(parse_interval(value, s)?),
+                "B" => birth = Some(parse_interval(value, s)?),
+                "N" => {
+                    neighbourhood = match value.to_ascii_uppercase().as_str() {
+                        "M" => Neighbourhood::Moore,
+                        "N" => Neighbourhood::VonNeumann,
+                        "C" => Neighbourhood::Circular,
+                        _ => return Err(format!("Invalid rule '{}': unknown neighbourhood '{}' (expected NM, NN or NC).", s, field).into()),
+                    }
+                }
+                _ => return Err(format!("Invalid rule '{}': unexpected field '{}'.", s, field).into()),
+            }
+        }
+
+        let range = match range {
+            Some(r) if (1..=MAX_RANGE as u32).contains(&r) => r as u16,
+            Some(_) => return Err(format!("Invalid rule '{}': the range must be 1-{}.", s, MAX_RANGE).into()),
+            None => return Err(format!("Invalid rule '{}': missing range (R).", s).into()),
+        };
+        let (Some(survival), Some(birth)) = (survival, birth) else {
+            return Err(format!("Invalid rule '{}': expected S<min>..<max> and B<min>..<max>.", s).into());
+        };
+        if birth.0 == 0 {
+            return Err(format!("Invalid rule '{}': births with no live neighbour are not supported.", s).into());
+        }
+
+        Ok((
+            Self {
+                range,
+                middle,
+                survival,
+                birth,
+                neighbourhood,
+            },
+            states,
+        ))
+    }
+
+    // Half width of the neighbourhood on the row dy cells above or below the centre (|dy| <= range)
+    pub fn half_width(&self, dy: i32) -> i32 {
+        let r = self.range as i32;
+        match self.neighbourhood {
+            Neighbourhood::Moore => r,
+            Neighbourhood::VonNeumann => r - dy.abs(),
+            Neighbourhood::Circular => ((r * r + r - dy * dy) as f64).sqrt().floor() as i32,
+        }
+    }
+
+    // State of a cell next generation given its state and the live count of its neighbourhood
+    // (the middle cell is already included or not according to `middle`)
+    #[inline]
+    pub fn next_state(&self, state: u8, count: u32, states: u16) -> u8 {
+        let within = |(min, max): (u32, u32)| (min..=max).contains(&count);
+        match state {
+            0 => within(self.birth) as u8,
+            1 if within(self.survival) => 1,
+            _ => {
+                let next = state as u16 + 1;
+                if next >= states { 0 } else { next as u8 }
+            }
+        }
+    }
+}
+
+// Golly canonical form, C0 for two states
+pub fn format_ltl(ltl: &LargerThanLife, states: u16, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let n = match ltl.neighbourhood {
+        Neighbourhood::Moore => 'M',
+        Neighbourhood::VonNeumann => 'N',
+        Neighbourhood::Circular => 'C',
+    };
+    write!(
+        f,
+        "R{},C{},M{},S{}..{},B{}..{},N{}",
+        ltl.range,
+        if states > 2 { states } else { 0 },
+        ltl.middle as u8,
+        ltl.survival.0,
+        ltl.survival.1,
+        ltl.birth.0,
+        ltl.birth.1,
+        n
+    )
+}
+
+// Compute one step of a Larger than Life rule on cell states (0 dead, 1 alive, 2.. dying)
+pub fn step_ltl(states_current: &[u8], states_next: &mut [u8], buffer_width: u32, buffer_height: u32, rule: &Rule) {
+    step_ltl_parallel(states_current, states_next, buffer_width, buffer_height, rule, &Workers::single());
+}
+
+// Neighbourhood counts come from prefix sums of a copy of the board padded by R cells (filled through the topology):
+// - Moore: summed-area table, 4 lookups per cell whatever the range
+// - von Neumann / circular: row prefix sums, 2 lookups per row of the neighbourhood (2R + 1 rows)
+// The rows of the next generation are then split into one band per worker, computed in parallel
+pub fn step_ltl_parallel(states_current: &[u8], states_next: &mut [u8], buffer_width: u32, buffer_height: u32, rule: &Rule, workers: &Workers) {
+    debug_assert_eq!(states_current.len(), states_next.len());
+    let Some(ltl) = rule.larger_than_life() else {
+        debug_assert!(false, "step_ltl() needs a Larger than Life rule, got {}", rule);
+        return;
+    };
+    if buffer_width == 0 || buffer_height == 0 {
+        return;
+    }
+
+    let table = PrefixSums::new(states_current, buffer_width, buffer_height, rule, &ltl);
+    let threads = workers.threads();
+    if threads <= 1 || buffer_height < 2 {
+        step_ltl_rows(states_current, states_next, buffer_width, 0, &table, &ltl, rule.states());
+        return;
+    }
+
+    let rows_per_band = (buffer_height as usize).div_ceil(threads);
+    let (table, ltl) = (&table, &ltl);
+    workers.scope(|scope| {
+        for (i, band) in states_next.chunks_mut(rows_per_band * buffer_width as usize).enumerate() {
+            scope.spawn(move |_| step_ltl_rows(states_current, band, buffer_width, i * rows_per_band, table, ltl, rule.states()));
+        }
+    });
+}
+
+// Compute the rows [first_row, first_row + band rows) of the next generation into `band`
+fn step_ltl_rows(states_current: &[u8], band: &mut [u8], buffer_width: u32, first_row: usize, table: &PrefixSums, ltl: &LargerThanLife, states: u16) {
+    let w = buffer_width as usize;
+    let mut counts = vec![0u32; w];
+    for (band_y, row) in band.chunks_exact_mut(w).enumerate() {
+        let y = first_row + band_y;
+        table.count_row(y, &mut counts);
+        for (x, next) in row.iter_mut().enumerate() {
+            let state = states_current[y * w + x];
+            let mut count = counts[x];
+            if !ltl.middle && state == 1 {
+                count -= 1;
+            }
+            *next = ltl.next_state(state, count, states);
+        }
+    }
+}
+
+// Prefix sums of the live cells (state 1) of the board padded by `range` cells on every side
+struct PrefixSums {
+    range: usize,
+    stride: usize,                   // padded width + 1
+    sums: Vec<u32>,                  // Moore: summed-area table, otherwise: per row prefix sums; sums[y * stride + x] covers columns [0, x)
+    half_widths: Option<Vec<usize>>, // von Neumann / circular: half width of each of the 2R + 1 rows of the neighbourhood
+}
+
+impl PrefixSums {
+    fn new(states: &[u8], width: u32, height: u32, rule: &Rule, ltl: &LargerThanLife) -> Self {
+        let topology = rule.topology();
+        let r = ltl.range as usize;
+        let (w, h) = (width as usize, height as usize);
+        let (pw, ph) = (w + 2 * r, h + 2 * r);
+        let stride = pw + 1;
+        let summed_area = ltl.neighbourhood == Neighbourhood::Moore;
+
+        // Summed-area table has an extra zero row on top
+        let rows = if summed_area { ph + 1 } else { ph };
+        let mut sums = vec![0u32; rows * stride];
+        for py in 0..ph {
+            let by = py as i64 - r as i64;
+            let out_row = if summed_area { py + 1 } else { py };
+            let mut running = 0u32;
+            for px in 0..pw {
+                let bx = px as i64 - r as i64;
+                let alive = if bx >= 0 && by >= 0 && (bx as usize) < w && (by as usize) < h {
+                    states[by as usize * w + bx as usize] == 1
+                } else {
+                    match topology.map(bx, by, width, height) {
+                        Some((x, y)) => states[y as usize * w + x as usize] == 1,
+                        None => false,
+                    }
+                };
+                running += alive as u32;
+                sums[out_row * stride + px + 1] = running;
+            }
+            if summed_area {
+                for px in 1..stride {
+                    sums[out_row * stride + px] += sums[py * stride + px];
+                }
+            }
+        }
+        let half_widths = (!summed_area).then(|| (-(r as i32)..=r as i32).map(|dy| ltl.half_width(dy) as usize).collect());
+        Self { range: r, stride, sums, half_widths }
+    }
+
+    // Live cells in the neighbourhood of every cell of board row y (the cells themselves included)
+    // Board (x, y) is padded (x + r, y + r): the neighbourhood window starts at padded (x, y)
+    fn count_row(&self, y: usize, counts: &mut [u32]) {
+        let (r, stride, w) = (self.range, self.stride, counts.len());
+        let Some(half_widths) = &self.half_widths else {
+            let top = &self.sums[y * stride..][..stride];
+            let bottom = &self.sums[(y + 2 * r + 1) * stride..][..stride];
+            for (x, count) in counts.iter_mut().enumerate() {
+                let x1 = x + 2 * r + 1;
+                *count = bottom[x1] + top[x] - top[x1] - bottom[x];
+            }
+            return;
+        };
+        // One pass per row of the neighbourhood, contiguous in x so that it vectorizes
+        counts.fill(0);
+        for (i, &hw) in half_widths.iter().enumerate() {
+            let row = &self.sums[(y + i) * stride..][..stride];
+            let (right, left) = (&row[r + hw + 1..][..w], &row[r - hw..][..w]);
+            for ((count, &a), &b) in counts.iter_mut().zip(right).zip(left) {
+                *count += a - b;
+            }
+        }
+    }
+}
+
+// --- helpers ----------------------------------------------------------------
+
+fn parse_number(value: &str, rule: &str) -> Result<u32> {
+    value
+        .trim()
+        .parse::<u32>()
+        .map_err(|e| -> crate::Error { format!("Invalid rule '{}': bad number '{}' ({}).", rule, value, e).into() })
+}
+
+// "34..58" -> (34, 58), "3" -> (3, 3)
+fn parse_interval(value: &str, rule: &str) -> Result<(u32, u32)> {
+    let (min, max) = match value.split_once("..") {
+        Some((min, max)) => (parse_number(min, rule)?, parse_number(max, rule)?),
+        None => {
+            let n = parse_number(value, rule)?;
+            (n, n)
+        }
+    };
+    if min > max {
+        return Err(format!("Invalid rule '{}': empty interval '{}'.", rule, value).into());
+    }
+    Ok((min, max))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gol::life::step_life;
+    use crate::gol::topology::{Topology, TopologyKind};
+
+    fn soup(w: u32, h: u32, seed: usize) -> Vec<u8> {
+        (0..(w * h) as usize).map(|i| (i * 7919 + i / 13 + seed).is_multiple_of(3) as u8).collect()
+    }
+
+    #[test]
+    fn parse_and_display() {
+        let bosco = Rule::parse("R5,C0,M1,S34..58,B34..45,NM").unwrap();
+        let ltl = bosco.larger_than_life().unwrap();
+        assert_eq!((ltl.range, ltl.middle, ltl.survival, ltl.birth), (5, true, (34, 58), (34, 45)));
+        assert_eq!(bosco.states(), 2);
+        assert_eq!(bosco.to_string(), "R5,C0,M1,S34..58,B34..45,NM");
+
+        let decay = Rule::parse("r10,c3,m0,s2..3,b3,nc:T200,100").unwrap();
+        assert_eq!(decay.states(), 3);
+        assert_eq!(decay.to_string(), "R10,C3,M0,S2..3,B3..3,NC:T200,100");
+
+        assert!(Rule::parse("R0,C0,M1,S1..2,B1..2,NM").is_err());
+        assert!(Rule::parse("R5,C0,M1,S34..58,NM").is_err());
+        assert!(Rule::parse("R5,C0,M1,S34..58,B4..2,NM").is_err());
+        assert!(Rule::parse("R5,C0,M1,S34..58,B34..45,NX").is_err());
+    }
+
+    #[test]
+    fn range_one_moore_is_life() {
+        let (w, h) = (50u32, 40u32);
+        for (text, topology) in [("R1,C0,M0,S2..3,B3,NM", Topology::plane()), ("R1,C0,M1,S3..4,B3,NM", Topology::new(TopologyKind::Torus, w, h))] {
+            let ltl = Rule::parse(text).unwrap().with_topology(topology);
+            let life = Rule::life().with_topology(topology);
+            let workers = Workers::new(3).unwrap();
+            let mut states = soup(w, h, 1);
+            let mut cells: Vec<bool> = states.iter().map(|&s| s == 1).collect();
+            let (mut next_states, mut next_cells) = (vec![0u8; states.len()], vec![false; cells.len()]);
+            for generation in 1..=20 {
+                step_ltl_parallel(&states, &mut next_states, w, h, &ltl, &workers);
+                std::mem::swap(&mut states, &mut next_states);
+                step_life(&cells, &mut next_cells, w, h, &life);
+                std::mem::swap(&mut cells, &mut next_cells);
+                assert!(states.iter().zip(&cells).all(|(&s, &c)| (s == 1) == c), "{text}: differs at generation {generation}");
+            }
+        }
+    }
+
+    #[test]
+    fn counts_match_brute_force() {
+        let (w, h) = (37u32, 29u32);
+        let states = soup(w, h, 5);
+        for text in ["R4,C0,M1,S1..100,B1..100,NM", "R4,C0,M1,S1..100,B1..100,NN", "R4,C0,M1,S1..100,B1..100,NC"] {
+            let rule = Rule::parse(text).unwrap();
+            let ltl = rule.larger_than_life().unwrap();
+            let table = PrefixSums::new(&states, w, h, &rule, &ltl);
+            let r = ltl.range as i32;
+            let mut counts = vec![0u32; w as usize];
+            for y in 0..h as i32 {
+                table.count_row(y as usize, &mut counts);
+                for x in 0..w as i32 {
+                    let mut expected = 0;
+                    for dy in -r..=r {
+                        for dx in -r..=r {
+                            let inside = match ltl.neighbourhood {
+                                Neighbourhood::Moore => true,
+                                Neighbourhood::VonNeumann => dx.abs() + dy.abs() <= r,
+                                Neighbourhood::Circular => dx * dx + dy * dy <= r * r + r,
+                            };
+                            let (nx, ny) = (x + dx, y + dy);
+                            if inside && nx >= 0 && ny >= 0 && nx < w as i32 && ny < h as i32 && states[(ny as u32 * w + nx as u32) as usize] == 1 {
+                                expected += 1;
+                            }
+                        }
+                    }
+                    assert_eq!(counts[x as usize], expected, "{text} at ({x}, {y})");
+                }
+            }
+        }
+    }
+}
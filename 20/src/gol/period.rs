@@ -0,0 +1,203 @@
+// gol/period.rs
+
+use crate::gol::tally::Signature;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+
+// Still lifes, oscillators and spaceships are found when a generation repeats an earlier one, maybe somewhere else.
+// Each generation comes as the signature its engine keeps while stepping (see gol::tally): a hash of the cells
+// relative to their centre, so a glider 4 generations later has the same hash as now: the two centres give the
+// displacement. Only the hashes of the last `window` generations are kept (a longer period is not seen).
+// The period is the number of generations between the two, which is only right when none was skipped in between:
+// a still life seen every 2^k generations (HashLife steps) repeats after 2^k of them, a blinker after 2^k too
+// or never. So a gap in the generations observed starts the detection over.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Periodicity {
+    Extinct,
+    StillLife,
+    Oscillator { period: u64 },
+    Spaceship { period: u64, dx: i64, dy: i64 },
+}
+
+impl Periodicity {
+    // Generations between two repeats (0 once nothing is left)
+    pub fn period(&self) -> u64 {
+        match *self {
+            Periodicity::Extinct => 0,
+            Periodicity::StillLife => 1,
+            Periodicity::Oscillator { period } | Periodicity::Spaceship { period, .. } => period,
+        }
+    }
+}
+
+impl fmt::Display for Periodicity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Periodicity::Extinct => write!(f, "died out"),
+            Periodicity::StillLife => write!(f, "still life"),
+            Periodicity::Oscillator { period } => write!(f, "oscillator period {}", period),
+            Periodicity::Spaceship { period, dx, dy } => {
+                // Cells per generation along the longest axis, c/4 for the glider, c/2 for the LWSS
+                let distance = dx.unsigned_abs().max(dy.unsigned_abs());
+                let divisor = gcd(distance, period);
+                let (distance, period_reduced) = (distance / divisor, period / divisor);
+                let speed = if distance == 1 {
+                    format!("c/{}", period_reduced)
+                } else {
+                    format!("{}c/{}", distance, period_reduced)
+                };
+                write!(f, "spaceship period {}, displacement ({},{}), speed {}", period, dx, dy, speed)
+            }
+        }
+    }
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 { a.max(1) } else { gcd(b, a % b) }
+}
+
+pub struct PeriodDetector {
+    window: usize,
+    seen: HashMap<u64, (u64, i64, i64)>, // hash -> (generation, centre x, centre y) of its latest occurrence
+    order: VecDeque<(u64, u64)>,         // (hash, generation) oldest first, to forget the generations out of the window
+    last: Option<u64>,                   // generation observed last, the next one has to follow it
+}
+
+impl PeriodDetector {
+    pub fn new(window: usize) -> Self {
+        Self {
+            window: window.max(1),
+            seen: HashMap::new(),
+            order: VecDeque::new(),
+            last: None,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.seen.clear();
+        self.order.clear();
+        self.last = None;
+    }
+
+    // Add a generation (the signature of its tally, None when no cell is left) and tell what it repeats,
+    // if it repeats anything. Generations must come in increasing order, when some are skipped
+    // (HashLife steps) the ones before the gap are forgotten.
+    pub fn observe(&mut self, generation: u64, signature: Option<Signature>) -> Option<Periodicity> {
+        if self.last.is_some_and(|last| last.checked_add(1) != Some(generation)) {
+            self.clear();
+        }
+        self.last = Some(generation);
+        let Some(Signature { hash, x, y }) = signature else {
+            return Some(Periodicity::Extinct);
+        };
+
+        let repeat = self.seen.insert(hash, (generation, x, y)).and_then(|(previous, previous_x, previous_y)| {
+            let period = generation.checked_sub(previous).filter(|&period| period > 0)?;
+            let (dx, dy) = (x.wrapping_sub(previous_x), y.wrapping_sub(previous_y));
+            Some(match (period, dx, dy) {
+                (1, 0, 0) => Periodicity::StillLife,
+                (_, 0, 0) => Periodicity::Oscillator { period },
+                _ => Periodicity::Spaceship { period, dx, dy },
+            })
+        });
+
+        self.order.push_back((hash, generation));
+        while self.order.len() > self.window {
+            let Some((old_hash, old_generation)) = self.order.pop_front() else {
+                break;
+            };
+            // Only forget the hash if it was not seen again since
+            if self.seen.get(&old_hash).is_some_and(|&(latest, _, _)| latest == old_generation) {
+                self.seen.remove(&old_hash);
+            }
+        }
+        repeat
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gol::{rule::Rule, sparse::SparseBoard, tally::Tally};
+
+    // Run a pattern on the sparse engine until it repeats
+    fn detect(pattern: &[(i64, i64)], generations: u64) -> Option<(u64, Periodicity)> {
+        let rule = Rule::life();
+        let mut board = SparseBoard::new();
+        pattern.iter().for_each(|&(x, y)| board.set_cell(x, y, true));
+        let mut detector = PeriodDetector::new(64);
+        for generation in 0..=generations {
+            if let Some(periodicity) = detector.observe(generation, board.tally().signature()) {
+                return Some((generation, periodicity));
+            }
+            board.step(&rule);
+        }
+        None
+    }
+
+    // Signature of some cells in their states
+    fn signature(cells: &[(i64, i64, u8)]) -> Option<Signature> {
+        let mut tally = Tally::default();
+        cells.iter().for_each(|&(x, y, state)| tally.change_at(x, y, 0, state));
+        tally.signature()
+    }
+
+    #[test]
+    fn still_life_oscillator_and_spaceships() {
+        let block = [(0, 0), (1, 0), (0, 1), (1, 1)];
+        assert_eq!(detect(&block, 10), Some((1, Periodicity::StillLife)));
+
+        let blinker = [(0, 0), (1, 0), (2, 0)];
+        assert_eq!(detect(&blinker, 10), Some((2, Periodicity::Oscillator { period: 2 })));
+
+        let glider = [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+        let (generation, periodicity) = detect(&glider, 10).unwrap();
+        assert_eq!((generation, periodicity), (4, Periodicity::Spaceship { period: 4, dx: 1, dy: 1 }));
+        assert_eq!(periodicity.to_string(), "spaceship period 4, displacement (1,1), speed c/4");
+
+        let lwss = [(1, 0), (4, 0), (0, 1), (0, 2), (4, 2), (0, 3), (1, 3), (2, 3), (3, 3)];
+        let (_, periodicity) = detect(&lwss, 10).unwrap();
+        assert_eq!(periodicity.to_string(), "spaceship period 4, displacement (-2,0), speed c/2");
+
+        // A lone cell dies
+        assert_eq!(detect(&[(5, 5)], 10), Some((1, Periodicity::Extinct)));
+    }
+
+    #[test]
+    fn skipped_generations_are_not_a_period() {
+        // A still life and a blinker seen every 4 generations, like HashLife steps at exponent 2 show them
+        let block = signature(&[(0, 0, 1), (1, 0, 1), (0, 1, 1), (1, 1, 1)]);
+        let horizontal = signature(&[(0, 1, 1), (1, 1, 1), (2, 1, 1)]);
+        let vertical = signature(&[(1, 0, 1), (1, 1, 1), (1, 2, 1)]);
+        let mut detector = PeriodDetector::new(64);
+        for generation in [0, 4, 8] {
+            assert_eq!(detector.observe(generation, block), None);
+        }
+        for generation in [100, 104, 108] {
+            assert_eq!(detector.observe(generation, horizontal), None);
+        }
+
+        // Back to one generation at a time: found again from there
+        assert_eq!(detector.observe(200, horizontal), None);
+        assert_eq!(detector.observe(201, vertical), None);
+        assert_eq!(detector.observe(202, horizontal), Some(Periodicity::Oscillator { period: 2 }));
+    }
+
+    #[test]
+    fn window_forgets_old_generations() {
+        let mut detector = PeriodDetector::new(2);
+        let a = signature(&[(0, 0, 1)]);
+        let b = signature(&[(0, 0, 1), (1, 0, 1)]);
+        let c = signature(&[(0, 0, 1), (0, 1, 2)]);
+        assert_eq!(detector.observe(0, a), None);
+        assert_eq!(detector.observe(1, b), None);
+        assert_eq!(detector.observe(2, c), None);
+        // `a` was 3 generations ago, out of the window
+        assert_eq!(detector.observe(3, a), None);
+        // `c` is 2 generations ago, still there
+        assert_eq!(detector.observe(4, c), Some(Periodicity::Oscillator { period: 2 }));
+        assert_eq!(Periodicity::Spaceship { period: 4, dx: 2, dy: 0 }.to_string(), "spaceship period 4, displacement (2,0), speed c/2");
+        assert_eq!(Periodicity::Spaceship { period: 5, dx: 0, dy: 2 }.to_string(), "spaceship period 5, displacement (0,2), speed 2c/5");
+    }
+}
@@ -0,0 +1,482 @@
+// gol/rule.rs
+
+use crate::Result;
+use crate::gol::hensel::NeighbourhoodSet;
+use crate::gol::ltl::{self, LargerThanLife};
+use crate::gol::ruletable::RuleTable;
+use crate::gol::topology::Topology;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+// Outer-totalistic rule in B/S notation (e.g. "B3/S23" for Conway's Life)
+// birth[n]    : a dead cell with n live neighbours becomes alive
+// survival[n] : a live cell with n live neighbours stays alive
+// states      : number of cell states, 2 for Life-like rules, more for Generations rules (e.g. "B2/S/C3")
+//               where a cell that does not survive goes through states 2..states-1 before it is dead again
+// isotropic   : [birth, survival] neighbourhood sets of an isotropic non-totalistic rule (e.g. "B2-a/S12",
+//               see gol::hensel), birth/survival then tell which counts have at least one configuration
+// neighbourhood: neighbours counted on the square grid, Golly's "H" (hexagonal) and "V" (von Neumann) suffixes
+// ltl         : Larger than Life rule (range-R neighbourhood, e.g. "R5,C0,M1,S34..58,B34..45,NM"),
+//               birth/survival are then unused
+// table       : Golly rule table loaded from a .rule file (e.g. WireWorld, see gol::ruletable), shared between
+//               the copies of the rule, birth/survival are then unused
+// topology    : optional Golly bounded grid suffix (e.g. "B3/S23:T1280,800")
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+    birth: [bool; 9],
+    survival: [bool; 9],
+    states: u16,
+    isotropic: Option<[NeighbourhoodSet; 2]>,
+    neighbourhood: Neighbourhood,
+    ltl: Option<LargerThanLife>,
+    table: Option<Arc<RuleTable>>,
+    topology: Topology,
+}
+
+// Neighbours of a cell on the square grid (see gol::hensel for the mask bit layout)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Neighbourhood {
+    Moore,      // the 8 surrounding cells
+    Hexagonal,  // "H": 6 neighbours, NE and SW are left out (a hexagonal grid drawn sheared, like in Golly)
+    VonNeumann, // "V": the 4 orthogonal neighbours
+}
+
+impl Neighbourhood {
+    // Bits of the neighbourhood mask this neighbourhood looks at
+    pub fn stencil(&self) -> u8 {
+        match self {
+            Neighbourhood::Moore => 0b1111_1111,
+            Neighbourhood::Hexagonal => 0b1101_1011,
+            Neighbourhood::VonNeumann => 0b0101_1010,
+        }
+    }
+
+    pub fn size(&self) -> u8 {
+        self.stencil().count_ones() as u8
+    }
+
+    fn suffix(&self) -> &'static str {
+        match self {
+            Neighbourhood::Moore => "",
+            Neighbourhood::Hexagonal => "H",
+            Neighbourhood::VonNeumann => "V",
+        }
+    }
+}
+
+// Golly's multi-state RLE alphabet encodes states 0..=255
+pub const MAX_STATES: u16 = 256;
+
+impl Rule {
+    // Conway's Game of Life (B3/S23)
+    pub const fn life() -> Self {
+        let mut birth = [false; 9];
+        let mut survival = [false; 9];
+        birth[3] = true;
+        survival[2] = true;
+        survival[3] = true;
+        Self {
+            birth,
+            survival,
+            states: 2,
+            isotropic: None,
+            neighbourhood: Neighbourhood::Moore,
+            ltl: None,
+            table: None,
+            topology: Topology::plane(),
+        }
+    }
+
+    // Parse a rule string. Accepted forms (case insensitive):
+    // - "B3/S23", "B36/S23", "B2/S" (Golly/RLE standard)
+    // - "S23/B3" (reversed)
+    // - "23/3" (legacy survival/birth form used by old .lif/.rle files)
+    // - isotropic non-totalistic (Hensel notation): "B2-a/S12", "B3-cnqy/S23-a" (see gol::hensel)
+    // - Generations: "B2/S/C3", "S345/B2/C4" or the legacy "345/2/4" (survival/birth/states)
+    // - hexagonal or von Neumann neighbourhood: "B2/S34H", "B13/S012V" (and "B2/S34/C3H")
+    // - Larger than Life: "R5,C0,M1,S34..58,B34..45,NM" (see gol::ltl)
+    // - a few well known names ("Life", "HighLife", "Seeds", "DayAndNight", "BriansBrain", "StarWars"...)
+    // Any of them may be followed by a bounded grid suffix, e.g. "B3/S23:T1280,800"
+    pub fn parse(s: &str) -> Result<Self> {
+        if let Some((rule, topology)) = s.split_once(':') {
+            return Ok(Self::parse(rule)?.with_topology(Topology::parse(topology)?));
+        }
+
+        let text: String = s.chars().filter(|c| !c.is_whitespace()).collect();
+        if text.is_empty() {
+            return Err("Empty rule string.".into());
+        }
+
+        if let Some(rule) = Self::from_name(&text) {
+            return Ok(rule);
+        }
+
+        if LargerThanLife::is_ltl_notation(&text) {
+            let (ltl, states) = LargerThanLife::parse(&text)?;
+            return Ok(Self {
+                birth: [false; 9],
+                survival: [false; 9],
+                states,
+                isotropic: None,
+                neighbourhood: Neighbourhood::Moore,
+                ltl: Some(ltl),
+                table: None,
+                topology: Topology::plane(),
+            });
+        }
+
+        // Golly's neighbourhood suffix, none of the other letters of a rule string is 'H' or 'V'
+        let (text, neighbourhood) = match text.chars().last() {
+            Some('H' | 'h') => (&text[..text.len() - 1], Neighbourhood::Hexagonal),
+            Some('V' | 'v') => (&text[..text.len() - 1], Neighbourhood::VonNeumann),
+            _ => (text.as_str(), Neighbourhood::Moore),
+        };
+
+        let mut parts: Vec<&str> = text.split('/').collect();
+        let mut states = 2;
+        if parts.len() == 3 {
+            // Generations: the last part is the number of states, with an optional 'C' (or 'G') prefix
+            let count = parts.pop().unwrap_or_default();
+            let digits = count.strip_prefix(['C', 'c', 'G', 'g']).unwrap_or(count);
+            states = match digits.parse::<u16>() {
+                Ok(n) if (2..=MAX_STATES).contains(&n) => n,
+                _ => return Err(format!("Invalid rule '{}': the number of states must be 2-{}.", s, MAX_STATES).into()),
+            };
+        }
+
+        let (birth_digits, survival_digits) = match parts.as_slice() {
+            [a, b] => {
+                let (a_prefix, a_digits) = split_prefix(a);
+                let (b_prefix, b_digits) = split_prefix(b);
+                match (a_prefix, b_prefix) {
+                    (Some('b'), Some('s')) | (Some('b'), None) => (a_digits, b_digits),
+                    (Some('s'), Some('b')) | (None, Some('b')) => (b_digits, a_digits),
+                    (None, None) => (b_digits, a_digits), // legacy "S/B", e.g. "23/3"
+                    _ => return Err(format!("Invalid rule '{}': expected B<digits>/S<digits>.", s).into()),
+                }
+            }
+            [single] => {
+                // "B3S23" without slash
+                let lower = single.to_ascii_lowercase();
+                match (lower.find('b'), lower.find('s')) {
+                    (Some(b), Some(s_pos)) if b < s_pos => (&single[b + 1..s_pos], &single[s_pos + 1..]),
+                    (Some(b), Some(s_pos)) => (&single[b + 1..], &single[s_pos + 1..b]),
+                    _ => return Err(format!("Invalid rule '{}': expected B<digits>/S<digits>.", s).into()),
+                }
+            }
+            _ => return Err(format!("Invalid rule '{}': too many '/' separators.", s).into()),
+        };
+
+        let birth = NeighbourhoodSet::parse(birth_digits, s)?;
+        let survival = NeighbourhoodSet::parse(survival_digits, s)?;
+        if neighbourhood != Neighbourhood::Moore {
+            if !birth.is_totalistic() || !survival.is_totalistic() {
+                return Err(format!("Invalid rule '{}': Hensel letters need the 8 cell (Moore) neighbourhood.", s).into());
+            }
+            let beyond_size = |counts: [bool; 9]| counts.iter().skip(neighbourhood.size() as usize + 1).any(|&set| set);
+            if beyond_size(birth.counts()) || beyond_size(survival.counts()) {
+                return Err(format!("Invalid rule '{}': cells have only {} neighbours.", s, neighbourhood.size()).into());
+            }
+        }
+        Ok(Self {
+            birth: birth.counts(),
+            survival: survival.counts(),
+            states,
+            // Letters naming every configuration of a count ("B2ceaikn") still make a B/S rule
+            isotropic: (!birth.is_totalistic() || !survival.is_totalistic()).then_some([birth, survival]),
+            neighbourhood,
+            ltl: None,
+            table: None,
+            topology: Topology::plane(),
+        })
+    }
+
+    // Rule named by a Golly rule table (the name Display shows), Rule::parse() does not read files
+    pub fn from_table(table: RuleTable) -> Self {
+        Self {
+            birth: [false; 9],
+            survival: [false; 9],
+            states: table.states(),
+            isotropic: None,
+            neighbourhood: table.neighbourhood(),
+            ltl: None,
+            table: Some(Arc::new(table)),
+            topology: Topology::plane(),
+        }
+    }
+
+    pub fn rule_table(&self) -> Option<&RuleTable> {
+        self.table.as_deref()
+    }
+
+    // Number of cell states (2 unless this is a Generations rule)
+    pub fn states(&self) -> u16 {
+        self.states
+    }
+
+    // Do dying cells go through refractory states?
+    pub fn is_generations(&self) -> bool {
+        self.states > 2
+    }
+
+    pub fn larger_than_life(&self) -> Option<LargerThanLife> {
+        self.ltl
+    }
+
+    // Two states and immediate neighbours only: the rules stepped on a board of bools
+    pub fn is_life_like(&self) -> bool {
+        self.states == 2 && self.ltl.is_none() && self.table.is_none()
+    }
+
+    // Life-like B/S rule counting the 8 surrounding cells: what the packed, HashLife and sparse engines run
+    pub fn is_moore_totalistic(&self) -> bool {
+        self.is_life_like() && self.is_totalistic() && self.neighbourhood == Neighbourhood::Moore
+    }
+
+    pub fn neighbourhood(&self) -> Neighbourhood {
+        self.neighbourhood
+    }
+
+    // Only the number of live neighbours matters (B/S rules), the engines counting neighbours need it
+    pub fn is_totalistic(&self) -> bool {
+        self.isotropic.is_none()
+    }
+
+    // Next state of a cell indexed by [alive][neighbourhood mask] (see gol::hensel for the mask layout)
+    // Built once per step by the steppers looking at the neighbourhood configuration or shape
+    pub fn neighbourhood_table(&self) -> [[bool; 256]; 2] {
+        let [birth, survival] = self.isotropic.unwrap_or([NeighbourhoodSet::from_counts(&self.birth), NeighbourhoodSet::from_counts(&self.survival)]);
+        let stencil = self.neighbourhood.stencil();
+        let mut table = [[false; 256]; 2];
+        for mask in 0..=255u8 {
+            table[0][mask as usize] = birth.contains(mask & stencil);
+            table[1][mask as usize] = survival.contains(mask & stencil);
+        }
+        table
+    }
+
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    // Same birth/survival conditions on another grid
+    pub fn with_topology(mut self, topology: Topology) -> Self {
+        self.topology = topology;
+        self
+    }
+
+    // Well known rule names (lowercase, no spaces) accepted in RLE headers
+    fn from_name(name: &str) -> Option<Self> {
+        let rule = match name.to_ascii_lowercase().as_str() {
+            "life" | "conway" | "conwayslife" => "B3/S23",
+            "highlife" => "B36/S23",
+            "seeds" => "B2/S",
+            "daynight" | "dayandnight" => "B3678/S34678",
+            "lifewithoutdeath" => "B3/S012345678",
+            "2x2" => "B36/S125",
+            "maze" => "B3/S12345",
+            "replicator" => "B1357/S1357",
+            "briansbrain" => "B2/S/C3",
+            "starwars" => "B2/S345/C4",
+            _ => return None,
+        };
+        Self::parse(rule).ok()
+    }
+
+    // State of a cell in the next generation given its state and live neighbour count
+    #[inline]
+    pub fn next_state(&self, alive: bool, neighbours: u8) -> bool {
+        if alive { self.survival[neighbours as usize] } else { self.birth[neighbours as usize] }
+    }
+
+    pub fn is_birth(&self, neighbours: u8) -> bool {
+        self.birth[neighbours as usize]
+    }
+
+    pub fn is_survival(&self, neighbours: u8) -> bool {
+        self.survival[neighbours as usize]
+    }
+
+    // Generations transition: only cells in state 1 are alive (and counted as neighbours)
+    // 0 -> 1 on birth, 1 stays 1 on survival or starts dying (2), dying states age until they wrap to 0
+    #[inline]
+    pub fn next_generations_state(&self, state: u8, live_neighbours: u8) -> u8 {
+        let alive_next = match state {
+            0 => self.birth[live_neighbours as usize],
+            1 => self.survival[live_neighbours as usize],
+            _ => false,
+        };
+        self.generations_transition(state, alive_next)
+    }
+
+    // Same as next_generations_state() once the rule said whether a dead cell is born / a live cell survives
+    #[inline]
+    pub fn generations_transition(&self, state: u8, alive_next: bool) -> u8 {
+        match state {
+            0 => alive_next as u8,
+            1 if alive_next => 1,
+            _ => {
+                let next = state as u16 + 1;
+                if next >= self.states { 0 } else { next as u8 }
+            }
+        }
+    }
+}
+
+impl Default for Rule {
+    fn default() -> Self {
+        Self::life()
+    }
+}
+
+impl FromStr for Rule {
+    type Err = crate::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::parse(s)
+    }
+}
+
+// Canonical Golly form: "B3/S23" or "B3/S23:T1280,800" (rule tables: their name, e.g. "WireWorld")
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(table) = &self.table {
+            write!(f, "{}", table.name())?;
+            if self.topology != Topology::plane() {
+                write!(f, ":{}", self.topology)?;
+            }
+            return Ok(());
+        }
+        if let Some(ltl) = &self.ltl {
+            ltl::format_ltl(ltl, self.states, f)?;
+            if self.topology != Topology::plane() {
+                write!(f, ":{}", self.topology)?;
+            }
+            return Ok(());
+        }
+        if let Some([birth, survival]) = &self.isotropic {
+            write!(f, "B{}/S{}", birth, survival)?;
+        } else {
+            write!(f, "B")?;
+            for n in (0..9).filter(|&n| self.birth[n]) {
+                write!(f, "{}", n)?;
+            }
+            write!(f, "/S")?;
+            for n in (0..9).filter(|&n| self.survival[n]) {
+                write!(f, "{}", n)?;
+            }
+        }
+        if self.is_generations() {
+            write!(f, "/C{}", self.states)?;
+        }
+        write!(f, "{}", self.neighbourhood.suffix())?;
+        if self.topology != Topology::plane() {
+            write!(f, ":{}", self.topology)?;
+        }
+        Ok(())
+    }
+}
+
+// --- helpers ----------------------------------------------------------------
+
+// Split an optional 'B'/'S' prefix from a rule half: "B36" -> (Some('b'), "36")
+fn split_prefix(part: &str) -> (Option<char>, &str) {
+    match part.chars().next() {
+        Some(c) if c.eq_ignore_ascii_case(&'b') || c.eq_ignore_ascii_case(&'s') => (Some(c.to_ascii_lowercase()), &part[1..]),
+        _ => (None, part),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_accepts_all_life_spellings() {
+        for s in ["B3/S23", "b3/s23", "s23/b3", "S23/B3", "23/3", "B3S23", " B3 / S23 ", "Life"] {
+            assert_eq!(Rule::parse(s).unwrap(), Rule::life(), "spelling: {s:?}");
+        }
+    }
+
+    #[test]
+    fn parse_other_rules() {
+        let highlife = Rule::parse("B36/S23").unwrap();
+        assert!(highlife.is_birth(6) && highlife.is_birth(3) && !highlife.is_birth(2));
+
+        let seeds = Rule::parse("B2/S").unwrap();
+        assert!(seeds.is_birth(2));
+        assert!((0..=8).all(|n| !seeds.is_survival(n)));
+
+        assert_eq!(Rule::parse("DayAndNight").unwrap(), Rule::parse("B3678/S34678").unwrap());
+    }
+
+    #[test]
+    fn parse_rejects_garbage() {
+        assert!(Rule::parse("").is_err());
+        assert!(Rule::parse("B9/S23").is_err());
+        assert!(Rule::parse("B3/S2/3/4").is_err());
+        assert!(Rule::parse("foo").is_err());
+    }
+
+    #[test]
+    fn display_is_canonical() {
+        assert_eq!(Rule::parse("s23/b36").unwrap().to_string(), "B36/S23");
+        assert_eq!(Rule::parse("B2/S").unwrap().to_string(), "B2/S");
+        assert_eq!(Rule::parse("b3/s23:t1280,800").unwrap().to_string(), "B3/S23:T1280,800");
+        assert_eq!(Rule::parse("345/2/4").unwrap().to_string(), "B2/S345/C4");
+    }
+
+    #[test]
+    fn parse_generations_rules() {
+        let brain = Rule::parse("B2/S/C3").unwrap();
+        assert_eq!(brain.states(), 3);
+        assert!(brain.is_generations());
+        assert_eq!(Rule::parse("/2/3").unwrap(), brain);
+        assert_eq!(Rule::parse("BriansBrain").unwrap(), brain);
+        assert_eq!(Rule::parse("StarWars").unwrap(), Rule::parse("S345/B2/C4").unwrap());
+        assert!(!Rule::parse("B3/S23/C2").unwrap().is_generations());
+        assert!(Rule::parse("B2/S/C1").is_err());
+        assert!(Rule::parse("B2/S/C257").is_err());
+
+        // alive -> dying -> dead, dead -> alive on birth
+        assert_eq!(brain.next_generations_state(1, 0), 2);
+        assert_eq!(brain.next_generations_state(2, 2), 0);
+        assert_eq!(brain.next_generations_state(0, 2), 1);
+        assert_eq!(brain.next_generations_state(0, 3), 0);
+    }
+
+    #[test]
+    fn parse_isotropic_rules() {
+        let rule = Rule::parse("B2-a/S12").unwrap();
+        assert!(!rule.is_totalistic() && rule.is_life_like());
+        assert!(rule.is_birth(2) && !rule.is_birth(3));
+        assert_eq!(rule.to_string(), "B2-a/S12");
+        assert_eq!(Rule::parse("b3-CNQY/s23-a").unwrap().to_string(), "B3-cnqy/S23-a");
+        assert_eq!(Rule::parse("B2-a/S12/C3").unwrap().to_string(), "B2-a/S12/C3");
+
+        // The table looks at the configuration: two live neighbours side by side (2a) or apart (2i)
+        let table = rule.neighbourhood_table();
+        assert!(!table[0][0b0000_0011] && table[0][0b0100_0010]);
+
+        // Naming every configuration is the B/S rule
+        assert_eq!(Rule::parse("B3/S2ceaikn3").unwrap(), Rule::life());
+        assert!(Rule::parse("B2x/S23").is_err());
+        assert!(Rule::parse("B1e/S4-").is_err());
+    }
+
+    #[test]
+    fn parse_neighbourhood_suffix() {
+        let hex = Rule::parse("b2/s34h").unwrap();
+        assert_eq!(hex.neighbourhood(), Neighbourhood::Hexagonal);
+        assert!(hex.is_life_like() && !hex.is_moore_totalistic());
+        assert_eq!(hex.to_string(), "B2/S34H");
+        assert_eq!(Rule::parse("B13/S012V:T100,100").unwrap().to_string(), "B13/S012V:T100,100");
+        assert_eq!(Rule::parse("B2/S34/C3H").unwrap().to_string(), "B2/S34/C3H");
+        assert_eq!(Rule::parse("B3/S23").unwrap().neighbourhood(), Neighbourhood::Moore);
+
+        assert!(Rule::parse("B7/S34H").is_err());
+        assert!(Rule::parse("B2/S5V").is_err());
+        assert!(Rule::parse("B2a/S34H").is_err());
+    }
+}
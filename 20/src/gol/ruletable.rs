@@ -0,0 +1,572 @@
+// gol/ruletable.rs
+
+use crate::Result;
+use crate::gol::rule::{Neighbourhood, Rule};
+use crate::gol::workers::Workers;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Golly rule tables (.rule files): arbitrary multi-state automata such as WireWorld or Langton's loops
+//
+//   @RULE WireWorld
+//   @TABLE
+//   n_states:4
+//   neighborhood:Moore
+//   symmetries:permute
+//   var a={0,1,2,3}
+//   1,a,b,c,d,e,f,g,h,2     <- C,N,NE,E,SE,S,SW,W,NW,C' (von Neumann: C,N,E,S,W,C', hexagonal: C,N,E,SE,S,W,NW,C')
+//   @COLORS
+//   1 0 128 255             <- state r g b (or "first last r1 g1 b1 r2 g2 b2" for a gradient)
+//
+// A variable used once in a transition matches any of its values, used twice or more it is bound (the same
+// value everywhere, the new state included). The symmetries add the rotated / reflected / permuted copies of
+// every transition. The first transition matching a cell gives its new state, a cell matching none keeps it.
+
+// Set of cell states, one bit per state
+type StateSet = [u64; 4];
+
+fn single(state: u8) -> StateSet {
+    let mut set = [0; 4];
+    set[(state >> 6) as usize] = 1 << (state & 63);
+    set
+}
+
+fn contains(set: &StateSet, state: u16) -> bool {
+    state < 256 && set[(state >> 6) as usize] & (1 << (state & 63)) != 0
+}
+
+// Neighbours in the order of the transitions (clockwise from N), see Neighbourhood for the hexagonal grid
+pub fn ring(neighbourhood: Neighbourhood) -> &'static [(isize, isize)] {
+    match neighbourhood {
+        Neighbourhood::Moore => &[(0, -1), (1, -1), (1, 0), (1, 1), (0, 1), (-1, 1), (-1, 0), (-1, -1)],
+        Neighbourhood::VonNeumann => &[(0, -1), (1, 0), (0, 1), (-1, 0)],
+        Neighbourhood::Hexagonal => &[(0, -1), (1, 0), (1, 1), (0, 1), (-1, 0), (-1, -1)],
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleTable {
+    name: String,
+    states: u16,
+    neighbourhood: Neighbourhood,
+    // Lookup table: matches[(position * states + state) * words..][..words] is the bitset of the transitions
+    // accepting `state` at `position` (0 = the cell itself, then the neighbours of ring())
+    matches: Vec<u64>,
+    words: usize,
+    outputs: Vec<u8>,             // new state of each transition
+    colors: Vec<Option<[u8; 3]>>, // @COLORS of each state, None = default palette
+    quiescent: u8,                // new state of a dead cell with dead neighbours (computed once, most cells are there)
+}
+
+impl RuleTable {
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path).map_err(|e| -> crate::Error { format!("Failed to read rule file '{}': {}", path.display(), e).into() })?;
+        Self::parse(&text).map_err(|e| -> crate::Error { format!("Invalid rule file '{}': {}", path.display(), e).into() })
+    }
+
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut section = String::new();
+        let mut name = String::new();
+        let mut states: Option<u16> = None;
+        let mut neighbourhood = Neighbourhood::Moore;
+        let mut symmetries = String::from("none");
+        let mut variables: HashMap<String, StateSet> = HashMap::new();
+        let mut transitions: Vec<(Vec<StateSet>, u8)> = Vec::new();
+        let mut colors: Vec<(u16, [u8; 3])> = Vec::new();
+
+        for (number, raw) in text.lines().enumerate() {
+            let line = raw.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let at_line = |e: crate::Error| -> crate::Error { format!("line {}: {}", number + 1, e).into() };
+
+            if let Some(header) = line.strip_prefix('@') {
+                let (tag, rest) = header.split_once(char::is_whitespace).unwrap_or((header, ""));
+                section = tag.to_ascii_uppercase();
+                match section.as_str() {
+                    "RULE" => name = rest.trim().to_string(),
+                    "TREE" => return Err("@TREE rules are not supported, only @TABLE.".into()),
+                    _ => {}
+                }
+                continue;
+            }
+
+            match section.as_str() {
+                "TABLE" => {
+                    if let Some((key, value)) = line.split_once(':') {
+                        let value = value.trim();
+                        match key.trim().to_ascii_lowercase().as_str() {
+                            "n_states" => match value.parse::<u16>() {
+                                Ok(n) if (2..=256).contains(&n) => states = Some(n),
+                                _ => return Err(at_line(format!("n_states must be 2-256, got '{}'.", value).into())),
+                            },
+                            "neighborhood" | "neighbourhood" => {
+                                neighbourhood = match value.to_ascii_lowercase().as_str() {
+                                    "moore" => Neighbourhood::Moore,
+                                    "vonneumann" => Neighbourhood::VonNeumann,
+                                    "hexagonal" => Neighbourhood::Hexagonal,
+                                    _ => return Err(at_line(format!("unsupported neighborhood '{}' (Moore, vonNeumann or hexagonal).", value).into())),
+                                }
+                            }
+                            "symmetries" => symmetries = value.to_ascii_lowercase(),
+                            _ => return Err(at_line(format!("unknown setting '{}'.", key.trim()).into())),
+                        }
+                    } else if let Some(definition) = line.strip_prefix("var ") {
+                        let (var, set) = parse_variable(definition, &variables).map_err(at_line)?;
+                        variables.insert(var, set);
+                    } else {
+                        let states = states.ok_or_else(|| at_line("n_states must come before the transitions.".into()))?;
+                        let expanded = expand_transition(line, states, neighbourhood, &symmetries, &variables).map_err(at_line)?;
+                        transitions.extend(expanded);
+                    }
+                }
+                "COLORS" => {
+                    let numbers: Vec<u16> = line
+                        .split_whitespace()
+                        .map(|n| n.parse::<u16>())
+                        .collect::<std::result::Result<_, _>>()
+                        .map_err(|e| at_line(format!("invalid color: {}.", e).into()))?;
+                    let rgb = |n: &[u16]| -> [u8; 3] { std::array::from_fn(|c| n[c].min(255) as u8) };
+                    match numbers.as_slice() {
+                        [state, color @ ..] if color.len() == 3 => colors.push((*state, rgb(color))),
+                        [first, last, from @ .., _, _, _] if from.len() == 3 => {
+                            let (from, to) = (rgb(from), rgb(&numbers[5..]));
+                            for state in *first..=*last {
+                                let t = if last > first { (state - first) as f32 / (last - first) as f32 } else { 0.0 };
+                                colors.push((state, std::array::from_fn(|c| (from[c] as f32 + (to[c] as f32 - from[c] as f32) * t).round() as u8)));
+                            }
+                        }
+                        _ => return Err(at_line("expected 'state r g b' or 'first last r1 g1 b1 r2 g2 b2'.".into())),
+                    }
+                }
+                _ => {} // @RULE description, @ICONS, @NAMES...
+            }
+        }
+
+        let Some(states) = states else {
+            return Err("no @TABLE with n_states found.".into());
+        };
+        if name.is_empty() {
+            return Err("missing @RULE name.".into());
+        }
+        Ok(Self::new(name, states, neighbourhood, transitions, colors))
+    }
+
+    fn new(name: String, states: u16, neighbourhood: Neighbourhood, transitions: Vec<(Vec<StateSet>, u8)>, colors: Vec<(u16, [u8; 3])>) -> Self {
+        let positions = ring(neighbourhood).len() + 1;
+        let words = transitions.len().div_ceil(64).max(1);
+        let mut matches = vec![0u64; positions * states as usize * words];
+        for (t, (inputs, _)) in transitions.iter().enumerate() {
+            for (position, set) in inputs.iter().enumerate() {
+                for state in (0..states).filter(|&s| contains(set, s)) {
+                    matches[(position * states as usize + state as usize) * words + t / 64] |= 1 << (t % 64);
+                }
+            }
+        }
+
+        let mut palette = vec![None; states as usize];
+        for (state, rgb) in colors.into_iter().filter(|&(s, _)| s < states) {
+            palette[state as usize] = Some(rgb);
+        }
+
+        let mut table = Self {
+            name,
+            states,
+            neighbourhood,
+            matches,
+            words,
+            outputs: transitions.iter().map(|&(_, output)| output).collect(),
+            colors: palette,
+            quiescent: 0,
+        };
+        table.quiescent = table.next_state(&[0; 9][..positions]);
+        table
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn states(&self) -> u16 {
+        self.states
+    }
+
+    pub fn neighbourhood(&self) -> Neighbourhood {
+        self.neighbourhood
+    }
+
+    pub fn colors(&self) -> &[Option<[u8; 3]>] {
+        &self.colors
+    }
+
+    pub fn transition_count(&self) -> usize {
+        self.outputs.len()
+    }
+
+    // New state of a cell, `cells` = the cell itself then its neighbours in ring() order
+    #[inline]
+    pub fn next_state(&self, cells: &[u8]) -> u8 {
+        let stride = self.states as usize * self.words;
+        for word in 0..self.words {
+            let mut candidates = !0u64;
+            for (position, &state) in cells.iter().enumerate() {
+                candidates &= self.matches[position * stride + state as usize * self.words + word];
+                if candidates == 0 {
+                    break;
+                }
+            }
+            if candidates != 0 {
+                return self.outputs[word * 64 + candidates.trailing_zeros() as usize];
+            }
+        }
+        cells[0]
+    }
+}
+
+// "a={0,1,2}" or "b = {a,3}" (earlier variables may be used as values)
+fn parse_variable(definition: &str, variables: &HashMap<String, StateSet>) -> Result<(String, StateSet)> {
+    let (var, values) = definition.split_once('=').ok_or("expected 'var name={values}'.")?;
+    let values = values.trim().strip_prefix('{').and_then(|v| v.strip_suffix('}')).ok_or("variable values must be written {a,b,c}.")?;
+    let mut set = [0u64; 4];
+    for value in values.split(',').map(str::trim).filter(|v| !v.is_empty()) {
+        let values = state_or_variable(value, variables)?;
+        for (word, bits) in set.iter_mut().zip(values) {
+            *word |= bits;
+        }
+    }
+    Ok((var.trim().to_string(), set))
+}
+
+fn state_or_variable(token: &str, variables: &HashMap<String, StateSet>) -> Result<StateSet> {
+    match token.parse::<u8>() {
+        Ok(state) => Ok(single(state)),
+        Err(_) => variables.get(token).copied().ok_or_else(|| format!("unknown state or variable '{}'.", token).into()),
+    }
+}
+
+// One transition line -> the transitions it stands for: bound variables take each of their values in turn,
+// then every copy the symmetries give is added (duplicates left out)
+fn expand_transition(line: &str, states: u16, neighbourhood: Neighbourhood, symmetries: &str, variables: &HashMap<String, StateSet>) -> Result<Vec<(Vec<StateSet>, u8)>> {
+    let count = ring(neighbourhood).len() + 2;
+    let mut tokens: Vec<&str> = line.split(|c: char| c == ',' || c.is_whitespace()).filter(|t| !t.is_empty()).collect();
+    if tokens.len() == 1 && states <= 10 {
+        // Compact form without separators: one digit per state
+        tokens = line.char_indices().map(|(i, c)| &line[i..i + c.len_utf8()]).collect();
+    }
+    if tokens.len() != count {
+        return Err(format!(
+            "expected {} states per transition (the cell, its {} neighbours and the new state), got {}.",
+            count,
+            count - 2,
+            tokens.len()
+        )
+        .into());
+    }
+    for token in &tokens {
+        let set = state_or_variable(token, variables)?;
+        if (states..256).any(|s| contains(&set, s)) {
+            return Err(format!("'{}' has a state outside 0-{}.", token, states - 1).into());
+        }
+    }
+
+    // Variables used more than once are bound, so is the new state when it is a variable
+    let mut uses: HashMap<&str, usize> = HashMap::new();
+    for token in tokens.iter().filter(|t| t.parse::<u8>().is_err()) {
+        *uses.entry(token).or_default() += 1;
+    }
+    let output = tokens[count - 1];
+    if output.parse::<u8>().is_err() && uses[output] < 2 {
+        return Err(format!("the new state '{}' is a variable that does not appear in the inputs.", output).into());
+    }
+    let mut bound: Vec<&str> = uses.iter().filter(|&(_, &n)| n >= 2).map(|(&var, _)| var).collect();
+    bound.sort_unstable();
+    let values: Vec<Vec<u8>> = bound.iter().map(|var| (0..states).filter(|&s| contains(&variables[*var], s)).map(|s| s as u8).collect()).collect();
+
+    let mut expanded = Vec::new();
+    let mut seen = HashSet::new();
+    let mut choice = vec![0usize; bound.len()];
+    loop {
+        if values.iter().all(|v| !v.is_empty()) {
+            let resolve = |token: &str| -> StateSet {
+                match bound.iter().position(|&var| var == token) {
+                    Some(i) => single(values[i][choice[i]]),
+                    None => state_or_variable(token, variables).unwrap_or_default(),
+                }
+            };
+            let inputs: Vec<StateSet> = tokens[..count - 1].iter().map(|t| resolve(t)).collect();
+            let new_state = resolve(output);
+            let new_state = (0..states).find(|&s| contains(&new_state, s)).unwrap_or_default() as u8;
+            for copy in symmetric_copies(&inputs, neighbourhood, symmetries)? {
+                if seen.insert(copy.clone()) {
+                    expanded.push((copy, new_state));
+                }
+            }
+        }
+
+        // Next combination of the bound variables' values
+        let Some(i) = (0..choice.len()).find(|&i| choice[i] + 1 < values[i].len()) else {
+            break;
+        };
+        choice[i] += 1;
+        choice[..i].fill(0);
+    }
+    Ok(expanded)
+}
+
+// The copies of a transition's inputs (cell first, then its neighbours) under the table's symmetries
+fn symmetric_copies(inputs: &[StateSet], neighbourhood: Neighbourhood, symmetries: &str) -> Result<Vec<Vec<StateSet>>> {
+    let (cell, neighbours) = (inputs[0], &inputs[1..]);
+    let n = neighbours.len();
+    let with_cell = |neighbours: Vec<StateSet>| -> Vec<StateSet> { std::iter::once(cell).chain(neighbours).collect() };
+
+    if symmetries == "permute" {
+        // Every distinct ordering of the neighbours: lexicographic permutations of the sorted multiset
+        let mut order = neighbours.to_vec();
+        order.sort_unstable();
+        let mut copies = vec![with_cell(order.clone())];
+        while next_permutation(&mut order) {
+            copies.push(with_cell(order.clone()));
+        }
+        return Ok(copies);
+    }
+
+    // Rotations turn the ring of neighbours, the reflection mirrors it around its first neighbour (N)
+    let (rotations, reflect) = match symmetries {
+        "none" => (1, false),
+        "reflect" | "reflect_horizontal" => (1, true),
+        _ => {
+            let spec = symmetries.strip_prefix("rotate").ok_or_else(|| format!("unsupported symmetries '{}'.", symmetries))?;
+            let (order, reflect) = match spec.strip_suffix("reflect") {
+                Some(order) => (order, true),
+                None => (spec, false),
+            };
+            match order.parse::<usize>() {
+                Ok(r) if r > 0 && n % r == 0 => (r, reflect),
+                _ => return Err(format!("symmetries '{}' do not fit the {:?} neighbourhood.", symmetries, neighbourhood).into()),
+            }
+        }
+    };
+    let mut copies = Vec::new();
+    for rotation in 0..rotations {
+        let turned: Vec<StateSet> = (0..n).map(|i| neighbours[(i + rotation * n / rotations) % n]).collect();
+        if reflect {
+            copies.push(with_cell((0..n).map(|i| turned[(n - i) % n]).collect()));
+        }
+        copies.push(with_cell(turned));
+    }
+    Ok(copies)
+}
+
+// Rearrange into the next lexicographic permutation, false once the last one was reached
+fn next_permutation<T: Ord>(items: &mut [T]) -> bool {
+    let Some(i) = (1..items.len()).rev().find(|&i| items[i - 1] < items[i]) else {
+        return false;
+    };
+    let j = (i..items.len()).rev().find(|&j| items[i - 1] < items[j]).unwrap_or(i);
+    items.swap(i - 1, j);
+    items[i..].reverse();
+    true
+}
+
+// Golly .rule file for a rule name: "<name>.rule" in one of `dirs` (exact name first, then ignoring case)
+pub fn find_rule_file(name: &str, dirs: &[PathBuf]) -> Option<PathBuf> {
+    let file_name = format!("{}.rule", name);
+    for dir in dirs {
+        let path = dir.join(&file_name);
+        if path.is_file() {
+            return Some(path);
+        }
+        let Ok(entries) = fs::read_dir(dir) else {
+            continue;
+        };
+        if let Some(path) = entries
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .find(|p| p.file_name().and_then(|f| f.to_str()).is_some_and(|f| f.eq_ignore_ascii_case(&file_name)))
+        {
+            return Some(path);
+        }
+    }
+    None
+}
+
+// Compute one step of a rule table on cell states (states_current -> states_next, row-major)
+// The topology is handled like in step_life(): cells across a plane's edge are in state 0
+pub fn step_table(states_current: &[u8], states_next: &mut [u8], buffer_width: u32, buffer_height: u32, rule: &Rule) {
+    step_table_parallel(states_current, states_next, buffer_width, buffer_height, rule, &Workers::single());
+}
+
+// Same as step_table() but the board is split into one horizontal band per worker, computed in parallel
+pub fn step_table_parallel(states_current: &[u8], states_next: &mut [u8], buffer_width: u32, buffer_height: u32, rule: &Rule, workers: &Workers) {
+    debug_assert_eq!(states_current.len(), states_next.len());
+    let Some(table) = rule.rule_table() else {
+        debug_assert!(false, "step_table() needs a rule table, got {}", rule);
+        return;
+    };
+    if buffer_width == 0 || buffer_height == 0 {
+        return;
+    }
+
+    let threads = workers.threads();
+    if threads <= 1 || buffer_height < 2 {
+        step_table_rows(states_current, states_next, buffer_width, buffer_height, rule, table, 0);
+        return;
+    }
+    let rows_per_band = (buffer_height as usize).div_ceil(threads);
+    workers.scope(|scope| {
+        for (i, band) in states_next.chunks_mut(rows_per_band * buffer_width as usize).enumerate() {
+            let first_row = (i * rows_per_band) as u32;
+            scope.spawn(move |_| step_table_rows(states_current, band, buffer_width, buffer_height, rule, table, first_row));
+        }
+    });
+}
+
+// Compute the rows [first_row, first_row + band rows) of the next generation into `band`
+fn step_table_rows(states_current: &[u8], band: &mut [u8], buffer_width: u32, buffer_height: u32, rule: &Rule, table: &RuleTable, first_row: u32) {
+    let topology = rule.topology();
+    let get = |x: isize, y: isize| -> u8 {
+        if x >= 0 && y >= 0 && (x as usize) < buffer_width as usize && (y as usize) < buffer_height as usize {
+            return states_current[y as usize * buffer_width as usize + x as usize];
+        }
+        match topology.map(x as i64, y as i64, buffer_width, buffer_height) {
+            Some((x, y)) => states_current[(y * buffer_width + x) as usize],
+            None => 0,
+        }
+    };
+
+    let ring = ring(table.neighbourhood());
+    let mut cells = [0u8; 9];
+    let cells = &mut cells[..ring.len() + 1];
+    let band_height = (band.len() / buffer_width as usize) as u32;
+    for y in first_row..first_row + band_height {
+        for x in 0..buffer_width {
+            let idx = (y * buffer_width + x) as usize;
+            cells[0] = states_current[idx];
+            for (cell, (dx, dy)) in cells[1..].iter_mut().zip(ring) {
+                *cell = get(x as isize + dx, y as isize + dy);
+            }
+            band[idx - (first_row * buffer_width) as usize] = if cells.iter().all(|&s| s == 0) { table.quiescent } else { table.next_state(cells) };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules_dir() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("../rules")
+    }
+
+    #[test]
+    fn wireworld_table() {
+        let table = RuleTable::load(&rules_dir().join("WireWorld.rule")).unwrap();
+        assert_eq!((table.name(), table.states(), table.neighbourhood()), ("WireWorld", 4, Neighbourhood::Moore));
+        // permute: 1 + 1 + 8 + 28 distinct orderings
+        assert_eq!(table.transition_count(), 38);
+        assert_eq!(table.colors()[3], Some([255, 128, 0]));
+
+        // head -> tail -> wire, wire -> head next to 1 or 2 heads (anywhere around it), not 3
+        assert_eq!(table.next_state(&[1, 0, 0, 0, 0, 0, 0, 0, 0]), 2);
+        assert_eq!(table.next_state(&[2, 3, 3, 0, 0, 0, 0, 0, 0]), 3);
+        assert_eq!(table.next_state(&[3, 0, 0, 0, 0, 0, 1, 0, 0]), 1);
+        assert_eq!(table.next_state(&[3, 1, 0, 2, 0, 3, 0, 1, 0]), 1);
+        assert_eq!(table.next_state(&[3, 1, 1, 0, 1, 0, 0, 0, 0]), 3);
+        assert_eq!(table.next_state(&[0, 1, 1, 0, 0, 0, 0, 0, 0]), 0);
+    }
+
+    #[test]
+    fn symmetries_and_bound_variables() {
+        // von Neumann, rotate4: a cell with a single live neighbour on any side copies it
+        let text = "@RULE Copy\n@TABLE\nn_states:3\nneighborhood:vonNeumann\nsymmetries:rotate4\nvar a={1,2}\nvar b={0}\n0,a,b,b,b,a\n";
+        let table = RuleTable::parse(text).unwrap();
+        for (i, side) in [[2, 0, 0, 0], [0, 2, 0, 0], [0, 0, 2, 0], [0, 0, 0, 2]].iter().enumerate() {
+            let cells: Vec<u8> = std::iter::once(0).chain(side.iter().copied()).collect();
+            assert_eq!(table.next_state(&cells), 2, "side {i}");
+        }
+        assert_eq!(table.next_state(&[0, 1, 1, 0, 0]), 0); // two live sides: no copy matches, the cell keeps its state
+
+        // none: only the side written down
+        let table = RuleTable::parse(&text.replace("rotate4", "none")).unwrap();
+        assert_eq!((table.next_state(&[0, 1, 0, 0, 0]), table.next_state(&[0, 0, 1, 0, 0])), (1, 0));
+        // reflect mirrors E and W (the ring around N)
+        let table = RuleTable::parse(&text.replace("rotate4", "reflect").replace("0,a,b,b,b,a", "0,b,a,b,b,a")).unwrap();
+        assert_eq!((table.next_state(&[0, 0, 1, 0, 0]), table.next_state(&[0, 0, 0, 0, 1]), table.next_state(&[0, 0, 0, 1, 0])), (1, 1, 0));
+
+        // compact form and errors
+        assert_eq!(
+            RuleTable::parse("@RULE C\n@TABLE\nn_states:2\nneighborhood:vonNeumann\n011111\n").unwrap().next_state(&[0, 1, 1, 1, 1]),
+            1
+        );
+        assert!(RuleTable::parse("@RULE E\n@TABLE\nn_states:2\nneighborhood:vonNeumann\n0,1,1,1,1,2\n").is_err());
+        assert!(RuleTable::parse("@RULE E\n@TABLE\nn_states:2\nneighborhood:vonNeumann\nvar a={0,1}\n0,a,1,1,1,b\n").is_err());
+        assert!(RuleTable::parse("@RULE E\n@TABLE\nn_states:2\nneighborhood:vonNeumann\nsymmetries:rotate8\n0,1,1,1,1,1\n").is_err());
+        assert!(RuleTable::parse("@RULE E\n@TREE\nnum_states=2\n").is_err());
+    }
+
+    #[test]
+    fn permute_matches_any_order() {
+        let text = "@RULE P\n@TABLE\nn_states:3\nneighborhood:Moore\nsymmetries:permute\n0,1,1,2,0,0,0,0,0,1\n";
+        let table = RuleTable::parse(text).unwrap();
+        assert_eq!(table.transition_count(), 8 * 7 / 2 * 6); // positions of the two 1s, then of the 2
+        assert_eq!(table.next_state(&[0, 0, 2, 0, 1, 0, 0, 0, 1]), 1);
+        assert_eq!(table.next_state(&[0, 0, 2, 0, 1, 0, 0, 0, 0]), 0);
+    }
+
+    #[test]
+    fn wireworld_clock_sends_electrons_down_its_wire() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../rle/wireworld-clock.rle");
+        let (cells, w, h, rule) = crate::gol::utils::read_rle_states(&path).unwrap();
+        assert_eq!(rule.to_string(), "WireWorld");
+
+        let (bw, bh) = (w + 4, h + 4);
+        let mut current = vec![0u8; (bw * bh) as usize];
+        crate::gol::utils::place_pattern_centered(&mut current, bw, bh, &cells, w, h);
+        let mut next = vec![0u8; current.len()];
+        let conductors = current.iter().filter(|&&s| s != 0).count();
+        // Last cell of the wire (pattern cell (14, 1), centred with a 2 cell margin)
+        let wire_end = (3 * bw + 16) as usize;
+        assert_eq!(current[wire_end], 3);
+        let mut heads_at_wire_end = 0;
+        let workers = Workers::new(2).unwrap();
+        for _ in 0..60 {
+            step_table_parallel(&current, &mut next, bw, bh, &rule, &workers);
+            std::mem::swap(&mut current, &mut next);
+            // Electrons move along the wire, which neither grows nor shrinks
+            assert_eq!(current.iter().filter(|&&s| s != 0).count(), conductors);
+            heads_at_wire_end += (current[wire_end] == 1) as usize;
+        }
+        assert!(heads_at_wire_end >= 4, "{heads_at_wire_end} electrons reached the end of the wire");
+    }
+
+    #[test]
+    fn langtons_loop_reproduces() {
+        let table = RuleTable::load(&rules_dir().join("Langtons-Loops.rule")).unwrap();
+        assert_eq!((table.name(), table.states(), table.neighbourhood()), ("Langtons-Loops", 8, Neighbourhood::VonNeumann));
+        // Bound variable: a core cell takes the signal coming from its west neighbour, whatever side it is on
+        assert_eq!((table.next_state(&[1, 0, 0, 2, 7]), table.next_state(&[1, 7, 0, 0, 2])), (7, 7));
+
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../rle/langtons-loop.rle");
+        let (cells, w, h, rule) = crate::gol::utils::read_rle_states(&path).unwrap();
+        assert_eq!((w, h, cells.iter().filter(|&&s| s != 0).count()), (15, 10, 86));
+
+        let (bw, bh, x0, y0) = (60, 40, 30, 15);
+        let mut current = vec![0u8; (bw * bh) as usize];
+        crate::gol::utils::place_pattern_at(&mut current, bw, bh, &cells, w, h, x0, y0);
+        let mut next = vec![0u8; current.len()];
+        for _ in 0..151 {
+            step_table(&current, &mut next, bw, bh, &rule);
+            std::mem::swap(&mut current, &mut next);
+        }
+        assert_eq!(current.iter().filter(|&&s| s != 0).count(), 171);
+        // 151 generations later the loop is back, 11 cells to the right, with its daughter on its left
+        for y in 0..h {
+            for x in 0..w {
+                let board = ((y0 as u32 + y) * bw + x0 as u32 + 11 + x) as usize;
+                assert_eq!(current[board], cells[(y * w + x) as usize], "({x}, {y})");
+            }
+        }
+    }
+}
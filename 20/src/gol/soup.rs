@@ -0,0 +1,139 @@
+// gol/soup.rs
+
+use crate::Result;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::fmt;
+use std::str::FromStr;
+
+// Random soups, reproducible from their seed: the same seed, size, density and symmetry give the same cells
+// (with this version of the `rand` crate, StdRng is not promised to stay the same across its versions).
+// A symmetric soup draws one value per orbit of the symmetry group: every cell takes the value drawn for the
+// first cell of its orbit in row order.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Symmetry {
+    C1, // none
+    C2, // 180 degree rotation
+    C4, // 90 degree rotations (square region)
+    D2, // mirror left <-> right
+    D4, // mirrors left <-> right and top <-> bottom
+    D8, // rotations and mirrors of the square (square region)
+}
+
+impl Symmetry {
+    pub const ALL: [Symmetry; 6] = [Symmetry::C1, Symmetry::C2, Symmetry::C4, Symmetry::D2, Symmetry::D4, Symmetry::D8];
+
+    pub fn needs_square(&self) -> bool {
+        matches!(self, Symmetry::C4 | Symmetry::D8)
+    }
+
+    // Cells `(x, y)` goes to under the group, itself included
+    fn orbit(&self, x: u32, y: u32, width: u32, height: u32) -> Vec<(u32, u32)> {
+        let (mx, my) = (width - 1 - x, height - 1 - y);
+        match self {
+            Symmetry::C1 => vec![(x, y)],
+            Symmetry::C2 => vec![(x, y), (mx, my)],
+            Symmetry::C4 => vec![(x, y), (my, x), (mx, my), (y, mx)],
+            Symmetry::D2 => vec![(x, y), (mx, y)],
+            Symmetry::D4 => vec![(x, y), (mx, y), (x, my), (mx, my)],
+            Symmetry::D8 => vec![(x, y), (my, x), (mx, my), (y, mx), (mx, y), (x, my), (y, x), (my, mx)],
+        }
+    }
+}
+
+impl fmt::Display for Symmetry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl FromStr for Symmetry {
+    type Err = crate::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Symmetry::ALL
+            .into_iter()
+            .find(|symmetry| symmetry.to_string().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("Unknown symmetry {:?} (C1, C2, C4, D2, D4 or D8)", s).into())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Soup {
+    pub density: f64, // Probability of a live cell, 0.0 to 1.0
+    pub symmetry: Symmetry,
+    pub seed: u64,
+}
+
+impl Soup {
+    // width x height cells, row by row
+    pub fn generate(&self, width: u32, height: u32) -> Result<Vec<bool>> {
+        if self.symmetry.needs_square() && width != height {
+            return Err(format!("{} symmetry needs a square region, not {}x{}", self.symmetry, width, height).into());
+        }
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let density = self.density.clamp(0.0, 1.0);
+        let mut cells = vec![false; width as usize * height as usize];
+        for y in 0..height {
+            for x in 0..width {
+                // Cells of an orbit drawn earlier already have their value
+                let first = self.symmetry.orbit(x, y, width, height).into_iter().min_by_key(|&(ox, oy)| (oy, ox)).unwrap_or((x, y));
+                cells[y as usize * width as usize + x as usize] = if first == (x, y) {
+                    rng.random_bool(density)
+                } else {
+                    cells[first.1 as usize * width as usize + first.0 as usize]
+                };
+            }
+        }
+        Ok(cells)
+    }
+}
+
+impl fmt::Display for Soup {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "density {}, symmetry {}, seed {}", self.density, self.symmetry, self.seed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_same_soup() {
+        let soup = Soup {
+            density: 0.5,
+            symmetry: Symmetry::C1,
+            seed: 42,
+        };
+        let cells = soup.generate(16, 16).unwrap();
+        assert_eq!(cells, soup.generate(16, 16).unwrap());
+        assert_ne!(cells, Soup { seed: 43, ..soup }.generate(16, 16).unwrap());
+        let population = cells.iter().filter(|&&alive| alive).count();
+        assert!((64..192).contains(&population), "{} live cells for a density of 0.5", population);
+
+        assert!(Soup { density: 0.0, ..soup }.generate(8, 8).unwrap().iter().all(|&alive| !alive));
+        assert!(Soup { density: 1.0, ..soup }.generate(8, 8).unwrap().iter().all(|&alive| alive));
+    }
+
+    #[test]
+    fn symmetries_hold() {
+        let (width, height) = (9, 9);
+        for symmetry in Symmetry::ALL {
+            let cells = Soup { density: 0.5, symmetry, seed: 7 }.generate(width, height).unwrap();
+            for y in 0..height {
+                for x in 0..width {
+                    for (ox, oy) in symmetry.orbit(x, y, width, height) {
+                        assert_eq!(cells[(y * width + x) as usize], cells[(oy * width + ox) as usize], "{} at ({}, {})", symmetry, x, y);
+                    }
+                }
+            }
+        }
+        let soup = |symmetry| Soup { density: 0.5, symmetry, seed: 7 };
+        assert!(soup(Symmetry::C4).generate(8, 6).is_err());
+        assert!(soup(Symmetry::D4).generate(8, 6).is_ok());
+        assert_eq!("d8".parse::<Symmetry>().unwrap(), Symmetry::D8);
+        assert!("C3".parse::<Symmetry>().is_err());
+    }
+}
@@ -0,0 +1,395 @@
+// gol/sparse.rs
+
+use crate::gol::bitlife::{Transition, full_add};
+use crate::gol::rule::Rule;
+use crate::gol::tally::{Frame, Tally};
+use crate::gol::topology::Topology;
+use crate::gol::workers::Workers;
+use std::collections::{HashMap, HashSet};
+
+// Unbounded universe made of 64x64 tiles allocated on demand around live cells
+// - Coordinates are signed 64-bit, tile (tx, ty) covers cells [64 tx, 64 tx + 64) x [64 ty, 64 ty + 64)
+// - A tile is 64 rows of one u64 (cell x of a row is bit x), stepped with the same bitwise adders as BitBoard
+// - Only tiles holding live cells are stored, a step visits them and the neighbours their edges touch
+pub struct SparseBoard {
+    tiles: HashMap<(i64, i64), Tile>,
+    generation: u64,
+    tally: Tally, // births and deaths counted on the stepped tiles
+}
+
+const TILE_SHIFT: u32 = 6;
+const TILE_SIZE: i64 = 1 << TILE_SHIFT;
+const TILE_MASK: i64 = TILE_SIZE - 1;
+
+type Tile = [u64; TILE_SIZE as usize];
+
+impl SparseBoard {
+    pub fn new() -> Self {
+        Self {
+            tiles: HashMap::new(),
+            generation: 0,
+            tally: Tally::default(),
+        }
+    }
+
+    // The universe has no edge: the empty space has to stay empty (no B0) and a bounded grid makes no sense
+    // Tiles hold one bit per cell and are stepped by counting the 8 immediate neighbours: Life-like B/S rules only
+    pub fn supports(rule: &Rule) -> bool {
+        rule.is_moore_totalistic() && !rule.is_birth(0) && rule.topology() == Topology::plane()
+    }
+
+    pub fn clear(&mut self) {
+        self.tiles.clear();
+        self.generation = 0;
+        self.tally = Tally::default();
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub fn population(&self) -> u64 {
+        self.tiles.values().flat_map(|tile| tile.iter()).map(|row| row.count_ones() as u64).sum()
+    }
+
+    pub fn tally(&self) -> Tally {
+        self.tally
+    }
+
+    pub fn tile_count(&self) -> usize {
+        self.tiles.len()
+    }
+
+    // Replace the universe with a dense row-major pattern whose top-left cell lands on (offset_x, offset_y)
+    pub fn load_cells(&mut self, cells: &[bool], width: u32, height: u32, offset_x: i64, offset_y: i64) {
+        debug_assert_eq!(cells.len(), (width * height) as usize);
+        self.clear();
+        if width == 0 {
+            return;
+        }
+        for (y, row) in cells.chunks_exact(width as usize).enumerate() {
+            for (x, &alive) in row.iter().enumerate() {
+                if alive {
+                    self.set_bit(offset_x + x as i64, offset_y + y as i64, true);
+                }
+            }
+        }
+        // Tile by tile, one power per row and column of a tile instead of two per cell
+        for (&key, tile) in &self.tiles {
+            let (left, top) = (key.0 << TILE_SHIFT, key.1 << TILE_SHIFT);
+            let frame = Frame::new(left, top, TILE_SIZE as u32, TILE_SIZE as u32);
+            for (y, &row) in tile.iter().enumerate() {
+                self.tally.change_word(&frame, left, top + y as i64, 0, row);
+            }
+        }
+        self.tally.start_step();
+    }
+
+    // Copy the universe window [x0, x0 + width) x [y0, y0 + height) into a dense row-major board
+    pub fn write_region(&self, out: &mut [bool], x0: i64, y0: i64, width: u32, height: u32) {
+        debug_assert_eq!(out.len(), (width * height) as usize);
+        out.fill(false);
+        let (x1, y1) = (x0 + width as i64, y0 + height as i64);
+        for (&(tx, ty), tile) in &self.tiles {
+            let (left, top) = (tx << TILE_SHIFT, ty << TILE_SHIFT);
+            if left >= x1 || top >= y1 || left + TILE_SIZE <= x0 || top + TILE_SIZE <= y0 {
+                continue;
+            }
+            for y in top.max(y0)..(top + TILE_SIZE).min(y1) {
+                let row = tile[(y - top) as usize];
+                if row == 0 {
+                    continue;
+                }
+                let line = &mut out[((y - y0) * width as i64) as usize..][..width as usize];
+                for x in left.max(x0)..(left + TILE_SIZE).min(x1) {
+                    line[(x - x0) as usize] = (row >> (x - left)) & 1 == 1;
+                }
+            }
+        }
+    }
+
+    pub fn get_cell(&self, x: i64, y: i64) -> bool {
+        match self.tiles.get(&(x >> TILE_SHIFT, y >> TILE_SHIFT)) {
+            Some(tile) => (tile[(y & TILE_MASK) as usize] >> (x & TILE_MASK)) & 1 == 1,
+            None => false,
+        }
+    }
+
+    pub fn set_cell(&mut self, x: i64, y: i64, alive: bool) {
+        self.tally.change_at(x, y, self.get_cell(x, y) as u8, alive as u8);
+        self.set_bit(x, y, alive);
+    }
+
+    // set_cell() without the tally
+    fn set_bit(&mut self, x: i64, y: i64, alive: bool) {
+        let key = (x >> TILE_SHIFT, y >> TILE_SHIFT);
+        let bit = 1u64 << (x & TILE_MASK);
+        if alive {
+            self.tiles.entry(key).or_insert([0; TILE_SIZE as usize])[(y & TILE_MASK) as usize] |= bit;
+        } else if let Some(tile) = self.tiles.get_mut(&key) {
+            tile[(y & TILE_MASK) as usize] &= !bit;
+            if tile.iter().all(|&row| row == 0) {
+                self.tiles.remove(&key);
+            }
+        }
+    }
+
+    // Coordinates of every live cell (no particular order)
+    pub fn live_cells(&self) -> Vec<(i64, i64)> {
+        let mut cells = Vec::new();
+        for (&(tx, ty), tile) in &self.tiles {
+            for (y, &row) in tile.iter().enumerate() {
+                let mut bits = row;
+                while bits != 0 {
+                    let x = bits.trailing_zeros() as i64;
+                    cells.push(((tx << TILE_SHIFT) + x, (ty << TILE_SHIFT) + y as i64));
+                    bits &= bits - 1;
+                }
+            }
+        }
+        cells
+    }
+
+    // (min_x, min_y, max_x, max_y) of the live cells, inclusive, None when the universe is empty
+    pub fn bounding_box(&self) -> Option<(i64, i64, i64, i64)> {
+        let mut result: Option<(i64, i64, i64, i64)> = None;
+        for (&(tx, ty), tile) in &self.tiles {
+            let columns = tile.iter().fold(0u64, |acc, &row| acc | row);
+            let (Some(first), Some(last)) = (tile.iter().position(|&row| row != 0), tile.iter().rposition(|&row| row != 0)) else {
+                continue;
+            };
+            let (left, top) = (tx << TILE_SHIFT, ty << TILE_SHIFT);
+            let b = (
+                left + columns.trailing_zeros() as i64,
+                top + first as i64,
+                left + 63 - columns.leading_zeros() as i64,
+                top + last as i64,
+            );
+            result = Some(match result {
+                None => b,
+                Some(r) => (r.0.min(b.0), r.1.min(b.1), r.2.max(b.2), r.3.max(b.3)),
+            });
+        }
+        result
+    }
+
+    // Compute one generation of an unbounded plane
+    pub fn step(&mut self, rule: &Rule) {
+        self.step_parallel(rule, &Workers::single());
+    }
+
+    // Same as step() with the active tiles split into one group per worker, computed in parallel
+    pub fn step_parallel(&mut self, rule: &Rule, workers: &Workers) {
+        debug_assert!(Self::supports(rule));
+        let transition = Transition::new(rule);
+        let active = self.active_tiles();
+        let threads = workers.threads();
+
+        let next: HashMap<(i64, i64), Tile> = if threads <= 1 || active.len() < 2 * threads {
+            active.iter().filter_map(|&key| self.step_tile(key, &transition).map(|tile| (key, tile))).collect()
+        } else {
+            let per_thread = active.len().div_ceil(threads);
+            let (tiles, transition) = (&*self, &transition);
+            let mut groups: Vec<Vec<((i64, i64), Tile)>> = vec![Vec::new(); active.len().div_ceil(per_thread)];
+            workers.scope(|scope| {
+                for (keys, group) in active.chunks(per_thread).zip(groups.iter_mut()) {
+                    scope.spawn(move |_| *group = keys.iter().filter_map(|&key| tiles.step_tile(key, transition).map(|tile| (key, tile))).collect());
+                }
+            });
+            groups.into_iter().flatten().collect()
+        };
+
+        self.tally.start_step();
+        for key in &active {
+            let (before, after) = (self.tiles.get(key).unwrap_or(&[0; TILE_SIZE as usize]), next.get(key).unwrap_or(&[0; TILE_SIZE as usize]));
+            if before == after {
+                continue;
+            }
+            let (left, top) = (key.0 << TILE_SHIFT, key.1 << TILE_SHIFT);
+            let frame = Frame::new(left, top, TILE_SIZE as u32, TILE_SIZE as u32);
+            for (y, (&before, &after)) in before.iter().zip(after).enumerate() {
+                self.tally.change_word(&frame, left, top + y as i64, before, after);
+            }
+        }
+
+        self.tiles = next;
+        self.generation += 1;
+    }
+
+    // Tiles that may hold live cells next generation: every live tile, and the neighbours its border cells touch
+    fn active_tiles(&self) -> Vec<(i64, i64)> {
+        let mut active: HashSet<(i64, i64)> = HashSet::with_capacity(self.tiles.len() * 2);
+        for (&(tx, ty), tile) in &self.tiles {
+            let columns = tile.iter().fold(0u64, |acc, &row| acc | row);
+            let (top, bottom) = (tile[0], tile[TILE_SIZE as usize - 1]);
+            let (west, east) = (columns & 1 != 0, columns >> 63 != 0);
+            let (north, south) = (top != 0, bottom != 0);
+
+            active.insert((tx, ty));
+            for (dx, dy, touched) in [
+                (-1, 0, west),
+                (1, 0, east),
+                (0, -1, north),
+                (0, 1, south),
+                (-1, -1, top & 1 != 0),
+                (1, -1, top >> 63 != 0),
+                (-1, 1, bottom & 1 != 0),
+                (1, 1, bottom >> 63 != 0),
+            ] {
+                if touched {
+                    active.insert((tx + dx, ty + dy));
+                }
+            }
+        }
+        active.into_iter().collect()
+    }
+
+    // Next generation of one tile, None when it dies out
+    fn step_tile(&self, (tx, ty): (i64, i64), transition: &Transition) -> Option<Tile> {
+        const LAST: usize = TILE_SIZE as usize - 1;
+        let tile = |dx: i64, dy: i64| self.tiles.get(&(tx + dx, ty + dy));
+        let centre = tile(0, 0);
+        let (north, south, west, east) = (tile(0, -1), tile(0, 1), tile(-1, 0), tile(1, 0));
+        let (north_west, north_east, south_west, south_east) = (tile(-1, -1), tile(1, -1), tile(-1, 1), tile(1, 1));
+
+        // Row r of the 3x3 neighbourhood (r in -1..=64) with the cell just left and just right of it
+        let row = |r: isize| -> (u64, u64, u64) {
+            let (west_tile, middle, east_tile, y) = match r {
+                -1 => (north_west, north, north_east, LAST),
+                64 => (south_west, south, south_east, 0),
+                _ => (west, centre, east, r as usize),
+            };
+            let word = middle.map_or(0, |t| t[y]);
+            let left = west_tile.map_or(0, |t| t[y] >> 63);
+            let right = east_tile.map_or(0, |t| t[y] & 1);
+            ((word << 1) | left, word, (word >> 1) | (right << 63))
+        };
+
+        let mut next: Tile = [0; TILE_SIZE as usize];
+        let mut any = 0u64;
+        let (mut above, mut current) = (row(-1), row(0));
+        for (y, next_row) in next.iter_mut().enumerate() {
+            let below = row(y as isize + 1);
+            let ((a_w, a, a_e), (c_w, c, c_e), (b_w, b, b_e)) = (above, current, below);
+
+            // Carry-save adder tree: 8 one-bit inputs -> 4-bit count (s3 s2 s1 s0)
+            let (sa, ca) = full_add(a_w, a, a_e);
+            let (sb, cb) = full_add(b_w, b, b_e);
+            let (sc, cc) = (c_w ^ c_e, c_w & c_e);
+            let (s0, c2) = full_add(sa, sb, sc);
+            let (t, c4a) = full_add(ca, cb, cc);
+            let (s1, c4b) = (t ^ c2, t & c2);
+            let (s2, s3) = (c4a ^ c4b, c4a & c4b);
+
+            *next_row = transition.apply(c, [s0, s1, s2, s3]);
+            any |= *next_row;
+            (above, current) = (current, below);
+        }
+        (any != 0).then_some(next)
+    }
+}
+
+impl Default for SparseBoard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gol::hashlife::HashLife;
+    use crate::gol::utils::read_rle;
+
+    #[test]
+    fn glider_crosses_tiles_and_negative_coordinates() {
+        let mut board = SparseBoard::new();
+        // Glider heading north-west, starting just right of the origin tile's corner
+        for (x, y) in [(1, 0), (2, 0), (0, 1), (1, 1), (2, 2)] {
+            board.set_cell(x + 2, y + 2, true);
+        }
+        let rule = Rule::life();
+        for _ in 0..400 {
+            board.step(&rule);
+        }
+        // c/4 diagonal: 400 generations move it 100 cells up and left
+        assert_eq!(board.population(), 5);
+        assert_eq!(board.bounding_box(), Some((-98, -98, -96, -96)));
+        assert!(board.tile_count() <= 4);
+        assert_eq!(board.generation(), 400);
+    }
+
+    #[test]
+    fn set_get_and_write_region() {
+        let mut board = SparseBoard::new();
+        board.set_cell(-1, -1, true);
+        board.set_cell(63, 64, true);
+        board.set_cell(1 << 40, -(1 << 40), true);
+        assert!(board.get_cell(-1, -1) && board.get_cell(63, 64) && board.get_cell(1 << 40, -(1 << 40)));
+        assert!(!board.get_cell(0, 0));
+
+        let mut out = vec![false; 3 * 3];
+        board.write_region(&mut out, -2, -2, 3, 3);
+        assert_eq!(out.iter().filter(|&&c| c).count(), 1);
+        assert!(out[4]);
+
+        board.set_cell(-1, -1, false);
+        assert_eq!(board.population(), 2);
+        assert_eq!(board.tile_count(), 2);
+        assert_eq!(board.live_cells().len(), 2);
+    }
+
+    // Both engines are unbounded, so they must agree on every pattern (no wall effects)
+    #[test]
+    fn same_generations_as_hashlife() {
+        let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../rle");
+        for entry in std::fs::read_dir(&dir).expect("rle/ directory") {
+            let path = entry.unwrap().path();
+            if path.extension().and_then(|e| e.to_str()) != Some("rle") {
+                continue;
+            }
+            let (cells, width, height, rule) = read_rle(&path).unwrap();
+            if !rule.is_moore_totalistic() {
+                continue; // rule tables like WireWorld only run on the dense engine
+            }
+            let mut sparse = SparseBoard::new();
+            sparse.load_cells(&cells, width, height, -(width as i64) / 2, -(height as i64) / 2);
+            let mut hashlife = HashLife::new(rule.clone());
+            hashlife.load_cells(&cells, width, height, -(width as i64) / 2, -(height as i64) / 2);
+
+            for _ in 0..32 {
+                sparse.step(&rule);
+            }
+            hashlife.step_pow2(&rule, 5);
+
+            let bbox = sparse.bounding_box();
+            assert_eq!(bbox, hashlife.bounding_box(), "{}", path.display());
+            if let Some((x0, y0, x1, y1)) = bbox {
+                let (w, h) = ((x1 - x0 + 1) as u32, (y1 - y0 + 1) as u32);
+                let (mut a, mut b) = (vec![false; (w * h) as usize], vec![false; (w * h) as usize]);
+                sparse.write_region(&mut a, x0, y0, w, h);
+                hashlife.write_region(&mut b, x0, y0, w, h);
+                assert!(a == b, "{}: engines differ", path.display());
+            }
+        }
+    }
+
+    #[test]
+    fn parallel_step_matches_single_thread() {
+        let (cells, width, height, rule) = read_rle(&std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../rle/gosperglidergun.rle")).unwrap();
+        let mut single = SparseBoard::new();
+        single.load_cells(&cells, width, height, 0, 0);
+        let mut parallel = SparseBoard::new();
+        parallel.load_cells(&cells, width, height, 0, 0);
+        let workers = Workers::new(3).unwrap();
+        for _ in 0..300 {
+            single.step(&rule);
+            parallel.step_parallel(&rule, &workers);
+        }
+        let mut a = single.live_cells();
+        let mut b = parallel.live_cells();
+        a.sort();
+        b.sort();
+        assert_eq!(a, b);
+    }
+}
@@ -0,0 +1,206 @@
+// gol/stats.rs
+
+use crate::Result;
+use crate::gol::tally::Tally;
+use std::collections::VecDeque;
+use std::io::Write;
+use std::path::Path;
+
+// Population statistics of the last generations (rolling, the oldest rows are dropped beyond `capacity`)
+// Every stepped generation gets a row from the tally its engine kept while stepping (see gol::tally): births and
+// deaths over one generation usually, net changes over 2^k of them when HashLife steps that many at once, `span`
+// tells how many. They are None for a generation that was not stepped to (pattern loaded, first generation).
+// The bounding box takes a scan of the board, it is only known for the generations shown (see set_bounding_box()).
+// A cell counts as alive in state 1 only: dying states of Generations rules are not population.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GenerationStats {
+    pub generation: u64,
+    pub population: u64,
+    pub births: Option<u64>,
+    pub deaths: Option<u64>,
+    pub span: Option<u64>,                          // generations the step advanced, births and deaths are net changes over them
+    pub bounding_box: Option<(i64, i64, i64, i64)>, // (min_x, min_y, max_x, max_y) of the live cells, inclusive
+}
+
+pub struct Statistics {
+    rows: VecDeque<GenerationStats>,
+    capacity: usize,
+}
+
+impl Statistics {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            rows: VecDeque::new(),
+            capacity: capacity.max(1),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.rows.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    pub fn latest(&self) -> Option<&GenerationStats> {
+        self.rows.back()
+    }
+
+    // Oldest first
+    pub fn rows(&self) -> impl DoubleEndedIterator<Item = &GenerationStats> + ExactSizeIterator {
+        self.rows.iter()
+    }
+
+    // Record a generation that was not stepped to (pattern loaded, first generation): births and deaths are unknown
+    pub fn record_start(&mut self, generation: u64, population: u64, bounding_box: Option<(i64, i64, i64, i64)>) {
+        self.push(GenerationStats {
+            generation,
+            population,
+            births: None,
+            deaths: None,
+            span: None,
+            bounding_box,
+        });
+    }
+
+    // Record a stepped generation from the tally of the engine, `span` generations after the one before the step
+    pub fn record_step(&mut self, generation: u64, tally: &Tally, span: u64) {
+        self.push(GenerationStats {
+            generation,
+            population: tally.population,
+            births: Some(tally.births),
+            deaths: Some(tally.deaths),
+            span: Some(span),
+            bounding_box: None,
+        });
+    }
+
+    // Bounding box of the latest generation, once it is shown
+    pub fn set_bounding_box(&mut self, generation: u64, bounding_box: Option<(i64, i64, i64, i64)>) {
+        if let Some(row) = self.rows.back_mut().filter(|row| row.generation == generation) {
+            row.bounding_box = bounding_box;
+        }
+    }
+
+    // Forget the generations after `generation` (the history went back)
+    pub fn truncate(&mut self, generation: u64) {
+        while self.rows.back().is_some_and(|row| row.generation > generation) {
+            self.rows.pop_back();
+        }
+    }
+
+    fn push(&mut self, row: GenerationStats) {
+        if self.rows.len() == self.capacity {
+            self.rows.pop_front();
+        }
+        self.rows.push_back(row);
+    }
+
+    // One line per recorded generation, empty fields for the unknown values
+    // births_deaths_span says how many generations births and deaths are net changes over
+    pub fn write_csv(&self, mut out: impl Write) -> std::io::Result<()> {
+        writeln!(out, "generation,population,births,deaths,births_deaths_span,min_x,min_y,max_x,max_y")?;
+        let field = |value: Option<i128>| value.map(|v| v.to_string()).unwrap_or_default();
+        for row in &self.rows {
+            let bbox = row.bounding_box;
+            writeln!(
+                out,
+                "{},{},{},{},{},{},{},{},{}",
+                row.generation,
+                row.population,
+                field(row.births.map(i128::from)),
+                field(row.deaths.map(i128::from)),
+                field(row.span.map(i128::from)),
+                field(bbox.map(|b| b.0 as i128)),
+                field(bbox.map(|b| b.1 as i128)),
+                field(bbox.map(|b| b.2 as i128)),
+                field(bbox.map(|b| b.3 as i128)),
+            )?;
+        }
+        Ok(())
+    }
+
+    pub fn save_csv(&self, path: &Path) -> Result<()> {
+        let file = std::fs::File::create(path).map_err(|e| -> crate::Error { format!("Cannot create {}: {}", path.display(), e).into() })?;
+        self.write_csv(std::io::BufWriter::new(file))
+            .map_err(|e| -> crate::Error { format!("Cannot write {}: {}", path.display(), e).into() })?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gol::{rule::Rule, sparse::SparseBoard};
+
+    #[test]
+    fn rows_from_the_engine_tally() {
+        // A blinker: 2 cells born and 2 dead every generation
+        let rule = Rule::life();
+        let mut board = SparseBoard::new();
+        [(0, 1), (1, 1), (2, 1)].into_iter().for_each(|(x, y)| board.set_cell(x, y, true));
+        let mut stats = Statistics::new(100);
+        stats.record_start(0, board.population(), board.bounding_box());
+        board.step(&rule);
+        stats.record_step(1, &board.tally(), 1);
+        stats.set_bounding_box(1, board.bounding_box());
+        board.step(&rule);
+        stats.record_step(2, &board.tally(), 1);
+        // Only the latest generation gets its bounding box
+        stats.set_bounding_box(1, Some((0, 0, 0, 0)));
+
+        let rows: Vec<&GenerationStats> = stats.rows().collect();
+        assert_eq!((rows[0].births, rows[0].deaths, rows[0].bounding_box), (None, None, Some((0, 1, 2, 1))));
+        assert_eq!(
+            *rows[1],
+            GenerationStats {
+                generation: 1,
+                population: 3,
+                births: Some(2),
+                deaths: Some(2),
+                span: Some(1),
+                bounding_box: Some((1, 0, 1, 2)),
+            }
+        );
+        assert_eq!((rows[2].population, rows[2].births, rows[2].bounding_box), (3, Some(2), None));
+
+        // HashLife steps: net changes over the 4 generations of the step
+        let mut tally = Tally::default();
+        (tally.population, tally.births) = (4, 1);
+        stats.record_step(6, &tally, 4);
+        let latest = stats.latest().unwrap();
+        assert_eq!((latest.births, latest.deaths, latest.span), (Some(1), Some(0), Some(4)));
+    }
+
+    #[test]
+    fn rolling_window_and_csv() {
+        let mut stats = Statistics::new(3);
+        let mut tally = Tally::of_cell(0, -4);
+        (tally.births, tally.deaths) = (1, 1);
+        stats.record_start(0, 1, Some((0, -4, 0, -4)));
+        for generation in 1..5 {
+            stats.record_step(generation, &tally, 1);
+            stats.set_bounding_box(generation, Some((generation as i64, -4, generation as i64, -4)));
+        }
+        assert_eq!(stats.len(), 3);
+        assert_eq!(stats.rows().next().unwrap().generation, 2);
+
+        stats.truncate(3);
+        assert_eq!(stats.latest().unwrap().generation, 3);
+        stats.record_step(4, &Tally::default(), 1);
+
+        let mut csv = Vec::new();
+        stats.write_csv(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert_eq!(
+            csv,
+            "generation,population,births,deaths,births_deaths_span,min_x,min_y,max_x,max_y\n2,1,1,1,1,2,-4,2,-4\n3,1,1,1,1,3,-4,3,-4\n4,0,0,0,1,,,,\n"
+        );
+    }
+}
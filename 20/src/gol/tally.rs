@@ -0,0 +1,268 @@
+// gol/tally.rs
+
+// Population of a generation and what the last step changed, kept up to date by the engines from the cells a step
+// changes (or from the cells they step anyway), so statistics can be recorded every generation without scanning the board.
+// Births and deaths are counted since start_step(): over one generation, or over the 2^k generations of a HashLife step
+// (net changes then, a cell born and dead again in between is not counted).
+// A cell counts as alive in state 1 only: dying states of Generations rules are not population.
+//
+// The tally also keeps a hash of the cells for gol::period, updated the same way: every cell in a state s != 0 adds
+// s * A^x * B^y modulo the prime P = 2^61 - 1. Moving all the cells by (dx, dy) multiplies it by A^dx * B^dy, so
+// signature() moves them back by their centre (the mean of the coordinates of the cells in any state) and gives a
+// hash that does not depend on where the cells are. Two different generations with the same hash are taken for
+// the same (not checked, too unlikely to matter).
+
+const P: u64 = (1 << 61) - 1;
+const A: u64 = 0x0E37_79B9_7F4A_7C15;
+const B: u64 = 0x0BF5_8476_D1CE_4E5B;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Tally {
+    pub population: u64,
+    pub births: u64,
+    pub deaths: u64,
+    occupied: u64, // cells in any state but 0
+    sum_x: i128,   // of the coordinates of those cells
+    sum_y: i128,
+    hash: u64,
+}
+
+// A generation as gol::period compares them: its hash with the cells centred at (0, 0), and their centre
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Signature {
+    pub hash: u64,
+    pub x: i64,
+    pub y: i64,
+}
+
+// A^x and B^y for the columns and rows of a rectangle of the board, so the term of a cell costs one multiplication
+pub struct Frame {
+    left: i64,
+    top: i64,
+    columns: Vec<u64>,
+    rows: Vec<u64>,
+}
+
+impl Frame {
+    pub fn new(left: i64, top: i64, width: u32, height: u32) -> Self {
+        let powers = |base: u64, start: i64, count: u32| {
+            let mut power = pow(base, start);
+            (0..count)
+                .map(|_| {
+                    let current = power;
+                    power = mul(power, base);
+                    current
+                })
+                .collect()
+        };
+        Self {
+            left,
+            top,
+            columns: powers(A, left, width),
+            rows: powers(B, top, height),
+        }
+    }
+
+    fn term(&self, x: i64, y: i64) -> u64 {
+        mul(self.columns[(x - self.left) as usize], self.rows[(y - self.top) as usize])
+    }
+}
+
+impl Tally {
+    // Tally of a row-major board of cell states (bool or u8) covering `frame`, nothing born or dead yet
+    pub fn of_cells<T: Copy + Into<u8>>(cells: &[T], frame: &Frame) -> Self {
+        let mut tally = Self::default();
+        for (y, row) in cells.chunks_exact(frame.columns.len().max(1)).enumerate() {
+            for (x, &state) in row.iter().enumerate() {
+                let state = state.into();
+                if state != 0 {
+                    let (x, y) = (frame.left + x as i64, frame.top + y as i64);
+                    tally.apply(frame.term(x, y), x, y, 0, state);
+                }
+            }
+        }
+        tally.start_step();
+        tally
+    }
+
+    // Tally of the step `before` -> `after` of two boards of cell states (0 dead, 1 alive, 2.. dying) covering `frame`
+    pub fn of_step(before: &[u8], after: &[u8], frame: &Frame) -> Self {
+        debug_assert_eq!(before.len(), after.len());
+        let mut tally = Self::of_cells(after, frame);
+        for (&from, &to) in before.iter().zip(after) {
+            tally.births += (from != 1 && to == 1) as u64;
+            tally.deaths += (from == 1 && to != 1) as u64;
+        }
+        tally
+    }
+
+    // Tally of the cells of a HashLife node from the tallies of its 4 quadrants, `half` cells wide
+    pub fn of_quadrants(nw: &Tally, ne: &Tally, sw: &Tally, se: &Tally, half: i64) -> Self {
+        let mut tally = *nw;
+        for quadrant in [ne.moved(half, 0), sw.moved(0, half), se.moved(half, half)] {
+            tally.population += quadrant.population;
+            tally.occupied += quadrant.occupied;
+            tally.sum_x += quadrant.sum_x;
+            tally.sum_y += quadrant.sum_y;
+            tally.hash = add(tally.hash, quadrant.hash);
+        }
+        tally.start_step();
+        tally
+    }
+
+    // Tally of a single live cell at (x, y)
+    pub fn of_cell(x: i64, y: i64) -> Self {
+        let mut tally = Self::default();
+        tally.change_at(x, y, 0, 1);
+        tally.start_step();
+        tally
+    }
+
+    // The same cells moved by (dx, dy)
+    pub fn moved(&self, dx: i64, dy: i64) -> Self {
+        Self {
+            sum_x: self.sum_x + dx as i128 * self.occupied as i128,
+            sum_y: self.sum_y + dy as i128 * self.occupied as i128,
+            hash: mul(self.hash, mul(pow(A, dx), pow(B, dy))),
+            ..*self
+        }
+    }
+
+    // A step starts, its births and deaths are counted from now
+    pub fn start_step(&mut self) {
+        self.births = 0;
+        self.deaths = 0;
+    }
+
+    // The cell (x, y) of `frame` went from state `from` to state `to`
+    pub fn change(&mut self, frame: &Frame, x: i64, y: i64, from: u8, to: u8) {
+        if from != to {
+            self.apply(frame.term(x, y), x, y, from, to);
+        }
+    }
+
+    // The same for a cell anywhere, slower (edits of the sparse engines)
+    pub fn change_at(&mut self, x: i64, y: i64, from: u8, to: u8) {
+        if from != to {
+            self.apply(mul(pow(A, x), pow(B, y)), x, y, from, to);
+        }
+    }
+
+    // The 64 cells from (x, y) went from the bits of `before` to the bits of `after` (words of gol::bitlife and gol::sparse)
+    pub fn change_word(&mut self, frame: &Frame, x: i64, y: i64, before: u64, after: u64) {
+        let mut changed = before ^ after;
+        while changed != 0 {
+            let bit = changed.trailing_zeros();
+            let alive = ((after >> bit) & 1) as u8;
+            self.apply(frame.term(x + bit as i64, y), x + bit as i64, y, 1 - alive, alive);
+            changed &= changed - 1;
+        }
+    }
+
+    // None once all the cells are gone
+    pub fn signature(&self) -> Option<Signature> {
+        if self.occupied == 0 {
+            return None;
+        }
+        let (x, y) = (self.sum_x.div_euclid(self.occupied as i128) as i64, self.sum_y.div_euclid(self.occupied as i128) as i64);
+        Some(Signature { hash: self.moved(-x, -y).hash, x, y })
+    }
+
+    fn apply(&mut self, term: u64, x: i64, y: i64, from: u8, to: u8) {
+        if from != 1 && to == 1 {
+            self.population += 1;
+            self.births += 1;
+        } else if from == 1 && to != 1 {
+            self.population -= 1;
+            self.deaths += 1;
+        }
+        if from == 0 {
+            self.occupied += 1;
+            self.sum_x += x as i128;
+            self.sum_y += y as i128;
+        } else if to == 0 {
+            self.occupied -= 1;
+            self.sum_x -= x as i128;
+            self.sum_y -= y as i128;
+        }
+        self.hash = add(self.hash, P - mul(term, from as u64));
+        self.hash = add(self.hash, mul(term, to as u64));
+    }
+}
+
+fn add(a: u64, b: u64) -> u64 {
+    let sum = a + b;
+    if sum >= P { sum - P } else { sum }
+}
+
+fn mul(a: u64, b: u64) -> u64 {
+    let product = a as u128 * b as u128;
+    let folded = (product as u64 & P) + (product >> 61) as u64;
+    let folded = (folded & P) + (folded >> 61);
+    if folded >= P { folded - P } else { folded }
+}
+
+// base^exponent, a negative exponent is fine: base^(P - 1) = 1
+fn pow(base: u64, exponent: i64) -> u64 {
+    let mut exponent = exponent.rem_euclid(P as i64 - 1) as u64;
+    let (mut base, mut result) = (base, 1);
+    while exponent != 0 {
+        if exponent & 1 == 1 {
+            result = mul(result, base);
+        }
+        base = mul(base, base);
+        exponent >>= 1;
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn births_deaths_and_dying_states() {
+        // Generations rule: a cell starting to die (1 -> 2) is a death, a dying cell going on dying is nothing
+        let frame = Frame::new(0, 0, 3, 2);
+        let before = [0, 1, 1, 2, 3, 1];
+        let after = [1, 1, 2, 3, 0, 1];
+        let tally = Tally::of_step(&before, &after, &frame);
+        assert_eq!((tally.population, tally.births, tally.deaths), (3, 1, 1));
+
+        let mut tally = Tally::of_cells(&[true, false, true], &Frame::new(0, 0, 3, 1));
+        let frame = Frame::new(0, 0, 64, 1);
+        tally.change_word(&frame, 0, 0, 0b101, 0b110);
+        tally.change(&frame, 1, 0, 1, 0);
+        assert_eq!((tally.population, tally.births, tally.deaths), (1, 1, 2));
+        assert_eq!(tally.signature(), Tally::of_cell(2, 0).signature());
+    }
+
+    #[test]
+    fn signature_does_not_depend_on_position() {
+        let glider = [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+        let at = |dx: i64, dy: i64| {
+            let mut tally = Tally::default();
+            glider.iter().for_each(|&(x, y)| tally.change_at(x + dx, y + dy, 0, 1));
+            tally.signature().unwrap()
+        };
+        let (here, there) = (at(0, 0), at(-1000, 7));
+        assert_eq!(here.hash, there.hash);
+        assert_eq!((there.x - here.x, there.y - here.y), (-1000, 7));
+
+        // The same cells in another state, or one cell less, are something else
+        let mut dying = Tally::default();
+        glider.iter().for_each(|&(x, y)| dying.change_at(x, y, 0, 2));
+        assert_ne!(dying.signature().unwrap().hash, here.hash);
+        let mut tally = Tally::default();
+        glider[1..].iter().for_each(|&(x, y)| tally.change_at(x, y, 0, 1));
+        assert_ne!(tally.signature().unwrap().hash, here.hash);
+        assert_eq!(Tally::default().signature(), None);
+
+        // A node made of quadrants is the same as its cells
+        let quadrants = Tally::of_quadrants(&Tally::of_cell(0, 0), &Tally::of_cell(1, 1), &Tally::default(), &Tally::of_cell(0, 0), 4);
+        let mut cells = Tally::default();
+        [(0, 0), (5, 1), (4, 4)].into_iter().for_each(|(x, y)| cells.change_at(x, y, 0, 1));
+        cells.start_step();
+        assert_eq!(quadrants, cells);
+    }
+}
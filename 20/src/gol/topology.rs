@@ -0,0 +1,318 @@
+// gol/topology.rs
+
+use crate::Result;
+use std::fmt;
+
+// How the edges of a bounded grid are joined (Golly "bounded grids")
+// - Plane        : nothing is joined, cells outside the board are dead (the historical behaviour)
+// - Torus        : left/right and top/bottom edges are joined, optionally with a shift
+// - KleinBottle  : one pair of edges is joined normally, the other with a twist (reversed)
+// - CrossSurface : both pairs of edges are joined with a twist
+// - Sphere       : top edge is joined to the left edge, bottom edge to the right edge (square grids only)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TopologyKind {
+    Plane,
+    Torus,
+    KleinBottle,
+    CrossSurface,
+    Sphere,
+}
+
+// Largest width or height of a bounded grid: the whole grid is allocated, and drawn in a texture of that size
+pub const MAX_BOARD: u32 = 8192;
+
+// Bounded grid declared after the rule, e.g. "B3/S23:T1280,800"
+// Unlike Golly, where 0 is an infinite dimension ("T0,100" is a tube), a size of 0 (or no size) here means
+// "use the board size": the dense and packed engines need a finite grid, "T0,100" is the board width by 100
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Topology {
+    pub kind: TopologyKind,
+    pub width: u32,
+    pub height: u32,
+    pub shift_x: i32,         // Torus only: horizontal shift applied when crossing the top/bottom edges
+    pub shift_y: i32,         // Torus only: vertical shift applied when crossing the left/right edges
+    pub twist_vertical: bool, // Klein bottle only: true when the twisted pair is left/right (`*` after the height)
+}
+
+impl Topology {
+    // Plane with a dead border, sized like the board
+    pub const fn plane() -> Self {
+        Self {
+            kind: TopologyKind::Plane,
+            width: 0,
+            height: 0,
+            shift_x: 0,
+            shift_y: 0,
+            twist_vertical: false,
+        }
+    }
+
+    pub const fn new(kind: TopologyKind, width: u32, height: u32) -> Self {
+        Self {
+            kind,
+            width,
+            height,
+            shift_x: 0,
+            shift_y: 0,
+            twist_vertical: false,
+        }
+    }
+
+    // Parse the part after ':' in a Golly rule string
+    // "P100,50", "T1280,800", "T100+5,50", "K100*,50", "K100,50*", "C80,60", "S100", "T100" (square)
+    pub fn parse(s: &str) -> Result<Self> {
+        let text: String = s.chars().filter(|c| !c.is_whitespace()).collect();
+        let mut chars = text.chars();
+        let kind = match chars.next().map(|c| c.to_ascii_uppercase()) {
+            Some('P') => TopologyKind::Plane,
+            Some('T') => TopologyKind::Torus,
+            Some('K') => TopologyKind::KleinBottle,
+            Some('C') => TopologyKind::CrossSurface,
+            Some('S') => TopologyKind::Sphere,
+            _ => return Err(format!("Invalid topology '{}': expected P, T, K, C or S followed by width,height.", s).into()),
+        };
+        let rest = chars.as_str();
+
+        let mut topology = Self::new(kind, 0, 0);
+        let (w_part, h_part) = match rest.split_once(',') {
+            Some((w, h)) => (w, Some(h)),
+            None => (rest, None),
+        };
+
+        let (width, shift_x, twist_w) = parse_dimension(w_part, s)?;
+        let (height, shift_y, twist_h) = match h_part {
+            Some(h) => parse_dimension(h, s)?,
+            None => (width, 0, false), // "T100" is a 100x100 torus
+        };
+        topology.width = width;
+        topology.height = height;
+
+        if (shift_x != 0 || shift_y != 0) && kind != TopologyKind::Torus {
+            return Err(format!("Invalid topology '{}': only a torus can have a shift.", s).into());
+        }
+        if shift_x != 0 && shift_y != 0 {
+            return Err(format!("Invalid topology '{}': only one pair of edges can be shifted.", s).into());
+        }
+        topology.shift_x = shift_x;
+        topology.shift_y = shift_y;
+
+        if (twist_w || twist_h) && kind != TopologyKind::KleinBottle {
+            return Err(format!("Invalid topology '{}': '*' is only meaningful for a Klein bottle.", s).into());
+        }
+        if twist_w && twist_h {
+            return Err(format!("Invalid topology '{}': only one pair of edges can be twisted.", s).into());
+        }
+        topology.twist_vertical = twist_h;
+
+        if kind == TopologyKind::Sphere && width != height {
+            return Err(format!("Invalid topology '{}': a sphere must be square.", s).into());
+        }
+
+        Ok(topology)
+    }
+
+    // Board size to use: declared size, or the default board size for 0 (a sphere has to be square)
+    pub fn board_size(&self, default_width: u32, default_height: u32) -> (u32, u32) {
+        let w = if self.width == 0 { default_width } else { self.width };
+        let h = if self.height == 0 { default_height } else { self.height };
+        if self.kind == TopologyKind::Sphere { (w.min(h), w.min(h)) } else { (w, h) }
+    }
+
+    // Do the edges wrap at all? (false for the plane)
+    pub fn wraps(&self) -> bool {
+        self.kind != TopologyKind::Plane
+    }
+
+    // Next kind in the cycle used by the `T` hotkey
+    pub fn next_kind(kind: TopologyKind) -> TopologyKind {
+        match kind {
+            TopologyKind::Plane => TopologyKind::Torus,
+            TopologyKind::Torus => TopologyKind::KleinBottle,
+            TopologyKind::KleinBottle => TopologyKind::CrossSurface,
+            TopologyKind::CrossSurface => TopologyKind::Sphere,
+            TopologyKind::Sphere => TopologyKind::Plane,
+        }
+    }
+
+    // Map any coordinate to the board cell it is glued to, None if it is outside and dead
+    // (x, y) may be anywhere, which lets the renderer draw "ghost" copies beyond the seams
+    #[inline]
+    pub fn map(&self, x: i64, y: i64, width: u32, height: u32) -> Option<(u32, u32)> {
+        let (w, h) = (width as i64, height as i64);
+        if x >= 0 && y >= 0 && x < w && y < h {
+            return Some((x as u32, y as u32));
+        }
+        if w == 0 || h == 0 {
+            return None;
+        }
+
+        let (x, y) = match self.kind {
+            TopologyKind::Plane => return None,
+            TopologyKind::Torus => {
+                // Cross the top/bottom edges first (they carry shift_x), then left/right (shift_y)
+                let ky = y.div_euclid(h);
+                let x = x - ky * self.shift_x as i64;
+                let kx = x.div_euclid(w);
+                let y = y - kx * self.shift_y as i64;
+                (x.rem_euclid(w), y.rem_euclid(h))
+            }
+            TopologyKind::KleinBottle => {
+                let (kx, ky) = (x.div_euclid(w), y.div_euclid(h));
+                let (mut x, mut y) = (x.rem_euclid(w), y.rem_euclid(h));
+                if self.twist_vertical {
+                    // left/right edges joined with a twist: flip y for each odd crossing
+                    if kx.rem_euclid(2) == 1 {
+                        y = h - 1 - y;
+                    }
+                } else if ky.rem_euclid(2) == 1 {
+                    // top/bottom edges joined with a twist: flip x for each odd crossing
+                    x = w - 1 - x;
+                }
+                (x, y)
+            }
+            TopologyKind::CrossSurface => {
+                let (kx, ky) = (x.div_euclid(w), y.div_euclid(h));
+                let (mut x, mut y) = (x.rem_euclid(w), y.rem_euclid(h));
+                if kx.rem_euclid(2) == 1 {
+                    y = h - 1 - y;
+                }
+                if ky.rem_euclid(2) == 1 {
+                    x = w - 1 - x;
+                }
+                (x, y)
+            }
+            TopologyKind::Sphere => {
+                // Only the cells right across a seam have a meaningful image; corners are dead
+                let n = w.min(h);
+                match (x, y) {
+                    (x, -1) if (0..n).contains(&x) => (0, x),              // top    <-> left
+                    (-1, y) if (0..n).contains(&y) => (y, 0),              // left   <-> top
+                    (x, y) if y == n && (0..n).contains(&x) => (n - 1, x), // bottom <-> right
+                    (x, y) if x == n && (0..n).contains(&y) => (y, n - 1), // right  <-> bottom
+                    _ => return None,
+                }
+            }
+        };
+        Some((x as u32, y as u32))
+    }
+}
+
+impl Default for Topology {
+    fn default() -> Self {
+        Self::plane()
+    }
+}
+
+// Golly suffix form without the ':' (e.g. "T1280,800", "K100*,50", "T100+5,50")
+impl fmt::Display for Topology {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let letter = match self.kind {
+            TopologyKind::Plane => 'P',
+            TopologyKind::Torus => 'T',
+            TopologyKind::KleinBottle => 'K',
+            TopologyKind::CrossSurface => 'C',
+            TopologyKind::Sphere => 'S',
+        };
+        write!(f, "{}{}", letter, self.width)?;
+        if self.shift_x != 0 {
+            write!(f, "{:+}", self.shift_x)?;
+        }
+        if self.kind == TopologyKind::KleinBottle && !self.twist_vertical {
+            write!(f, "*")?;
+        }
+        if self.kind == TopologyKind::Sphere {
+            return Ok(());
+        }
+        write!(f, ",{}", self.height)?;
+        if self.shift_y != 0 {
+            write!(f, "{:+}", self.shift_y)?;
+        }
+        if self.kind == TopologyKind::KleinBottle && self.twist_vertical {
+            write!(f, "*")?;
+        }
+        Ok(())
+    }
+}
+
+// --- helpers ----------------------------------------------------------------
+
+// "100+5*" -> (100, 5, true)
+fn parse_dimension(part: &str, topology: &str) -> Result<(u32, i32, bool)> {
+    let (part, twist) = match part.strip_suffix('*') {
+        Some(p) => (p, true),
+        None => (part, false),
+    };
+    let (size, shift) = match part.find(['+', '-']) {
+        Some(pos) => (&part[..pos], &part[pos..]),
+        None => (part, ""),
+    };
+    let size = if size.is_empty() {
+        0
+    } else {
+        size.parse::<u32>()
+            .map_err(|e| -> crate::Error { format!("Invalid topology '{}': bad size '{}' ({}).", topology, size, e).into() })?
+    };
+    if size > MAX_BOARD {
+        return Err(format!("Invalid topology '{}': size {} is larger than {}.", topology, size, MAX_BOARD).into());
+    }
+    let shift = if shift.is_empty() {
+        0
+    } else {
+        shift
+            .parse::<i32>()
+            .map_err(|e| -> crate::Error { format!("Invalid topology '{}': bad shift '{}' ({}).", topology, shift, e).into() })?
+    };
+    Ok((size, shift, twist))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_golly_suffixes() {
+        let t = Topology::parse("T1280,800").unwrap();
+        assert_eq!((t.kind, t.width, t.height), (TopologyKind::Torus, 1280, 800));
+
+        let t = Topology::parse("T100+5,50").unwrap();
+        assert_eq!((t.width, t.height, t.shift_x), (100, 50, 5));
+
+        let k = Topology::parse("K100,50*").unwrap();
+        assert!(k.twist_vertical);
+
+        assert_eq!(Topology::parse("S64").unwrap().board_size(0, 0), (64, 64));
+        assert!(Topology::parse("S64,32").is_err());
+        assert!(Topology::parse("C10*,10").is_err());
+        assert!(Topology::parse("X10,10").is_err());
+        assert!(Topology::parse("T100000,100000").is_err());
+        assert_eq!(Topology::parse("T0,100").unwrap().board_size(1280, 800), (1280, 100));
+    }
+
+    #[test]
+    fn display_roundtrip() {
+        for s in ["T1280,800", "P100,50", "K100*,50", "K100,50*", "C80,60", "S100", "T100+5,50", "T100,50-3"] {
+            assert_eq!(Topology::parse(s).unwrap().to_string(), s);
+        }
+    }
+
+    #[test]
+    fn map_wraps_edges() {
+        let torus = Topology::new(TopologyKind::Torus, 0, 0);
+        assert_eq!(torus.map(-1, -1, 10, 5), Some((9, 4)));
+        assert_eq!(torus.map(10, 5, 10, 5), Some((0, 0)));
+
+        let plane = Topology::plane();
+        assert_eq!(plane.map(-1, 0, 10, 5), None);
+        assert_eq!(plane.map(3, 2, 10, 5), Some((3, 2)));
+
+        // Top/bottom twisted: leaving through the top at x=2 comes back through the bottom at x=7
+        let klein = Topology::new(TopologyKind::KleinBottle, 0, 0);
+        assert_eq!(klein.map(2, -1, 10, 5), Some((7, 4)));
+        assert_eq!(klein.map(-1, 1, 10, 5), Some((9, 1)));
+
+        let sphere = Topology::new(TopologyKind::Sphere, 0, 0);
+        assert_eq!(sphere.map(3, -1, 8, 8), Some((0, 3)));
+        assert_eq!(sphere.map(8, 2, 8, 8), Some((2, 7)));
+        assert_eq!(sphere.map(-1, -1, 8, 8), None);
+    }
+}
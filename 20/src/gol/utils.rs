@@ -1,6 +1,9 @@
 // gol/utils.rs
 
 use crate::Result;
+use crate::gol::rule::Rule;
+use crate::gol::ruletable::{self, RuleTable};
+use crate::gol::topology::Topology;
 // use crate::prelude::*;
 use std::fs;
 use std::num::ParseIntError;
@@ -8,8 +11,9 @@ use std::path::Path; // see lib.rs
 
 // Place a pattern at the center of the board
 // May receive a pattern bigger than the board when the window is resized
+// Works with bool cells and with u8 cell states (Generations rules)
 // pub fn place_pattern_centered(board_current: &mut [bool], board_width: u32, board_height: u32, pattern_cells: &[bool], pattern_width: u32, pattern_height: u32) -> Result<()> {
-pub fn place_pattern_centered(board_current: &mut [bool], board_width: u32, board_height: u32, pattern_cells: &[bool], pattern_width: u32, pattern_height: u32) {
+pub fn place_pattern_centered<T: Copy>(board_current: &mut [T], board_width: u32, board_height: u32, pattern_cells: &[T], pattern_width: u32, pattern_height: u32) {
     // Make sure the board is initialized
     // if board_current.is_empty() {
     //     return Err("Board must not be empty".into());
@@ -17,18 +21,33 @@ pub fn place_pattern_centered(board_current: &mut [bool], board_width: u32, boar
     debug_assert_eq!(board_current.len(), (board_width * board_height) as usize);
 
     // Compute offsets to center the pattern
-    let offset_x = (board_width as i32 - pattern_width as i32) / 2;
-    let offset_y = (board_height as i32 - pattern_height as i32) / 2;
+    let offset_x = (board_width as i64 - pattern_width as i64) / 2;
+    let offset_y = (board_height as i64 - pattern_height as i64) / 2;
 
     // Copy the pattern while centering it
+    place_pattern_at(board_current, board_width, board_height, pattern_cells, pattern_width, pattern_height, offset_x, offset_y);
+
+    log::debug!(
+        "place_pattern_centered(): Pattern ({}x{}) centered in buffer ({}x{}).",
+        pattern_width,
+        pattern_height,
+        board_width,
+        board_height
+    );
+    // Ok(())
+}
+
+// Place a pattern with its top-left corner at (offset_x, offset_y), the cells outside of the board are dropped
+#[allow(clippy::too_many_arguments)]
+pub fn place_pattern_at<T: Copy>(board_current: &mut [T], board_width: u32, board_height: u32, pattern_cells: &[T], pattern_width: u32, pattern_height: u32, offset_x: i64, offset_y: i64) {
     for y in 0..pattern_height {
         for x in 0..pattern_width {
             let pattern_idx = (y * pattern_width + x) as usize;
-            let buffer_x = offset_x + x as i32;
-            let buffer_y = offset_y + y as i32;
+            let buffer_x = offset_x + x as i64;
+            let buffer_y = offset_y + y as i64;
 
             // Check versus buffer's limits
-            if buffer_x >= 0 && buffer_x < board_width as i32 && buffer_y >= 0 && buffer_y < board_height as i32 {
+            if buffer_x >= 0 && buffer_x < board_width as i64 && buffer_y >= 0 && buffer_y < board_height as i64 {
                 let buffer_idx = (buffer_y as u32 * board_width + buffer_x as u32) as usize;
 
                 if pattern_idx < pattern_cells.len() && buffer_idx < board_current.len() {
@@ -37,35 +56,77 @@ pub fn place_pattern_centered(board_current: &mut [bool], board_width: u32, boar
             }
         }
     }
+}
 
-    log::debug!(
-        "place_pattern_centered(): Pattern ({}x{}) centered in buffer ({}x{}).",
-        pattern_width,
-        pattern_height,
-        board_width,
-        board_height
-    );
-    // Ok(())
+// Read an RLE file and provide (pattern_cells, pattern_width, pattern_height, rule)
+// The rule defaults to Conway's Life (B3/S23) when the header does not declare one
+// Only cells in state 1 are alive, see read_rle_states() for the dying states of Generations rules
+pub fn read_rle(filename: &Path) -> Result<(Vec<bool>, u32, u32, Rule)> {
+    let (states, width, height, rule) = read_rle_states(filename)?;
+    Ok((states.iter().map(|&s| s == 1).collect(), width, height, rule))
 }
 
-// Read an RLE file and provide (pattern_cells, pattern_width, pattern_height)
-pub fn read_rle(filename: &Path) -> Result<(Vec<bool>, u32, u32)> {
+// What the '#' lines and the header of a pattern file say about it
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PatternMetadata {
+    pub name: Option<String>,              // #N
+    pub originator: Option<String>,        // #O
+    pub comments: Vec<String>,             // #C (or #c), one per line
+    pub rule: Rule,                        // rule of the header, Conway's Life when there is none
+    pub declared_size: Option<(u32, u32)>, // x and y of the header
+    pub position: Option<(i64, i64)>,      // #P, #R or "#CXRLE Pos=x,y": where the top-left corner of the pattern goes
+    pub generation: Option<u64>,           // "#CXRLE Gen=n": generation of the pattern saved by Golly
+}
+
+impl PatternMetadata {
+    // "101 by Achim Flammenkamp", None when the file has no name nor originator
+    pub fn title(&self) -> Option<String> {
+        match (&self.name, &self.originator) {
+            (Some(name), Some(originator)) => Some(format!("{} by {}", name, originator)),
+            (Some(name), None) => Some(name.clone()),
+            (None, Some(originator)) => Some(format!("by {}", originator)),
+            (None, None) => None,
+        }
+    }
+}
+
+// Same as read_rle() but every cell is a state (0 = dead, 1 = alive, 2.. = dying)
+// Two-state files use 'b'/'o', multi-state files use Golly's alphabet: '.' = 0, 'A'..'X' = 1..24, 'pA'..'yO' = 25..255
+pub fn read_rle_states(filename: &Path) -> Result<(Vec<u8>, u32, u32, Rule)> {
+    let (states, width, height, metadata) = read_rle_with_metadata(filename)?;
+    Ok((states, width, height, metadata.rule))
+}
+
+// Same as read_rle_states(), with the name, originator, comments... of the file
+pub fn read_rle_with_metadata(filename: &Path) -> Result<(Vec<u8>, u32, u32, PatternMetadata)> {
     let content = fs::read_to_string(filename).map_err(|e| -> crate::Error { format!("Failed to read RLE file '{}': {}", filename.display(), e).into() })?;
 
     let mut pattern_width: u32 = 0;
     let mut pattern_height: u32 = 0;
+    let mut rule = Rule::life();
+    let mut metadata = PatternMetadata::default();
     let mut data_lines: Vec<String> = Vec::new();
 
     // 1) Separate metadata from data; tolerate comments and empty lines
     for raw in content.lines() {
         let line = raw.trim();
-        if line.is_empty() || line.starts_with('#') {
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(comment) = line.strip_prefix('#') {
+            read_comment_line(comment, &mut metadata);
             continue;
         }
 
-        if line.starts_with('x') || line.starts_with('X') {
+        // Multi-state data lines may start with 'x' too ("xA" is state 217): the header comes first and has "x ="
+        if data_lines.is_empty() && is_rle_header(line) {
             // Header line: e.g. "x = 19, y = 11, rule = B3/S23"
-            for part in line.split(',') {
+            // The rule is always last and may itself contain commas, so split it off first
+            let (dims, rule_text) = split_rule_from_header(line);
+            if let Some(r) = rule_text {
+                rule = parse_rule(r, filename).map_err(|e| -> crate::Error { format!("Invalid rule in RLE file '{}': {}", filename.display(), e).into() })?;
+            }
+            for part in dims.split(',') {
                 let p = part.trim();
                 if let Some(v) = p.strip_prefix("x").and_then(|s| s.strip_prefix(|c: char| c.is_ascii_whitespace() || c == '='))
                     && pattern_width == 0
@@ -76,7 +137,6 @@ pub fn read_rle(filename: &Path) -> Result<(Vec<bool>, u32, u32)> {
                 {
                     pattern_height = parse_u32_trim(v)?;
                 }
-                // "rule" is ignored on purpose
             }
         } else {
             data_lines.push(line.to_string());
@@ -97,20 +157,403 @@ pub fn read_rle(filename: &Path) -> Result<(Vec<bool>, u32, u32)> {
         (pattern_width as usize, pattern_height as usize)
     };
 
-    // 3) Second pass: actually decode into a dense Vec<bool> (row-major, y-major)
-    let cells = decode_rle(&payload, w, h)?;
+    // 3) Second pass: actually decode into a dense Vec<u8> of states (row-major, y-major)
+    let cells = decode_rle(&payload, w, h, rule.states())?;
+
+    metadata.declared_size = (pattern_width != 0 && pattern_height != 0).then_some((pattern_width, pattern_height));
+    metadata.rule = rule;
+    Ok((cells, w as u32, h as u32, metadata))
+}
+
+// Read a pattern file whatever its format: by extension (.rle, .cells), by content when the extension says nothing
+// Life 1.05 and 1.06 files (.lif, .life) are told apart by their "#Life 1.0x" header
+pub fn read_pattern(filename: &Path) -> Result<(Vec<u8>, u32, u32, PatternMetadata)> {
+    let extension = filename.extension().and_then(|e| e.to_str()).map(|e| e.to_ascii_lowercase());
+    match extension.as_deref() {
+        Some("rle") => read_rle_with_metadata(filename),
+        Some("cells") => read_cells(filename),
+        _ => {
+            let content = fs::read_to_string(filename).map_err(|e| -> crate::Error { format!("Failed to read pattern file '{}': {}", filename.display(), e).into() })?;
+            let header = content.lines().map(str::trim).find(|line| !line.is_empty()).unwrap_or_default().to_ascii_lowercase();
+            if header.starts_with("#life 1.06") {
+                read_life106(filename)
+            } else if header.starts_with("#life 1.05") || matches!(extension.as_deref(), Some("lif" | "life")) {
+                read_life105(filename)
+            } else if looks_like_cells(&content) {
+                read_cells(filename)
+            } else {
+                read_rle_with_metadata(filename)
+            }
+        }
+    }
+}
+
+// Plaintext: '!' comment lines ("!Name: Glider", "!Author: ..."), then one line per row, '.' dead and 'O' alive
+// Short rows are padded with dead cells, the rule is always Conway's Life
+pub fn read_cells(filename: &Path) -> Result<(Vec<u8>, u32, u32, PatternMetadata)> {
+    let content = fs::read_to_string(filename).map_err(|e| -> crate::Error { format!("Failed to read .cells file '{}': {}", filename.display(), e).into() })?;
+    let mut metadata = PatternMetadata::default();
+    let mut rows: Vec<&str> = Vec::new();
+    for line in content.lines().map(str::trim_end) {
+        if let Some(comment) = line.strip_prefix('!') {
+            let comment = comment.trim();
+            if let Some(name) = comment.strip_prefix("Name:") {
+                metadata.name = Some(name.trim().to_string());
+            } else if let Some(author) = comment.strip_prefix("Author:") {
+                metadata.originator = Some(author.trim().to_string());
+            } else {
+                metadata.comments.push(comment.to_string());
+            }
+            continue;
+        }
+        if let Some(c) = line.chars().find(|c| !matches!(c, '.' | 'O' | '*')) {
+            return Err(format!("Invalid character '{}' in .cells file '{}'", c, filename.display()).into());
+        }
+        rows.push(line);
+    }
+    // Empty rows at the end are not part of the pattern
+    while rows.last().is_some_and(|row| row.is_empty()) {
+        rows.pop();
+    }
+
+    let width = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+    if width == 0 {
+        return Err(format!("No cells found in .cells file '{}'", filename.display()).into());
+    }
+    let mut states = vec![0u8; width * rows.len()];
+    for (y, row) in rows.iter().enumerate() {
+        for (x, c) in row.chars().enumerate() {
+            states[y * width + x] = (c != '.') as u8;
+        }
+    }
+    Ok((states, width as u32, rows.len() as u32, metadata))
+}
+
+// Life 1.05: "#Life 1.05", "#D" comments, "#N" (Conway's Life) or "#R 23/36" (survival/birth), then blocks of
+// '.' and '*' rows, each one after "#P x y", the position of its top-left corner (Golly's coordinates)
+// The blocks are merged into one pattern, positioned at the top-left corner of their bounding box
+pub fn read_life105(filename: &Path) -> Result<(Vec<u8>, u32, u32, PatternMetadata)> {
+    let content = fs::read_to_string(filename).map_err(|e| -> crate::Error { format!("Failed to read Life 1.05 file '{}': {}", filename.display(), e).into() })?;
+    let mut metadata = PatternMetadata::default();
+    let mut cells: Vec<(i64, i64)> = Vec::new();
+    let mut positioned = false;
+    // Top-left corner of the current block, and row in it
+    let (mut block_x, mut block_y, mut row) = (0, 0, 0);
+    for line in content.lines().map(str::trim) {
+        if line.is_empty() || line.to_ascii_lowercase().starts_with("#life") {
+            continue;
+        }
+        if let Some(comment) = line.strip_prefix('#') {
+            let mut chars = comment.chars();
+            let tag = chars.next().unwrap_or_default();
+            let text = chars.as_str().trim();
+            match tag {
+                'D' | 'C' => metadata.comments.push(text.to_string()),
+                'N' => metadata.rule = Rule::life(),
+                'R' => metadata.rule = parse_rule(text, filename).map_err(|e| -> crate::Error { format!("Invalid rule in Life 1.05 file '{}': {}", filename.display(), e).into() })?,
+                'P' => {
+                    let mut numbers = text.split_whitespace().map(|n| n.parse::<i64>());
+                    let (Some(Ok(x)), Some(Ok(y))) = (numbers.next(), numbers.next()) else {
+                        return Err(format!("Invalid block position '{}' in Life 1.05 file '{}'", line, filename.display()).into());
+                    };
+                    (block_x, block_y, row) = (x, y, 0);
+                    positioned = true;
+                }
+                _ => {}
+            }
+            continue;
+        }
+        for (x, c) in line.chars().enumerate() {
+            match c {
+                '*' => cells.push((block_x + x as i64, block_y + row)),
+                '.' => {}
+                _ => return Err(format!("Invalid character '{}' in Life 1.05 file '{}'", c, filename.display()).into()),
+            }
+        }
+        row += 1;
+    }
+
+    let (states, width, height, top_left) = grid_of(&cells).ok_or_else(|| -> crate::Error { format!("No cells found in Life 1.05 file '{}'", filename.display()).into() })?;
+    metadata.position = positioned.then_some(top_left);
+    Ok((states, width, height, metadata))
+}
+
+// Life 1.06: "#Life 1.06" then one "x y" line per live cell (Golly's coordinates)
+pub fn read_life106(filename: &Path) -> Result<(Vec<u8>, u32, u32, PatternMetadata)> {
+    let content = fs::read_to_string(filename).map_err(|e| -> crate::Error { format!("Failed to read Life 1.06 file '{}': {}", filename.display(), e).into() })?;
+    let mut metadata = PatternMetadata::default();
+    let mut cells: Vec<(i64, i64)> = Vec::new();
+    for line in content.lines().map(str::trim) {
+        if line.is_empty() || line.to_ascii_lowercase().starts_with("#life") {
+            continue;
+        }
+        // Not in the format, but some files carry "#D" comments anyway
+        if let Some(comment) = line.strip_prefix('#') {
+            metadata.comments.push(comment.trim_start_matches(['D', 'C']).trim().to_string());
+            continue;
+        }
+        let mut numbers = line.split_whitespace().map(|n| n.parse::<i64>());
+        let (Some(Ok(x)), Some(Ok(y)), None) = (numbers.next(), numbers.next(), numbers.next()) else {
+            return Err(format!("Invalid cell '{}' in Life 1.06 file '{}'", line, filename.display()).into());
+        };
+        cells.push((x, y));
+    }
+
+    let (states, width, height, top_left) = grid_of(&cells).ok_or_else(|| -> crate::Error { format!("No cells found in Life 1.06 file '{}'", filename.display()).into() })?;
+    metadata.position = Some(top_left);
+    Ok((states, width, height, metadata))
+}
+
+// A .cells file starts with '!' comments or a row of '.' and 'O', an RLE file with '#' comments or its header
+fn looks_like_cells(content: &str) -> bool {
+    let Some(first) = content.lines().map(str::trim).find(|line| !line.is_empty()) else {
+        return false;
+    };
+    first.starts_with('!') || first.chars().all(|c| matches!(c, '.' | 'O' | '*'))
+}
+
+// Write the cells (states, row by row) as an RLE file, see encode_rle()
+pub fn write_rle(filename: &Path, states: &[u8], width: u32, height: u32, rule: &Rule, comments: &[(char, String)]) -> Result<()> {
+    fs::write(filename, encode_rle(states, width, height, rule, comments)).map_err(|e| -> crate::Error { format!("Failed to write RLE file '{}': {}", filename.display(), e).into() })?;
+    Ok(())
+}
+
+// RLE text of the cells (states, row by row): '#' comment lines such as ('N', name), ('O', author), ('C', comment),
+// then the "x = .., y = .., rule = .." header and the runs, wrapped at 70 columns and ended by '!'
+// Two-state rules use 'b'/'o', the others Golly's '.', 'A'..'X', 'pA'..'yO' (see read_rle_states())
+// Dead cells at the end of a row are not written, empty rows go into the count of the '$' before them
+pub fn encode_rle(states: &[u8], width: u32, height: u32, rule: &Rule, comments: &[(char, String)]) -> String {
+    let multi_state = rule.states() > 2;
+    let mut runs: Vec<(usize, String)> = Vec::new();
+    let mut push = |count: usize, symbol: String| match runs.last_mut() {
+        Some((last_count, last_symbol)) if *last_symbol == symbol => *last_count += count,
+        _ => runs.push((count, symbol)),
+    };
+
+    let mut pending_rows = 0;
+    for row in states.chunks(width.max(1) as usize).take(height as usize) {
+        let Some(end) = row.iter().rposition(|&state| state != 0) else {
+            pending_rows += 1;
+            continue;
+        };
+        if pending_rows > 0 {
+            push(pending_rows, "$".to_string());
+        }
+        for &state in &row[..=end] {
+            push(1, encode_state(state, multi_state));
+        }
+        pending_rows = 1;
+    }
+    push(1, "!".to_string());
+
+    let mut rle = String::new();
+    for (tag, text) in comments {
+        for line in text.lines() {
+            rle.push_str(format!("#{} {}", tag, line).trim_end());
+            rle.push('\n');
+        }
+    }
+    rle.push_str(&format!("x = {}, y = {}, rule = {}\n", width, height, rule));
+
+    // A token (count and symbol) never straddles two lines
+    let mut line = String::new();
+    for (count, symbol) in runs {
+        let token = if count > 1 { format!("{}{}", count, symbol) } else { symbol };
+        if line.len() + token.len() > 70 {
+            rle.push_str(&line);
+            rle.push('\n');
+            line.clear();
+        }
+        line.push_str(&token);
+    }
+    rle.push_str(&line);
+    rle.push('\n');
+    rle
+}
+
+// Write the cells as a plaintext file, see encode_cells()
+pub fn write_cells(filename: &Path, states: &[u8], width: u32, height: u32, comments: &[(char, String)]) -> Result<()> {
+    fs::write(filename, encode_cells(states, width, height, comments)).map_err(|e| -> crate::Error { format!("Failed to write .cells file '{}': {}", filename.display(), e).into() })?;
+    Ok(())
+}
+
+// Plaintext of the cells: ('N', name) as "!Name:", ('O', author) as "!Author:", other comments as "!" lines, then
+// one row per line ('O' for state 1, '.' for the others, the dead cells at the end of a row are not written)
+pub fn encode_cells(states: &[u8], width: u32, height: u32, comments: &[(char, String)]) -> String {
+    let mut text = String::new();
+    for (tag, comment) in comments {
+        let prefix = match tag {
+            'N' => "!Name: ",
+            'O' => "!Author: ",
+            _ => "!",
+        };
+        for line in comment.lines() {
+            text.push_str(format!("{}{}", prefix, line).trim_end());
+            text.push('\n');
+        }
+    }
+    for row in states.chunks(width.max(1) as usize).take(height as usize) {
+        let end = row.iter().rposition(|&state| state == 1).map_or(0, |end| end + 1);
+        text.extend(row[..end].iter().map(|&state| if state == 1 { 'O' } else { '.' }));
+        text.push('\n');
+    }
+    text
+}
+
+// Write the cells as a Life 1.05 file, see encode_life105()
+pub fn write_life105(filename: &Path, states: &[u8], width: u32, height: u32, rule: &Rule, position: (i64, i64), comments: &[(char, String)]) -> Result<()> {
+    let text = encode_life105(states, width, height, rule, position, comments)?;
+    fs::write(filename, text).map_err(|e| -> crate::Error { format!("Failed to write Life 1.05 file '{}': {}", filename.display(), e).into() })?;
+    Ok(())
+}
+
+// Life 1.05 text of the cells: the header, every comment as a "#D" line (the tags are lost), "#N" for Conway's
+// Life or "#R" with the survival/birth counts, then one block at `position` ('*' for state 1, '.' for the others)
+// The format has no other rules: Generations, isotropic, hexagonal... rules are an error
+pub fn encode_life105(states: &[u8], width: u32, height: u32, rule: &Rule, position: (i64, i64), comments: &[(char, String)]) -> Result<String> {
+    if !rule.is_moore_totalistic() {
+        return Err(format!("Life 1.05 files only hold B/S rules, not {}", rule).into());
+    }
+    let mut text = String::from("#Life 1.05\n");
+    for (_, comment) in comments {
+        for line in comment.lines() {
+            text.push_str(format!("#D {}", line).trim_end());
+            text.push('\n');
+        }
+    }
+    if *rule == Rule::life() {
+        text.push_str("#N\n");
+    } else {
+        let counts = |is_set: fn(&Rule, u8) -> bool| (0..=8).filter(|&n| is_set(rule, n)).map(|n| n.to_string()).collect::<String>();
+        text.push_str(&format!("#R {}/{}\n", counts(Rule::is_survival), counts(Rule::is_birth)));
+    }
+    text.push_str(&format!("#P {} {}\n", position.0, position.1));
+    for row in states.chunks(width.max(1) as usize).take(height as usize) {
+        // An empty row is written as a single '.', a blank line would be skipped
+        let end = row.iter().rposition(|&state| state == 1).map_or(1, |end| end + 1);
+        text.extend((0..end).map(|x| if row.get(x) == Some(&1) { '*' } else { '.' }));
+        text.push('\n');
+    }
+    Ok(text)
+}
 
-    Ok((cells, w as u32, h as u32))
+// Write the cells as a Life 1.06 file, see encode_life106()
+pub fn write_life106(filename: &Path, states: &[u8], width: u32, height: u32, position: (i64, i64)) -> Result<()> {
+    fs::write(filename, encode_life106(states, width, height, position)).map_err(|e| -> crate::Error { format!("Failed to write Life 1.06 file '{}': {}", filename.display(), e).into() })?;
+    Ok(())
+}
+
+// Life 1.06 text of the cells: the header, then "x y" for every cell in state 1, the top-left one at `position`
+// No comments nor rule in this format
+pub fn encode_life106(states: &[u8], width: u32, height: u32, position: (i64, i64)) -> String {
+    let mut text = String::from("#Life 1.06\n");
+    for (y, row) in states.chunks(width.max(1) as usize).take(height as usize).enumerate() {
+        for x in row.iter().enumerate().filter(|&(_, &state)| state == 1).map(|(x, _)| x) {
+            text.push_str(&format!("{} {}\n", position.0 + x as i64, position.1 + y as i64));
+        }
+    }
+    text
 }
 
 // --- helpers ----------------------------------------------------------------
 
+// Cells (x, y) into a grid of states cropped to their bounding box: (states, width, height, top-left corner)
+// None when there are no cells
+#[allow(clippy::type_complexity)]
+fn grid_of(cells: &[(i64, i64)]) -> Option<(Vec<u8>, u32, u32, (i64, i64))> {
+    let min_x = cells.iter().map(|cell| cell.0).min()?;
+    let min_y = cells.iter().map(|cell| cell.1).min()?;
+    let width = (cells.iter().map(|cell| cell.0).max()? - min_x + 1) as usize;
+    let height = (cells.iter().map(|cell| cell.1).max()? - min_y + 1) as usize;
+    let mut states = vec![0u8; width * height];
+    for &(x, y) in cells {
+        states[(y - min_y) as usize * width + (x - min_x) as usize] = 1;
+    }
+    Some((states, width as u32, height as u32, (min_x, min_y)))
+}
+
+// Rules Rule::parse() does not know (e.g. "WireWorld") are Golly rule tables: "<name>.rule" is searched next to
+// the pattern, then in the rules/ directory alongside the pattern's directory (rle/ and rules/)
+fn parse_rule(text: &str, pattern: &Path) -> Result<Rule> {
+    let error = match Rule::parse(text) {
+        Ok(rule) => return Ok(rule),
+        Err(e) => e,
+    };
+    let (name, topology) = match text.split_once(':') {
+        Some((name, topology)) => (name.trim(), Some(topology)),
+        None => (text.trim(), None),
+    };
+    let dir = pattern.parent().unwrap_or(Path::new(""));
+    let dirs = [dir.to_path_buf(), dir.parent().unwrap_or(Path::new("")).join("rules")];
+    let Some(path) = ruletable::find_rule_file(name, &dirs) else {
+        return Err(error);
+    };
+
+    let mut rule = Rule::from_table(RuleTable::load(&path)?);
+    if let Some(topology) = topology {
+        rule = rule.with_topology(Topology::parse(topology)?);
+    }
+    log::info!("Rule {} loaded from {}", rule, path.display());
+    Ok(rule)
+}
+
+// "N 101", "O Achim Flammenkamp", "C A period 5 oscillator...", "P -1 -2", "CXRLE Pos=0,-4 Gen=3"... (the text after '#')
+// Lines of other kinds are ignored
+fn read_comment_line(line: &str, metadata: &mut PatternMetadata) {
+    let mut chars = line.chars();
+    let Some(tag) = chars.next() else {
+        return;
+    };
+    let text = chars.as_str().trim();
+    match tag {
+        'N' if !text.is_empty() => metadata.name = Some(text.to_string()),
+        'O' if !text.is_empty() => metadata.originator = Some(text.to_string()),
+        // Golly's extended RLE: "#CXRLE Pos=-3,-5 Gen=120"
+        'C' if text.starts_with("XRLE") => {
+            for field in text["XRLE".len()..].split_whitespace() {
+                if let Some((x, y)) = field.strip_prefix("Pos=").and_then(|pos| pos.split_once(','))
+                    && let (Ok(x), Ok(y)) = (x.trim().parse::<i64>(), y.trim().parse::<i64>())
+                {
+                    metadata.position = Some((x, y));
+                } else if let Some(Ok(generation)) = field.strip_prefix("Gen=").map(|n| n.parse::<u64>()) {
+                    metadata.generation = Some(generation);
+                }
+            }
+        }
+        'C' | 'c' => metadata.comments.push(text.to_string()),
+        'P' | 'R' => {
+            let mut numbers = text.split_whitespace().map(|n| n.parse::<i64>());
+            if let (Some(Ok(x)), Some(Ok(y))) = (numbers.next(), numbers.next()) {
+                metadata.position = Some((x, y));
+            }
+        }
+        _ => {}
+    }
+}
+
+// "x = 3, y = 3, ..." or "x=3,y=3"
+fn is_rle_header(line: &str) -> bool {
+    line.strip_prefix(['x', 'X']).is_some_and(|rest| rest.trim_start().starts_with('='))
+}
+
+// "x = 3, y = 3, rule = B3/S23" -> ("x = 3, y = 3, ", Some("B3/S23"))
+fn split_rule_from_header(line: &str) -> (&str, Option<&str>) {
+    match line.to_ascii_lowercase().find("rule") {
+        Some(pos) => {
+            let rule = line[pos + 4..].trim().trim_start_matches('=').trim();
+            (&line[..pos], if rule.is_empty() { None } else { Some(rule) })
+        }
+        None => (line, None),
+    }
+}
+
 fn parse_u32_trim(s: &str) -> std::result::Result<u32, ParseIntError> {
     s.trim().trim_start_matches('=').trim().parse::<u32>()
 }
 
 // Token iterator over the compact RLE stream.
-// Produces (count, symbol) where symbol in {'o','b','$','!'}; count defaults to 1.
+// Produces (count, symbol) where symbol in {'o','b','$','!'} or a multi-state letter; count defaults to 1.
+// The 'p'..'y' prefix of a two-letter state is returned alone, the caller reads the letter that follows.
 fn next_token(chars: &mut std::str::Chars<'_>) -> Option<(usize, char)> {
     let mut count: usize = 0;
     while let Some(c) = chars.clone().next() {
@@ -138,8 +581,11 @@ fn infer_dims_from_rle(s: &str) -> Result<(usize, usize)> {
     let mut height = 0usize;
 
     while let Some((n, sym)) = next_token(&mut chars) {
+        if is_state_prefix(sym) {
+            chars.next(); // second letter of the state
+        }
         match sym {
-            'o' | 'b' | 'O' | 'B' => {
+            'o' | 'b' | '.' | 'A'..='X' | 'p'..='y' => {
                 cur_len = cur_len.saturating_add(n);
                 if cur_len > max_len {
                     max_len = cur_len;
@@ -169,33 +615,47 @@ fn infer_dims_from_rle(s: &str) -> Result<(usize, usize)> {
     Ok((max_len, height))
 }
 
-// Second pass: decode into a fixed grid (w,h). Excess decoded cells beyond bounds are ignored.
-// Missing cells at end of a short line are left false (dead).
-fn decode_rle(s: &str, w: usize, h: usize) -> Result<Vec<bool>> {
-    let mut grid = vec![false; w * h];
+// Second pass: decode into a fixed grid (w,h) of states. Excess decoded cells beyond bounds are ignored.
+// Missing cells at end of a short line are left 0 (dead).
+// States the rule does not have are an error.
+fn decode_rle(s: &str, w: usize, h: usize, states: u16) -> Result<Vec<u8>> {
+    let mut grid = vec![0u8; w * h];
     let mut chars = s.chars();
 
     let mut x = 0usize;
     let mut y = 0usize;
 
     while let Some((n, sym)) = next_token(&mut chars) {
-        match sym {
-            'o' | 'O' => {
+        let state = if is_state_prefix(sym) {
+            let letter = chars.next().ok_or_else(|| -> crate::Error { format!("Truncated multi-state cell '{}' in RLE data.", sym).into() })?;
+            Some(decode_state(sym, letter, states)?)
+        } else {
+            match sym {
+                'o' => Some(1),
+                'b' | '.' => Some(0),
+                'O' | 'B' if states <= 2 => Some((sym == 'O') as u8), // lenient upper case in two-state files
+                'A'..='X' => Some(decode_state(' ', sym, states)?),
+                _ => None,
+            }
+        };
+
+        match (state, sym) {
+            (Some(0), _) => {
+                // dead cells: advance cursor, values already 0
+                x = x.saturating_add(n);
+            }
+            (Some(state), _) => {
                 for _ in 0..n {
                     if y >= h {
                         break;
                     }
                     if x < w {
-                        grid[y * w + x] = true;
+                        grid[y * w + x] = state;
                     }
                     x = x.saturating_add(1);
                 }
             }
-            'b' | 'B' => {
-                // dead cells: advance cursor, values already false
-                x = x.saturating_add(n);
-            }
-            '$' => {
+            (None, '$') => {
                 // n newlines
                 for _ in 0..n {
                     y = y.saturating_add(1);
@@ -205,7 +665,7 @@ fn decode_rle(s: &str, w: usize, h: usize) -> Result<Vec<bool>> {
                     }
                 }
             }
-            '!' => break,
+            (None, '!') => break,
             _ => {
                 // Be permissive: ignore anything else (spaces, stray commas, etc.)
             }
@@ -218,6 +678,38 @@ fn decode_rle(s: &str, w: usize, h: usize) -> Result<Vec<bool>> {
     Ok(grid)
 }
 
+// 'p'..'y' start a two-letter state (25..255)
+fn is_state_prefix(c: char) -> bool {
+    ('p'..='y').contains(&c)
+}
+
+// Multi-state letter: 'A'..'X' = 1..24, prefixed by 'p'..'y' for 25..255 (prefix ' ' = none)
+fn decode_state(prefix: char, letter: char, states: u16) -> Result<u8> {
+    if !('A'..='X').contains(&letter) {
+        return Err(format!("Invalid multi-state cell '{}{}' in RLE data.", prefix.to_string().trim(), letter).into());
+    }
+    let high = if prefix == ' ' { 0 } else { prefix as u16 - 'p' as u16 + 1 };
+    let state = high * 24 + (letter as u16 - 'A' as u16 + 1);
+    if state >= states {
+        return Err(format!("RLE cell state {} does not exist in a rule with {} states.", state, states).into());
+    }
+    Ok(state as u8)
+}
+
+// Reverse of decode_state(): 'b'/'o' in two-state files, '.', 'A'..'X' and 'pA'..'yO' otherwise
+fn encode_state(state: u8, multi_state: bool) -> String {
+    match (state, multi_state) {
+        (0, false) => "b".to_string(),
+        (_, false) => "o".to_string(),
+        (0, true) => ".".to_string(),
+        (1..=24, true) => ((b'A' + state - 1) as char).to_string(),
+        _ => {
+            let (high, letter) = ((state - 1) / 24, (state - 1) % 24);
+            format!("{}{}", (b'p' + high - 1) as char, (b'A' + letter) as char)
+        }
+    }
+}
+
 // ----------------------------------------------------------------------------
 // ----------------------------------------------------------------------------
 // ----------------------------------------------------------------------------
@@ -268,8 +760,8 @@ mod tests {
     fn place_pattern_centered_empty_board() {
         let mut board: Vec<bool> = vec![];
         let pattern = vec![true];
-        let res = place_pattern_centered(&mut board, 0, 0, &pattern, 1, 1);
-        assert!(res.is_err(), "Expected Err on empty board");
+        place_pattern_centered(&mut board, 0, 0, &pattern, 1, 1);
+        assert!(board.is_empty(), "Expected nothing placed on an empty board");
     }
 
     #[test]
@@ -289,7 +781,7 @@ mod tests {
 
         // Centered offsets should be:
         // x0 = (7-3)/2 = 2, y0 = (5-3)/2 = 1
-        place_pattern_centered(&mut board, board_w, board_h, &glider, pat_w, pat_h).expect("center pattern");
+        place_pattern_centered(&mut board, board_w, board_h, &glider, pat_w, pat_h);
 
         // Build expected board
         // let mut expected = vec![false; (board_w * board_h) as usize];
@@ -321,6 +813,16 @@ mod tests {
         assert_eq!(board, expected);
     }
 
+    #[test]
+    fn place_pattern_at_crops_outside_of_the_board() {
+        let mut board = vec![0u8; 4 * 3];
+        let pattern = [1, 2, 3, 4];
+        place_pattern_at(&mut board, 4, 3, &pattern, 2, 2, -1, 2);
+        assert_eq!(board, vec![0, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0]);
+        place_pattern_at(&mut board, 4, 3, &pattern, 2, 2, 3, 0);
+        assert_eq!(board, vec![0, 0, 0, 1, 0, 0, 0, 3, 2, 0, 0, 0]);
+    }
+
     #[test]
     fn place_pattern_centered_pattern_bigger_than_board() {
         let board_w = 4;
@@ -329,8 +831,10 @@ mod tests {
 
         // 5x1 pattern doesn't fit horizontally
         let pattern = vec![true; 5];
-        let res = place_pattern_centered(&mut board, board_w, board_h, &pattern, 5, 1);
-        assert!(res.is_ok(), "Expected no Err even if the pattern is wider than the board");
+        place_pattern_centered(&mut board, board_w, board_h, &pattern, 5, 1);
+        // The column that doesn't fit is cropped, the row lands at y = (4-1)/2 = 1
+        let expected: Vec<bool> = (0..board_w * board_h).map(|i| i / board_w == 1).collect();
+        assert_eq!(board, expected);
     }
 
     // ----------------------------------------------------------------------------
@@ -344,10 +848,11 @@ mod tests {
         // Write in temp file for testing
         std::fs::write("test_glider_001.rle", glider).unwrap();
 
-        let (cells, width, height) = read_rle(&PathBuf::from("test_glider_001.rle")).unwrap();
+        let (cells, width, height, rule) = read_rle(&PathBuf::from("test_glider_001.rle")).unwrap();
 
         assert_eq!(width, 3);
         assert_eq!(height, 3);
+        assert_eq!(rule, Rule::life());
 
         // The glider should look like:
         // . O .
@@ -365,6 +870,234 @@ mod tests {
         let _ = std::fs::remove_file("test_glider_001.rle");
     }
 
+    #[test]
+    fn read_rle_reads_rule_from_header() {
+        let highlife = "x = 3, y = 1, rule = b36/s23\n3o!";
+        std::fs::write("test_rule_001.rle", highlife).unwrap();
+
+        let (cells, width, height, rule) = read_rle(&PathBuf::from("test_rule_001.rle")).unwrap();
+
+        assert_eq!((width, height), (3, 1));
+        assert_eq!(cells, vec![true, true, true]);
+        assert_eq!(rule, Rule::parse("B36/S23").unwrap());
+
+        let _ = std::fs::remove_file("test_rule_001.rle");
+    }
+
+    #[test]
+    fn read_rle_states_decodes_multi_state_alphabet() {
+        let brain = "x = 4, y = 2, rule = B2/S/C3\n.AB$2B.A!";
+        std::fs::write("test_states_001.rle", brain).unwrap();
+
+        let (states, width, height, rule) = read_rle_states(&PathBuf::from("test_states_001.rle")).unwrap();
+        assert_eq!((width, height, rule.states()), (4, 2, 3));
+        assert_eq!(states, vec![0, 1, 2, 0, 2, 2, 0, 1]);
+
+        // read_rle() keeps the live cells only
+        let (cells, _, _, _) = read_rle(&PathBuf::from("test_states_001.rle")).unwrap();
+        assert_eq!(cells, vec![false, true, false, false, false, false, false, true]);
+        let _ = std::fs::remove_file("test_states_001.rle");
+
+        // Two-letter states, and a state the rule does not have
+        assert_eq!(decode_state('p', 'A', 256).unwrap(), 25);
+        assert_eq!(decode_state('y', 'O', 256).unwrap(), 255);
+        assert!(decode_state(' ', 'D', 3).is_err());
+    }
+
+    #[test]
+    fn read_rle_with_metadata_keeps_the_comments() {
+        let oscillator = "#N 101\n#O Achim Flammenkamp\n#C A period 5 oscillator\n#C found in August 1994.\n#P -2 3\n#Z unknown\nx = 3, y = 1, rule = B3/S23\n3o!";
+        std::fs::write("test_metadata_001.rle", oscillator).unwrap();
+        let (states, width, height, metadata) = read_rle_with_metadata(&PathBuf::from("test_metadata_001.rle")).unwrap();
+        let _ = std::fs::remove_file("test_metadata_001.rle");
+
+        assert_eq!((states, width, height), (vec![1, 1, 1], 3, 1));
+        assert_eq!(
+            metadata,
+            PatternMetadata {
+                name: Some("101".to_string()),
+                originator: Some("Achim Flammenkamp".to_string()),
+                comments: vec!["A period 5 oscillator".to_string(), "found in August 1994.".to_string()],
+                rule: Rule::life(),
+                declared_size: Some((3, 1)),
+                position: Some((-2, 3)),
+                generation: None,
+            }
+        );
+        assert_eq!(metadata.title().unwrap(), "101 by Achim Flammenkamp");
+
+        // Nothing declared
+        std::fs::write("test_metadata_002.rle", "bo$2bo$3o!").unwrap();
+        let (_, width, height, metadata) = read_rle_with_metadata(&PathBuf::from("test_metadata_002.rle")).unwrap();
+        let _ = std::fs::remove_file("test_metadata_002.rle");
+        assert_eq!((width, height), (3, 3));
+        assert_eq!(metadata, PatternMetadata::default());
+        assert_eq!(metadata.title(), None);
+
+        // Golly's extended RLE
+        std::fs::write("test_metadata_003.rle", "#CXRLE Pos=-1,-12 Gen=1234\nx = 3, y = 3, rule = B3/S23\nbo$2bo$3o!").unwrap();
+        let (_, _, _, metadata) = read_rle_with_metadata(&PathBuf::from("test_metadata_003.rle")).unwrap();
+        let _ = std::fs::remove_file("test_metadata_003.rle");
+        assert_eq!((metadata.position, metadata.generation), (Some((-1, -12)), Some(1234)));
+        assert!(metadata.comments.is_empty());
+    }
+
+    // ----------------------------------------------------------------------------
+    // .cells ---------------------------------------------------------------------
+    // ----------------------------------------------------------------------------
+
+    #[test]
+    fn read_cells_reads_rows_and_comments() {
+        let glider = "!Name: Glider\n!Author: Richard K. Guy\n!The smallest spaceship.\n.O\n..O\nOOO\n";
+        std::fs::write("test_cells_001.cells", glider).unwrap();
+        let (states, width, height, metadata) = read_cells(&PathBuf::from("test_cells_001.cells")).unwrap();
+        let _ = std::fs::remove_file("test_cells_001.cells");
+
+        assert_eq!((width, height), (3, 3));
+        assert_eq!(states, vec![0, 1, 0, 0, 0, 1, 1, 1, 1]);
+        assert_eq!(metadata.title().unwrap(), "Glider by Richard K. Guy");
+        assert_eq!(metadata.comments, vec!["The smallest spaceship.".to_string()]);
+
+        std::fs::write("test_cells_002.cells", "O.X\n").unwrap();
+        assert!(read_cells(&PathBuf::from("test_cells_002.cells")).is_err());
+        let _ = std::fs::remove_file("test_cells_002.cells");
+    }
+
+    #[test]
+    fn read_pattern_by_extension_or_content() {
+        let cells = "!Name: Blinker\nOOO\n";
+        let rle = "#N Blinker\nx = 3, y = 1\n3o!";
+        for (file, content) in [("test_sniff_001.cells", cells), ("test_sniff_002.rle", rle), ("test_sniff_003.txt", cells), ("test_sniff_004", rle)] {
+            std::fs::write(file, content).unwrap();
+            let read = read_pattern(&PathBuf::from(file));
+            let _ = std::fs::remove_file(file);
+            let (states, width, height, metadata) = read.unwrap();
+            assert_eq!((states, width, height, metadata.name.as_deref()), (vec![1, 1, 1], 3, 1, Some("Blinker")), "{}", file);
+        }
+    }
+
+    #[test]
+    fn write_cells_then_read_cells_gives_the_same_cells() {
+        let glider = vec![0, 1, 0, 0, 0, 1, 1, 1, 1, 0, 0, 0];
+        let comments = [('N', "Glider".to_string()), ('C', "saved".to_string())];
+        assert_eq!(encode_cells(&glider, 3, 4, &comments), "!Name: Glider\n!saved\n.O\n..O\nOOO\n\n");
+
+        write_cells(&PathBuf::from("test_cells_003.cells"), &glider, 3, 4, &comments).unwrap();
+        let (states, width, height, metadata) = read_cells(&PathBuf::from("test_cells_003.cells")).unwrap();
+        let _ = std::fs::remove_file("test_cells_003.cells");
+        // The empty last row is not kept
+        assert_eq!((states, width, height), (glider[..9].to_vec(), 3, 3));
+        assert_eq!((metadata.name.as_deref(), metadata.comments), (Some("Glider"), vec!["saved".to_string()]));
+    }
+
+    // ----------------------------------------------------------------------------
+    // Life 1.05 and 1.06 ---------------------------------------------------------
+    // ----------------------------------------------------------------------------
+
+    #[test]
+    fn read_life105_merges_the_blocks() {
+        let life105 = "#Life 1.05\n#D Two gliders\n#R 23/36\n#P -1 -1\n.*\n..*\n***\n#P 4 0\n*\n";
+        std::fs::write("test_life105_001.lif", life105).unwrap();
+        let read = read_pattern(&PathBuf::from("test_life105_001.lif"));
+        let _ = std::fs::remove_file("test_life105_001.lif");
+        let (states, width, height, metadata) = read.unwrap();
+
+        assert_eq!((width, height, metadata.position), (6, 3, Some((-1, -1))));
+        assert_eq!(states, vec![0, 1, 0, 0, 0, 0, 0, 0, 1, 0, 0, 1, 1, 1, 1, 0, 0, 0]);
+        assert_eq!(metadata.rule, Rule::parse("B36/S23").unwrap());
+        assert_eq!(metadata.comments, vec!["Two gliders".to_string()]);
+    }
+
+    #[test]
+    fn read_life106_reads_the_coordinates() {
+        let life106 = "#Life 1.06\n0 -1\n1 0\n-1 1\n0 1\n1 1\n";
+        std::fs::write("test_life106_001.life", life106).unwrap();
+        let read = read_pattern(&PathBuf::from("test_life106_001.life"));
+        let _ = std::fs::remove_file("test_life106_001.life");
+        let (states, width, height, metadata) = read.unwrap();
+
+        assert_eq!((width, height, metadata.position), (3, 3, Some((-1, -1))));
+        assert_eq!(states, vec![0, 1, 0, 0, 0, 1, 1, 1, 1]);
+        assert_eq!(metadata.rule, Rule::life());
+
+        std::fs::write("test_life106_002.life", "#Life 1.06\n0 zero\n").unwrap();
+        assert!(read_life106(&PathBuf::from("test_life106_002.life")).is_err());
+        let _ = std::fs::remove_file("test_life106_002.life");
+    }
+
+    #[test]
+    fn write_life_then_read_life_gives_the_same_cells() {
+        let glider = vec![0, 1, 0, 0, 0, 1, 1, 1, 1];
+        let highlife = Rule::parse("B36/S23").unwrap();
+        let comments = [('N', "Glider".to_string())];
+        assert_eq!(
+            encode_life105(&glider, 3, 3, &highlife, (5, -2), &comments).unwrap(),
+            "#Life 1.05\n#D Glider\n#R 23/36\n#P 5 -2\n.*\n..*\n***\n"
+        );
+        assert!(encode_life105(&glider, 3, 3, &Rule::parse("B2/S/C3").unwrap(), (0, 0), &[]).is_err());
+        assert_eq!(encode_life106(&glider, 3, 3, (5, -2)), "#Life 1.06\n6 -2\n7 -1\n5 0\n6 0\n7 0\n");
+
+        write_life105(&PathBuf::from("test_life105_002.lif"), &glider, 3, 3, &highlife, (5, -2), &comments).unwrap();
+        write_life106(&PathBuf::from("test_life106_003.life"), &glider, 3, 3, (5, -2)).unwrap();
+        let life105 = read_pattern(&PathBuf::from("test_life105_002.lif"));
+        let life106 = read_pattern(&PathBuf::from("test_life106_003.life"));
+        let _ = std::fs::remove_file("test_life105_002.lif");
+        let _ = std::fs::remove_file("test_life106_003.life");
+
+        let (states, width, height, metadata) = life105.unwrap();
+        assert_eq!((states, width, height, metadata.position, metadata.rule), (glider.clone(), 3, 3, Some((5, -2)), highlife));
+        let (states, width, height, metadata) = life106.unwrap();
+        assert_eq!((states, width, height, metadata.position), (glider, 3, 3, Some((5, -2))));
+    }
+
+    // ----------------------------------------------------------------------------
+    // write_rle ------------------------------------------------------------------
+    // ----------------------------------------------------------------------------
+
+    #[test]
+    fn write_rle_encodes_runs_and_rows() {
+        // Glider, an empty row, then a wide row
+        let glider = bools(&[&[0, 1, 0, 0], &[0, 0, 1, 0], &[1, 1, 1, 0], &[0, 0, 0, 0], &[1, 1, 1, 1]]);
+        let states: Vec<u8> = glider.iter().map(|&alive| alive as u8).collect();
+        let comments = [('N', "Glider".to_string()), ('C', "two lines\nof comment".to_string())];
+        let rle = encode_rle(&states, 4, 5, &Rule::life(), &comments);
+        assert_eq!(rle, "#N Glider\n#C two lines\n#C of comment\nx = 4, y = 5, rule = B3/S23\nbo$2bo$3o2$4o!\n");
+
+        // Long rows wrap at 70 columns without cutting a run
+        let stripes: Vec<u8> = (0..200).map(|i| (i % 2) as u8).collect();
+        let rle = encode_rle(&stripes, 200, 1, &Rule::life(), &[]);
+        assert!(rle.lines().all(|line| line.len() <= 70));
+        assert!(rle.lines().skip(1).all(|line| line.starts_with('b') || line.starts_with('o') || line == "!"));
+    }
+
+    #[test]
+    fn write_rle_then_read_rle_gives_the_same_cells() {
+        let brain = Rule::parse("B2/S/C3").unwrap();
+        let states = vec![0, 1, 2, 0, 0, 0, 0, 0, 2, 2, 0, 1];
+        std::fs::write("test_write_001.rle", encode_rle(&states, 4, 3, &brain, &[])).unwrap();
+        let read = read_rle_states(&PathBuf::from("test_write_001.rle")).unwrap();
+        let _ = std::fs::remove_file("test_write_001.rle");
+        assert_eq!(read, (states, 4, 3, brain));
+        assert_eq!(encode_state(25, true), "pA");
+        assert_eq!(encode_state(255, true), "yO");
+
+        // States 217 to 240 are written "xA".."xX", a data line starting with 'x' is not a header
+        let many = Rule::parse("/2/256").unwrap();
+        let states = vec![217, 1, 0, 217];
+        assert_eq!(encode_rle(&states, 2, 2, &many, &[]), "x = 2, y = 2, rule = B2/S/C256\nxAA$.xA!\n");
+        std::fs::write("test_write_003.rle", encode_rle(&states, 2, 2, &many, &[])).unwrap();
+        let read = read_rle_states(&PathBuf::from("test_write_003.rle"));
+        let _ = std::fs::remove_file("test_write_003.rle");
+        assert_eq!(read.unwrap(), (states, 2, 2, many));
+
+        let glider = bools(&[&[0, 1, 0], &[0, 0, 1], &[1, 1, 1]]);
+        let states: Vec<u8> = glider.iter().map(|&alive| alive as u8).collect();
+        write_rle(&PathBuf::from("test_write_002.rle"), &states, 3, 3, &Rule::life(), &[('O', "me".to_string())]).unwrap();
+        let (cells, width, height, rule) = read_rle(&PathBuf::from("test_write_002.rle")).unwrap();
+        let _ = std::fs::remove_file("test_write_002.rle");
+        assert_eq!((cells, width, height, rule), (glider, 3, 3, Rule::life()));
+    }
+
     //     #[test]
     //     fn read_rle_parses_header_and_data() {
     //         // 3x3 glider in RLE with header
@@ -521,7 +1254,7 @@ pub fn get_random_pattern(dir: &str) -> Result<std::path::PathBuf> {
         return Err(err_msg.into());
     }
 
-    // Read all .rle files in the directory
+    // Read all pattern files (.rle, .cells, .lif, .life) in the directory
     let entries = fs::read_dir(dir_path).map_err(|e| {
         let err_msg = format!("Failed to read directory {:?}: {}", dir, e);
         log::error!("{}", err_msg);
@@ -531,12 +1264,12 @@ pub fn get_random_pattern(dir: &str) -> Result<std::path::PathBuf> {
     let rle_files: Vec<std::path::PathBuf> = entries
         .filter_map(|entry| entry.ok())
         .map(|entry| entry.path())
-        .filter(|path| path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("rle"))
+        .filter(|path| path.is_file() && matches!(path.extension().and_then(|s| s.to_str()), Some("rle" | "cells" | "lif" | "life")))
         .collect();
 
-    // Check if we found any .rle files
+    // Check if we found any pattern files
     if rle_files.is_empty() {
-        let err_msg = format!("No .rle files found in directory: {:?}", dir);
+        let err_msg = format!("No pattern files (.rle, .cells, .lif, .life) found in directory: {:?}", dir);
         log::error!("{}", err_msg);
         return Err(err_msg.into());
     }
@@ -0,0 +1,69 @@
+// gol/workers.rs
+
+use crate::Result;
+
+// Worker threads the parallel steppers hand their bands (or groups of tiles) to.
+// They are started once, by App::set_threads(), and wait between generations: spawning threads at every
+// generation costs more than stepping a small or quiet board.
+// With 1 thread there is no pool, the steppers compute everything on the calling thread.
+pub struct Workers {
+    pool: Option<rayon::ThreadPool>,
+}
+
+impl Workers {
+    pub fn new(threads: usize) -> Result<Self> {
+        if threads <= 1 {
+            return Ok(Self::single());
+        }
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .thread_name(|i| format!("gol-worker-{}", i))
+            .build()
+            .map_err(|e| -> crate::Error { format!("Failed to start {} worker threads: {}", threads, e).into() })?;
+        Ok(Self { pool: Some(pool) })
+    }
+
+    // No pool, the calling thread does the work
+    pub const fn single() -> Self {
+        Self { pool: None }
+    }
+
+    pub fn threads(&self) -> usize {
+        self.pool.as_ref().map_or(1, |pool| pool.current_num_threads())
+    }
+
+    // Run `op`, the jobs it spawns go to the workers, and return once they are all done
+    // Without a pool (single()) the steppers do not spawn anything, the jobs would go to rayon's global pool
+    pub fn scope<'scope, OP>(&self, op: OP)
+    where
+        OP: FnOnce(&rayon::Scope<'scope>) + Send,
+    {
+        match &self.pool {
+            Some(pool) => pool.scope(op),
+            None => rayon::in_place_scope(op),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn jobs_run_on_the_workers() {
+        let workers = Workers::new(3).unwrap();
+        assert_eq!(workers.threads(), 3);
+        assert_eq!(Workers::new(0).unwrap().threads(), 1);
+
+        let mut bands = vec![0usize; 12];
+        // Twice: the same threads take the jobs of the next generation
+        for _ in 0..2 {
+            workers.scope(|scope| {
+                for (i, band) in bands.chunks_mut(4).enumerate() {
+                    scope.spawn(move |_| band.iter_mut().for_each(|cell| *cell += i + 1));
+                }
+            });
+        }
+        assert_eq!(bands, vec![2, 2, 2, 2, 4, 4, 4, 4, 6, 6, 6, 6]);
+    }
+}
@@ -5,7 +5,11 @@ use clap::{Arg, Command};
 use flexi_logger::Logger;
 use std::fs::File;
 use std::path::{Path, PathBuf};
-use step_20::{Result, app::state::App};
+use step_20::{
+    Result,
+    app::state::{App, Engine, Placement},
+    gol::soup::Symmetry,
+};
 use winit::event_loop::{ControlFlow, EventLoop};
 
 fn main() -> Result<()> {
@@ -13,9 +17,9 @@ fn main() -> Result<()> {
     log::info!("Logger initialized.");
 
     // Handle parameters and exit gracefully on error
-    let pattern_path = match handle_parameters() {
+    let params = match handle_parameters() {
         Ok(p) => {
-            log::info!("Using pattern file: {}", p.display());
+            log::info!("Using pattern file: {}", p.pattern_path.display());
             p
         }
         Err(e) => {
@@ -24,17 +28,56 @@ fn main() -> Result<()> {
         }
     };
 
-    // if pattern
+    let mut app = App::try_new(&params.pattern_path, params.placement)?;
+    app.set_engine(params.engine);
+    app.set_threads(params.threads);
+    app.soup.density = params.density;
+    app.soup.symmetry = params.symmetry;
+    if let Some(size) = params.soup {
+        app.soup_size = (size > 0).then_some(size);
+        app.load_soup(params.seed)?;
+    }
+
+    // Headless: no window, the answer goes to stdout
+    if let Some(max_generations) = params.detect {
+        app.set_history_budget(0);
+        match app.run_until_repeat(max_generations) {
+            Some((generation, periodicity)) => println!(
+                "{}: {} (from generation {}, repeated at generation {})",
+                params.pattern_path.display(),
+                periodicity,
+                generation - periodicity.period(),
+                generation
+            ),
+            None => println!("{}: no repeat within {} generations", params.pattern_path.display(), max_generations),
+        }
+        return Ok(());
+    }
+    if let Some(max_generations) = params.lifespan {
+        if let Some(lifespan) = app.analyse_lifespan(max_generations) {
+            println!("{}: {}", params.pattern_path.display(), lifespan.to_string().replace('\n', "\n  "));
+        }
+        return Ok(());
+    }
 
     let event_loop = EventLoop::new()?;
     event_loop.set_control_flow(ControlFlow::Poll);
 
-    let mut app = App::try_new(&pattern_path)?;
+    app.set_history_budget(params.history_mb);
+    app.set_speed(params.speed);
     log::info!("App initialized successfully, starting event loop...");
 
+    let export_stats = params.stats_csv.is_some();
+    if let Some(path) = params.stats_csv {
+        app.stats_csv = path;
+    }
+
     if let Err(e) = event_loop.run_app(&mut app) {
         log::error!("Application error: {:?}", e);
     }
+    if export_stats {
+        app.export_stats();
+    }
 
     log::info!("Application terminated.");
     Ok(())
@@ -53,8 +96,25 @@ fn setup_logging() -> Result<()> {
     Ok(())
 }
 
+// Values collected from the command line
+struct Params {
+    pattern_path: PathBuf,
+    engine: Engine,
+    threads: usize,
+    history_mb: usize,
+    speed: f64,
+    stats_csv: Option<PathBuf>,
+    detect: Option<u64>,
+    lifespan: Option<u64>,
+    soup: Option<u32>,
+    density: f64,
+    symmetry: Symmetry,
+    seed: Option<u64>,
+    placement: Placement,
+}
+
 // Handle CLI parameters and return parsed values if valid
-fn handle_parameters() -> Result<PathBuf> {
+fn handle_parameters() -> Result<Params> {
     let cli = Command::new("step_13")
         .version("0.1.0")
         .author("Philippe <philippe@gmail.com>")
@@ -65,18 +125,122 @@ fn handle_parameters() -> Result<PathBuf> {
                 .long("pattern")
                 .value_name("PATTERN")
                 .value_parser(clap::value_parser!(PathBuf)) // specify the PathBuf type
-                .help("Path to the pattern file without .rle extension (e.g. \"rle/gosperglidergun\")")
+                .help("Path to the pattern file, .rle, .cells, .lif or .life (Life 1.05 or 1.06), the extension may be omitted (e.g. \"rle/gosperglidergun\")")
                 .required(false),
         )
+        .arg(
+            Arg::new("engine")
+                .short('e')
+                .long("engine")
+                .value_name("ENGINE")
+                .value_parser(["dense", "packed", "hashlife", "sparse"])
+                .default_value("dense")
+                .help(
+                    "Simulation engine: \"dense\" (fixed board, one bool per cell), \"packed\" (fixed board, 64 cells per u64), \"hashlife\" (unbounded quadtree, use [ and ] to jump 2^k generations) or \"sparse\" (unbounded, tiles allocated around live cells). Press E to switch engine while running",
+                ),
+        )
+        .arg(
+            Arg::new("threads")
+                .short('t')
+                .long("threads")
+                .value_name("N")
+                .value_parser(clap::value_parser!(usize))
+                .default_value("1")
+                .help("Number of threads stepping the board in horizontal bands (0 = one per core)"),
+        )
+        .arg(
+            Arg::new("history-mb")
+                .long("history-mb")
+                .value_name("MB")
+                .value_parser(clap::value_parser!(usize))
+                .default_value("64")
+                .help("Memory kept for the previous generations (Backspace steps back, 0 = no history)"),
+        )
+        .arg(
+            Arg::new("speed")
+                .short('s')
+                .long("speed")
+                .value_name("GEN_PER_SEC")
+                .value_parser(clap::value_parser!(f64))
+                .default_value("60")
+                .help("Generations per second, from 1 (slow motion) to 61440 (1024 per frame). Change it with , and . while running, W for warp"),
+        )
+        .arg(
+            Arg::new("stats-csv")
+                .long("stats-csv")
+                .value_name("FILE")
+                .value_parser(clap::value_parser!(PathBuf))
+                .help("Write the population statistics (generation, population, births, deaths and the generations they span, bounding box) to this CSV file on exit and with Ctrl+E"),
+        )
+        .arg(
+            Arg::new("detect")
+                .long("detect")
+                .value_name("GENERATIONS")
+                .value_parser(clap::value_parser!(u64))
+                .help("Headless: run the pattern until it becomes a still life, an oscillator or a spaceship (at most GENERATIONS generations), print what it is and exit"),
+        )
+        .arg(
+            Arg::new("lifespan")
+                .long("lifespan")
+                .value_name("GENERATIONS")
+                .value_parser(clap::value_parser!(u64))
+                .help("Headless: run the pattern until it stabilizes (at most GENERATIONS generations), print the stabilization generation, final and maximum population, bounding box and escaped gliders, and exit"),
+        )
+        .arg(
+            Arg::new("soup")
+                .long("soup")
+                .value_name("SIZE")
+                .value_parser(clap::value_parser!(u32))
+                .help("Start with a random soup filling the centred SIZE x SIZE square instead of the pattern (0 = the whole board). Press S for a new one"),
+        )
+        .arg(
+            Arg::new("density")
+                .long("density")
+                .value_name("P")
+                .value_parser(clap::value_parser!(f64))
+                .default_value("0.5")
+                .help("Probability of a live cell in a soup, from 0 to 1"),
+        )
+        .arg(
+            Arg::new("symmetry")
+                .long("symmetry")
+                .value_name("SYMMETRY")
+                .value_parser(["C1", "C2", "C4", "D2", "D4", "D8"])
+                .ignore_case(true)
+                .default_value("C1")
+                .help("Symmetry of the soups: C1 (none), C2 (half turn), C4 (quarter turns), D2 (mirror), D4 (two mirrors) or D8 (all of them). C4 and D8 need a square"),
+        )
+        .arg(
+            Arg::new("seed")
+                .long("seed")
+                .value_name("SEED")
+                .value_parser(clap::value_parser!(u64))
+                .help("Seed of the --soup random generator, the seed of every soup is logged so it can be made again"),
+        )
+        .arg(
+            Arg::new("placement")
+                .long("placement")
+                .value_name("PLACEMENT")
+                .value_parser(["auto", "centred", "absolute", "cursor"])
+                .default_value("auto")
+                .help("Where patterns go: \"centred\" on the board, \"absolute\" at the position the file declares (#P, #R, #CXRLE Pos) with Golly's coordinates, \"cursor\" under the mouse, \"auto\" absolute when the file declares a position, centred otherwise. Press P to change it"),
+        )
         .after_help("Example: step_11 --pattern rle/canadagoose");
 
     let matches = cli.clone().get_matches();
 
-    // Try to get and parse the path to .rle
+    // Try to get and parse the path to the pattern: as given, or with a pattern file extension added
     let path_to_pattern = match matches.get_one::<PathBuf>("pattern") {
         Some(p) => {
             let mut path = p.clone(); // Clone to get an owned PathBuf
-            path.set_extension("rle");
+            if !is_valid_file_path(&path)
+                && let Some(found) = ["rle", "cells", "lif", "life"]
+                    .iter()
+                    .map(|extension| p.with_extension(extension))
+                    .find(|candidate| is_valid_file_path(candidate))
+            {
+                path = found;
+            }
             if !is_valid_file_path(&path) {
                 let err_msg = format!("Invalid path to pattern file: {:?}", path);
                 log::error!("{err_msg}");
@@ -93,7 +257,35 @@ fn handle_parameters() -> Result<PathBuf> {
         }
     };
 
-    Ok(path_to_pattern)
+    let engine = match matches.get_one::<String>("engine").map(String::as_str) {
+        Some("packed") => Engine::BitPacked,
+        Some("hashlife") => Engine::HashLife,
+        Some("sparse") => Engine::Sparse,
+        _ => Engine::Dense,
+    };
+
+    let threads = matches.get_one::<usize>("threads").copied().unwrap_or(1);
+    let speed = matches.get_one::<f64>("speed").copied().unwrap_or(step_20::config::SPEED_DEFAULT);
+    let history_mb = matches.get_one::<usize>("history-mb").copied().unwrap_or(step_20::config::HISTORY_BUDGET_MB);
+
+    Ok(Params {
+        pattern_path: path_to_pattern,
+        engine,
+        threads,
+        history_mb,
+        speed,
+        stats_csv: matches.get_one::<PathBuf>("stats-csv").cloned(),
+        detect: matches.get_one::<u64>("detect").copied(),
+        lifespan: matches.get_one::<u64>("lifespan").copied(),
+        soup: matches.get_one::<u32>("soup").copied(),
+        density: matches.get_one::<f64>("density").copied().unwrap_or(step_20::config::SOUP_DENSITY),
+        symmetry: matches.get_one::<String>("symmetry").map_or(Ok(Symmetry::C1), |s| s.parse())?,
+        seed: matches.get_one::<u64>("seed").copied(),
+        placement: Placement::ALL
+            .into_iter()
+            .find(|placement| matches.get_one::<String>("placement").is_some_and(|name| name == placement.name()))
+            .unwrap_or(Placement::Auto),
+    })
 }
 
 // Check if the path points to a valid file